@@ -0,0 +1,716 @@
+// BLE Scanner 无界面命令行，与桌面应用共用 blescanner-core
+
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use blescanner_core::aging::{self, AgingPolicy};
+use blescanner_core::coalesce::{self, CoalesceSettings};
+use blescanner_core::connection::{self, ReconnectPolicy};
+use blescanner_core::rssi;
+use blescanner_core::scan::StartScanArgs;
+use blescanner_core::filter::{self, ManufacturerFilter, ScanFilter};
+use blescanner_core::decoder;
+use blescanner_core::history::{self, HistoryQuery};
+use blescanner_core::long_write::{self, LongWriteMode, LongWriteOptions};
+use blescanner_core::notifications::{self, RecordingFormat};
+use blescanner_core::payload::PayloadEncoding;
+use blescanner_core::snapshot::{self, SnapshotFormat};
+use blescanner_core::subscriptions;
+use blescanner_core::{backend, device, gatt, scan, state, AppState, EventSink};
+
+mod output;
+
+use output::Output;
+
+const USAGE: &str = "用法: blescanner-cli [--backend simpleble|sim] [--json] [--scan 秒] [--adapter ID|all]... [--history 文件]
+                     [--decoders 文件] [--stale-after 秒] [--lost-after 秒] [--coalesce 毫秒] [过滤选项] <命令> [参数]
+
+命令:
+  adapters                                      列出蓝牙适配器
+  scan [--duration 秒]                          实时输出发现的设备（0 表示持续扫描，Ctrl-C 结束）
+  list [--duration 秒]                          扫描结束后输出设备表
+  connect <设备ID>                              连接并列出 GATT 服务
+  watch <设备ID> [--reconnect] [--max-attempts N]
+                                                保持连接并输出连接状态变化（--reconnect 启用自动重连）
+  pair <设备ID> / unpair <设备ID>               配对 / 解除配对
+  read <设备ID> <服务UUID> <特征UUID>
+  write <设备ID> <服务UUID> <特征UUID> <数据|--file 文件> [--encoding 编码] [--command] [--chunked|--prepared|--reliable] [--interval 毫秒] [--verify]
+                                                按 MTU 分块写入长值并输出进度（--interval 为写命令间隔，--verify 写后回读校验）
+  notify <设备ID> <服务UUID> <特征UUID> [--indicate] [--count N] [--record 文件] [--format csv|jsonl|binary]
+         [--stats] [--seq 偏移:类型]
+                                                订阅并输出通知（--record 同时录制，格式默认按扩展名 .csv/.jsonl/.bin；
+                                                --stats 每秒输出吞吐统计、结束时输出汇总，--seq 如 0:u8 / 2:u16le 按序号统计丢包）
+  replay <文件> [--speed 倍速]                   按原始间隔回放录制的通知（--speed 2 为两倍速，0 为不等待）
+  read-descriptor <设备ID> <服务UUID> <特征UUID> <描述符UUID>
+  write-descriptor <设备ID> <服务UUID> <特征UUID> <描述符UUID> <数据> [--encoding 编码]
+  mtu <设备ID>
+  snapshot <设备ID> [--values] [--output 文件] [--yaml]
+                                                导出 GATT 快照（--values 同时读取可读特征值；文件扩展名 .yaml/.yml 为 YAML）
+  snapshot diff <旧快照> <新快照|设备ID>         比较两份快照或快照与设备当前 GATT 表，有差异时退出码非 0
+  rssi <设备ID> [--duration 秒] [--calibrate 米]
+                                                扫描并输出 RSSI 统计与距离估计（--calibrate 在已知距离处校准）
+  history [--address 前缀] [--company 厂商ID] [--name 名称] [--limit N]
+                                                查询历史设备（厂商ID为十六进制，如 004C）
+  history show <地址> [--limit N]               单个设备的 RSSI 与 GATT 历史
+  history prune --days N                        删除 N 天未更新的历史记录
+  decoders                                      列出已加载的厂商数据解码器
+
+设备操作前会先扫描 --scan 秒（默认 5）以发现目标设备。
+--encoding 指定写入数据编码：hex / base64 / utf8、u8 / i8 / u16le / i32be / f32le / f64be 等数值类型（多个值以空格或逗号分隔），
+  或 struct:u8,u16le,hex 按字段模板编码；未指定时按 UTF-8，auto 表示全为十六进制字符按十六进制，否则按 UTF-8。
+--adapter 可重复指定多个适配器同时扫描，all 表示全部；连接时使用第一个指定的适配器。
+--stale-after / --lost-after 设置设备老化超时（默认 30 / 120 秒，0 表示关闭），scan 会输出 device-stale / device-lost。
+--coalesce 按窗口合并设备更新并输出 devices-updated 差异（默认 0，即每次广播输出一行）。
+过滤选项（在扫描循环中求值，不匹配的设备不会出现在结果中）:
+  --filter-name 正则 / --filter-address 前缀 / --filter-service UUID（可重复）
+  --filter-company 厂商ID[:数据[/掩码]]（十六进制，如 004C:0215）/ --min-rssi dBm / --connectable
+--history 指定设备历史数据库（默认取环境变量 BLESCANNER_HISTORY），指定后扫描结果会持久化。
+--decoders 指定厂商数据解码器布局文件（默认取环境变量 BLESCANNER_DECODERS），格式见 decoders.example.json。";
+
+// 把核心库事件转入通道，由主循环决定打印与退出时机
+struct ChannelSink(UnboundedSender<(String, serde_json::Value)>);
+
+impl EventSink for ChannelSink {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        self.0.send((event.to_string(), payload)).map_err(|e| e.to_string())
+    }
+}
+
+struct Args {
+    json: bool,
+    backend: Option<String>,
+    scan_secs: u64,
+    adapters: Vec<String>,
+    history: Option<String>,
+    decoders: Option<String>,
+    aging: AgingPolicy,
+    coalesce_ms: u64,
+    flags: Vec<String>,
+    options: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse(raw: Vec<String>) -> Result<Self, String> {
+        let mut args = Args { json: false, backend: None, scan_secs: 5, adapters: Vec::new(), history: None, decoders: None, aging: AgingPolicy::default(), coalesce_ms: 0, flags: Vec::new(), options: Vec::new(), positional: Vec::new() };
+        let mut it = raw.into_iter();
+        while let Some(a) = it.next() {
+            match a.as_str() {
+                "--json" => args.json = true,
+                "--backend" => args.backend = Some(it.next().ok_or("--backend 需要参数")?),
+                "--scan" => args.scan_secs = parse_num(&it.next().ok_or("--scan 需要参数")?)?,
+                "--adapter" => args.adapters.push(it.next().ok_or("--adapter 需要参数")?),
+                "--stale-after" => args.aging.stale_after_secs = parse_num(&it.next().ok_or("--stale-after 需要参数")?)?,
+                "--lost-after" => args.aging.lost_after_secs = parse_num(&it.next().ok_or("--lost-after 需要参数")?)?,
+                "--coalesce" => args.coalesce_ms = parse_num(&it.next().ok_or("--coalesce 需要参数")?)?,
+                "--history" => args.history = Some(it.next().ok_or("--history 需要参数")?),
+                "--decoders" => args.decoders = Some(it.next().ok_or("--decoders 需要参数")?),
+                "--command" | "--indicate" | "--reconnect" | "--connectable" | "--values" | "--yaml"
+                | "--chunked" | "--prepared" | "--reliable" | "--verify" | "--stats" => args.flags.push(a),
+                "--duration" | "--count" | "--max-attempts" | "--address" | "--company" | "--name" | "--limit" | "--days" | "--calibrate" | "--output" | "--file" | "--interval" | "--encoding" | "--record" | "--format" | "--speed" | "--seq"
+                | "--filter-name" | "--filter-address" | "--filter-service" | "--filter-company" | "--min-rssi" => {
+                    let v = it.next().ok_or_else(|| format!("{} 需要参数", a))?;
+                    args.options.push((a, v));
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ if a.starts_with("--") => return Err(format!("未知选项: {}\n\n{}", a, USAGE)),
+                _ => args.positional.push(a),
+            }
+        }
+        Ok(args)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn option_num(&self, name: &str) -> Result<Option<u64>, String> {
+        self.option(name).map(parse_num).transpose()
+    }
+
+    fn encoding(&self) -> Result<Option<PayloadEncoding>, String> {
+        self.option("--encoding").map(str::parse).transpose()
+    }
+
+    // 由过滤选项组成扫描过滤器；没有任何过滤选项时返回 None
+    fn scan_filter(&self) -> Result<Option<ScanFilter>, String> {
+        let mut spec = ScanFilter {
+            name: self.option("--filter-name").map(str::to_string),
+            address_prefix: self.option("--filter-address").map(str::to_string),
+            service_uuids: self.options.iter().filter(|(k, _)| k == "--filter-service").map(|(_, v)| v.clone()).collect(),
+            connectable_only: self.flag("--connectable"),
+            ..Default::default()
+        };
+        if let Some(v) = self.option("--min-rssi") {
+            spec.min_rssi = Some(v.parse::<i16>().map_err(|_| format!("无效的 RSSI: {}", v))?);
+        }
+        if let Some(v) = self.option("--filter-company") {
+            let (company, rest) = v.split_once(':').map_or((v, None), |(c, r)| (c, Some(r)));
+            let (data, mask) = match rest {
+                Some(r) => r.split_once('/').map_or((Some(r), None), |(d, m)| (Some(d), Some(m))),
+                None => (None, None),
+            };
+            spec.manufacturer = Some(ManufacturerFilter {
+                company_id: u16::from_str_radix(company.trim_start_matches("0x"), 16).map_err(|_| format!("无效的厂商ID: {}", company))?,
+                data: data.map(str::to_string),
+                mask: mask.map(str::to_string),
+            });
+        }
+        let empty = spec.name.is_none()
+            && spec.address_prefix.is_none()
+            && spec.service_uuids.is_empty()
+            && spec.manufacturer.is_none()
+            && spec.min_rssi.is_none()
+            && !spec.connectable_only;
+        Ok(if empty { None } else { Some(spec) })
+    }
+
+    fn pos(&self, index: usize, what: &str) -> Result<String, String> {
+        self.positional.get(index).cloned().ok_or_else(|| format!("缺少参数: {}\n\n{}", what, USAGE))
+    }
+}
+
+fn parse_num(s: &str) -> Result<u64, String> {
+    s.parse::<u64>().map_err(|_| format!("无效的数字: {}", s))
+}
+
+struct Cli {
+    state: AppState,
+    sink: Arc<dyn EventSink>,
+    rx: UnboundedReceiver<(String, serde_json::Value)>,
+    out: Output,
+    adapters: Vec<String>,
+}
+
+impl Cli {
+    // 扫描指定秒数并等待扫描任务结束；print 为 true 时实时打印发现的设备
+    async fn scan(&mut self, secs: u64, print: bool) -> Result<(), String> {
+        let handle = scan::start_scan(&self.state, self.sink.clone(), StartScanArgs {
+            duration_secs: Some(secs),
+            adapter_ids: Some(self.adapters.clone()),
+            filter: None,
+        }).await?;
+        if print {
+            self.out.device_header();
+        }
+        loop {
+            tokio::select! {
+                msg = self.rx.recv() => match msg {
+                    Some((event, payload)) => {
+                        let done = event == "scan-completed";
+                        if print || event == "scan-error" {
+                            self.out.event(&event, &payload);
+                        }
+                        if done {
+                            break;
+                        }
+                    }
+                    None => break,
+                },
+                _ = tokio::signal::ctrl_c() => {
+                    scan::stop_scan(&self.state).await?;
+                    break;
+                }
+            }
+        }
+        let _ = handle.await;
+        Ok(())
+    }
+
+    // 扫描后连接目标设备
+    async fn prepare(&mut self, device_id: &str, scan_secs: u64) -> Result<(), String> {
+        self.scan(scan_secs.max(1), false).await?;
+        let adapter = self.adapters.first().filter(|id| id.as_str() != "all").cloned();
+        device::connect_device(&self.state, self.sink.clone(), device_id.to_string(), adapter).await
+    }
+
+    async fn history(&self, args: &Args) -> Result<(), String> {
+        if self.state.history.lock().unwrap().is_none() {
+            return Err("未指定设备历史数据库，请使用 --history 或设置 BLESCANNER_HISTORY".to_string());
+        }
+        let limit = args.option_num("--limit")?.map(|n| n as u32);
+        match args.positional.get(1).map(String::as_str) {
+            Some("show") => {
+                let history = history::get_device_history(&self.state, args.pos(2, "地址")?, None, limit).await?;
+                self.out.device_history(&history);
+            }
+            Some("prune") => {
+                let days = args.option_num("--days")?.ok_or_else(|| format!("history prune 需要 --days\n\n{}", USAGE))?;
+                let max_age_secs = days.checked_mul(24 * 3600).ok_or_else(|| format!("--days 过大: {}", days))?;
+                let summary = history::prune_history(&self.state, max_age_secs).await?;
+                self.out.value("pruned", &summary);
+            }
+            Some(other) => return Err(format!("未知的 history 子命令: {}\n\n{}", other, USAGE)),
+            None => {
+                let company_id = match args.option("--company") {
+                    Some(v) => Some(
+                        u16::from_str_radix(v.trim_start_matches("0x").trim_start_matches("0X"), 16)
+                            .map_err(|_| format!("无效的厂商ID: {}", v))?,
+                    ),
+                    None => None,
+                };
+                let query = HistoryQuery {
+                    address: args.option("--address").map(str::to_string),
+                    company_id,
+                    name: args.option("--name").map(str::to_string),
+                    since: None,
+                    limit,
+                };
+                self.out.history(&history::query_history(&self.state, query).await?);
+            }
+        }
+        Ok(())
+    }
+
+    // 长值写入，写入过程中实时输出 write-progress 事件
+    async fn write_long(&mut self, args: &Args) -> Result<(), String> {
+        let id = args.pos(1, "设备ID")?;
+        // 文件内容按原始字节发送
+        let (data, encoding) = match args.option("--file") {
+            Some(path) => {
+                let bytes = std::fs::read(path).map_err(|e| format!("读取文件失败: {}: {}", path, e))?;
+                (bytes.iter().map(|b| format!("{:02X}", b)).collect(), Some(PayloadEncoding::Hex))
+            }
+            None => (args.pos(4, "数据")?, args.encoding()?),
+        };
+        let mode = if args.flag("--reliable") {
+            LongWriteMode::Reliable
+        } else if args.flag("--prepared") {
+            LongWriteMode::Prepared
+        } else {
+            LongWriteMode::Chunked
+        };
+        let mut options = LongWriteOptions { mode, without_response: args.flag("--command"), verify: args.flag("--verify"), encoding, ..Default::default() };
+        if let Some(ms) = args.option_num("--interval")? {
+            options.chunk_interval_ms = ms;
+        }
+        self.prepare(&id, args.scan_secs).await?;
+        let (svc, chr) = (args.pos(2, "服务UUID")?, args.pos(3, "特征UUID")?);
+        let write = long_write::write_long(&self.state, self.sink.clone(), id, svc, chr, data, options);
+        tokio::pin!(write);
+        let result = loop {
+            tokio::select! {
+                result = &mut write => break result?,
+                Some((event, payload)) = self.rx.recv() => {
+                    if event == "write-progress" {
+                        self.out.event(&event, &payload);
+                    }
+                }
+            }
+        };
+        while let Ok((event, payload)) = self.rx.try_recv() {
+            if event == "write-progress" {
+                self.out.event(&event, &payload);
+            }
+        }
+        self.out.value("written", &result);
+        match result.verified {
+            Some(false) => Err("回读校验失败：特征值与写入数据不一致".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn snapshot(&mut self, args: &Args) -> Result<(), String> {
+        if args.positional.get(1).map(String::as_str) == Some("diff") {
+            let old = snapshot::load(Path::new(&args.pos(2, "旧快照")?))?;
+            let target = args.pos(3, "新快照或设备ID")?;
+            let new = if Path::new(&target).is_file() {
+                snapshot::load(Path::new(&target))?
+            } else {
+                self.prepare(&target, args.scan_secs).await?;
+                snapshot::take_snapshot(&self.state, target, !old.values.is_empty()).await?
+            };
+            let diff = snapshot::diff(&old, &new);
+            self.out.snapshot_diff(&diff);
+            return if diff.identical { Ok(()) } else { Err(format!("GATT 快照存在 {} 处差异", diff.changes.len())) };
+        }
+        let id = args.pos(1, "设备ID")?;
+        self.prepare(&id, args.scan_secs).await?;
+        let snap = snapshot::take_snapshot(&self.state, id, args.flag("--values")).await?;
+        match args.option("--output") {
+            Some(path) => {
+                snapshot::save(&snap, Path::new(path))?;
+                self.out.value("saved", &path);
+            }
+            None => {
+                let format = if args.flag("--yaml") { SnapshotFormat::Yaml } else { SnapshotFormat::Json };
+                println!("{}", snapshot::to_string(&snap, format)?);
+            }
+        }
+        Ok(())
+    }
+
+    async fn run(&mut self, args: &Args) -> Result<(), String> {
+        let command = args.pos(0, "命令")?;
+        match command.as_str() {
+            "adapters" => {
+                self.out.adapters(&state::get_adapters(&self.state).await?);
+                Ok(())
+            }
+            "scan" => {
+                let secs = args.option_num("--duration")?.unwrap_or(10);
+                self.scan(secs, true).await?;
+                if !args.json {
+                    self.out.value("events", &coalesce::get_event_stats(&self.state).await?);
+                }
+                Ok(())
+            }
+            "list" => {
+                let secs = args.option_num("--duration")?.unwrap_or(args.scan_secs);
+                self.scan(secs.max(1), false).await?;
+                self.out.devices(&scan::get_devices(&self.state).await?);
+                Ok(())
+            }
+            "connect" => {
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                self.out.services(&gatt::get_device_services(&self.state, id).await?);
+                Ok(())
+            }
+            "watch" => {
+                let id = args.pos(1, "设备ID")?;
+                if args.flag("--reconnect") {
+                    let mut policy = ReconnectPolicy { enabled: true, ..Default::default() };
+                    if let Some(n) = args.option_num("--max-attempts")? {
+                        policy.max_attempts = n as u32;
+                    }
+                    connection::set_reconnect_policy(&self.state, Some(id.clone()), policy).await?;
+                }
+                self.prepare(&id, args.scan_secs).await?;
+                // 继续扫描，以便按广播触发重连
+                let scan = scan::start_scan(
+                    &self.state,
+                    self.sink.clone(),
+                    StartScanArgs { duration_secs: Some(0), adapter_ids: Some(self.adapters.clone()), filter: None },
+                )
+                .await?;
+                loop {
+                    tokio::select! {
+                        msg = self.rx.recv() => match msg {
+                            Some((event, payload)) => {
+                                if event.starts_with("device-connection") || event == "device-reconnect-scheduled" {
+                                    self.out.event(&event, &payload);
+                                }
+                            }
+                            None => break,
+                        },
+                        _ = tokio::signal::ctrl_c() => break,
+                    }
+                }
+                scan::stop_scan(&self.state).await?;
+                let _ = scan.await;
+                device::disconnect_device(&self.state, self.sink.clone(), id).await
+            }
+            "pair" | "unpair" => {
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                if command == "pair" {
+                    device::pair_device(&self.state, self.sink.clone(), id.clone()).await?;
+                } else {
+                    device::unpair_device(&self.state, self.sink.clone(), id.clone()).await?;
+                }
+                let devices = scan::get_devices(&self.state).await?;
+                if let Some(d) = devices.iter().find(|d| d.identifier == id) {
+                    self.out.value("paired", &d.paired);
+                    self.out.value("bonded", &d.bonded);
+                }
+                Ok(())
+            }
+            "read" => {
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                let value = gatt::read_characteristic(&self.state, id, args.pos(2, "服务UUID")?, args.pos(3, "特征UUID")?).await?;
+                self.out.characteristic_value(&value);
+                Ok(())
+            }
+            "write" => {
+                let long = ["--chunked", "--prepared", "--reliable", "--verify"].iter().any(|f| args.flag(f)) || args.option("--file").is_some();
+                if long {
+                    return self.write_long(args).await;
+                }
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                let write_type = if args.flag("--command") { "command" } else { "request" };
+                gatt::write_characteristic(
+                    &self.state,
+                    id,
+                    args.pos(2, "服务UUID")?,
+                    args.pos(3, "特征UUID")?,
+                    args.pos(4, "数据")?,
+                    Some(write_type.to_string()),
+                    args.encoding()?,
+                )
+                .await?;
+                self.out.value("written", &true);
+                Ok(())
+            }
+            "notify" => {
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                let (svc, chr) = (args.pos(2, "服务UUID")?, args.pos(3, "特征UUID")?);
+                if let Some(path) = args.option("--record") {
+                    let format = match args.option("--format") {
+                        Some("csv") => Some(RecordingFormat::Csv),
+                        Some("jsonl") => Some(RecordingFormat::Jsonl),
+                        Some("binary") | Some("bin") => Some(RecordingFormat::Binary),
+                        Some(other) => return Err(format!("未知的录制格式: {}", other)),
+                        None => None,
+                    };
+                    notifications::start_recording(&self.state, path.to_string(), format, Some(id.clone())).await?;
+                }
+                if args.flag("--indicate") {
+                    gatt::indicate_characteristic(&self.state, self.sink.clone(), id.clone(), svc.clone(), chr.clone()).await?;
+                } else {
+                    gatt::notify_characteristic(&self.state, self.sink.clone(), id.clone(), svc.clone(), chr.clone()).await?;
+                }
+                if let Some(seq) = args.option("--seq") {
+                    subscriptions::set_subscription_sequence(&self.state, id.clone(), svc.clone(), chr.clone(), Some(seq.parse()?)).await?;
+                }
+                let stats = args.flag("--stats");
+                let limit = args.option_num("--count")?;
+                let mut received = 0u64;
+                loop {
+                    tokio::select! {
+                        msg = self.rx.recv() => match msg {
+                            Some((event, payload)) => {
+                                if event == "subscription-stats" && stats {
+                                    self.out.event(&event, &payload);
+                                } else if event == "characteristic-value" {
+                                    self.out.event(&event, &payload);
+                                    received += 1;
+                                    if limit.map(|n| received >= n).unwrap_or(false) {
+                                        break;
+                                    }
+                                }
+                            }
+                            None => break,
+                        },
+                        _ = tokio::signal::ctrl_c() => break,
+                    }
+                }
+                if stats {
+                    for s in subscriptions::get_subscription_stats(&self.state, Some(id.clone())).await? {
+                        self.out.value("stats", &s);
+                    }
+                }
+                gatt::unsubscribe_characteristic(&self.state, id, svc, chr).await?;
+                if let Some(status) = notifications::stop_recording(&self.state).await? {
+                    self.out.value("recorded", &status);
+                }
+                Ok(())
+            }
+            "replay" => {
+                let speed = match args.option("--speed") {
+                    Some(v) => v.parse::<f64>().map_err(|_| format!("无效的倍速: {}", v))?,
+                    None => 1.0,
+                };
+                notifications::replay_recording(&self.state, self.sink.clone(), args.pos(1, "文件")?, speed).await?;
+                loop {
+                    tokio::select! {
+                        msg = self.rx.recv() => match msg {
+                            Some((event, payload)) => {
+                                self.out.event(&event, &payload);
+                                if event == "replay-completed" {
+                                    break;
+                                }
+                            }
+                            None => break,
+                        },
+                        _ = tokio::signal::ctrl_c() => {
+                            notifications::stop_replay(&self.state).await?;
+                            break;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            "read-descriptor" => {
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                let value =
+                    gatt::read_descriptor(&self.state, id, args.pos(2, "服务UUID")?, args.pos(3, "特征UUID")?, args.pos(4, "描述符UUID")?)
+                        .await?;
+                self.out.value("value", &value);
+                Ok(())
+            }
+            "write-descriptor" => {
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                gatt::write_descriptor(
+                    &self.state,
+                    id,
+                    args.pos(2, "服务UUID")?,
+                    args.pos(3, "特征UUID")?,
+                    args.pos(4, "描述符UUID")?,
+                    args.pos(5, "数据")?,
+                    args.encoding()?,
+                )
+                .await?;
+                self.out.value("written", &true);
+                Ok(())
+            }
+            "mtu" => {
+                let id = args.pos(1, "设备ID")?;
+                self.prepare(&id, args.scan_secs).await?;
+                self.out.value("mtu", &gatt::get_mtu(&self.state, id).await?);
+                Ok(())
+            }
+            "rssi" => {
+                let id = args.pos(1, "设备ID")?;
+                let secs = args.option_num("--duration")?.unwrap_or(10);
+                self.scan(secs.max(1), false).await?;
+                if let Some(meters) = args.option("--calibrate") {
+                    let meters = meters.parse::<f64>().map_err(|_| format!("无效的距离: {}", meters))?;
+                    self.out.value("settings", &rssi::calibrate_distance(&self.state, id.clone(), meters).await?);
+                }
+                self.out.rssi(&rssi::get_rssi_history(&self.state, id).await?);
+                Ok(())
+            }
+            "history" => self.history(args).await,
+            "snapshot" => self.snapshot(args).await,
+            "decoders" => {
+                self.out.decoders(&decoder::list_decoders(&self.state).await?);
+                Ok(())
+            }
+            other => Err(format!("未知命令: {}\n\n{}", other, USAGE)),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = match Args::parse(std::env::args().skip(1).collect()) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let backend = match &args.backend {
+        Some(name) => backend::by_name(Some(name)),
+        None => backend::from_env(),
+    };
+    let state = AppState::new(backend);
+    let _ = coalesce::set_coalesce_settings(&state, CoalesceSettings { window_ms: args.coalesce_ms }).await;
+    let configured = match args.scan_filter() {
+        Ok(spec) => match aging::set_aging_policy(&state, args.aging.clone()).await {
+            Ok(()) => filter::set_scan_filter(&state, spec).await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+    if let Err(e) = configured {
+        eprintln!("错误: {}", e);
+        std::process::exit(2);
+    }
+    if let Some(path) = args.history.clone().map(std::path::PathBuf::from).or_else(history::path_from_env) {
+        if let Err(e) = history::open(&state, &path) {
+            eprintln!("错误: {}", e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = args.decoders.clone().map(std::path::PathBuf::from).or_else(decoder::path_from_env) {
+        if !path.exists() {
+            eprintln!("错误: 解码器布局文件不存在: {}", path.display());
+            std::process::exit(1);
+        }
+        if let Err(e) = decoder::open(&state, &path) {
+            eprintln!("错误: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let (tx, rx) = unbounded_channel();
+    let mut cli = Cli {
+        state,
+        sink: Arc::new(ChannelSink(tx)),
+        rx,
+        out: Output { json: args.json },
+        adapters: args.adapters.clone(),
+    };
+
+    if let Err(e) = cli.run(&args).await {
+        eprintln!("错误: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &[&str]) -> Result<Args, String> {
+        Args::parse(raw.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn parses_global_options_flags_and_positionals() {
+        let args = parse(&[
+            "--json", "--backend", "sim", "--scan", "3", "--adapter", "hci0", "--adapter", "hci1", "--stale-after", "10",
+            "--lost-after", "0", "--coalesce", "250", "write", "DEV", "180F", "2A19", "0102", "--command", "--interval", "5",
+        ])
+        .unwrap();
+        assert!(args.json);
+        assert_eq!(args.backend.as_deref(), Some("sim"));
+        assert_eq!(args.scan_secs, 3);
+        assert_eq!(args.adapters, vec!["hci0", "hci1"]);
+        assert_eq!((args.aging.stale_after_secs, args.aging.lost_after_secs), (10, 0));
+        assert_eq!(args.coalesce_ms, 250);
+        assert_eq!(args.positional, vec!["write", "DEV", "180F", "2A19", "0102"]);
+        assert!(args.flag("--command") && !args.flag("--verify"));
+        assert_eq!(args.option_num("--interval").unwrap(), Some(5));
+        assert_eq!(args.option("--duration"), None);
+        assert_eq!(args.pos(1, "设备ID").unwrap(), "DEV");
+        assert!(args.pos(9, "数据").unwrap_err().starts_with("缺少参数: 数据"));
+    }
+
+    #[test]
+    fn defaults_apply_without_options() {
+        let args = parse(&["list"]).unwrap();
+        assert!(!args.json);
+        assert_eq!(args.scan_secs, 5);
+        assert!(args.adapters.is_empty() && args.history.is_none() && args.decoders.is_none());
+        assert_eq!(args.coalesce_ms, 0);
+        assert!(args.scan_filter().unwrap().is_none());
+        assert!(args.encoding().unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_missing_values_unknown_options_and_bad_numbers() {
+        assert_eq!(parse(&["--backend"]).err().unwrap(), "--backend 需要参数");
+        assert_eq!(parse(&["scan", "--duration"]).err().unwrap(), "--duration 需要参数");
+        assert!(parse(&["--bogus"]).err().unwrap().starts_with("未知选项: --bogus"));
+        assert_eq!(parse(&["--scan", "abc"]).err().unwrap(), "无效的数字: abc");
+        assert_eq!(parse(&["--help"]).err().unwrap(), USAGE);
+        let args = parse(&["history", "--limit", "-1"]).unwrap();
+        assert!(args.option_num("--limit").is_err());
+    }
+
+    #[test]
+    fn builds_scan_filter_from_filter_options() {
+        let args = parse(&[
+            "list", "--filter-name", "^SIM", "--filter-service", "180D", "--filter-service", "180F", "--filter-company",
+            "0x004C:0215/FFFF", "--min-rssi", "-70", "--connectable",
+        ])
+        .unwrap();
+        let filter = args.scan_filter().unwrap().unwrap();
+        assert_eq!(filter.name.as_deref(), Some("^SIM"));
+        assert_eq!(filter.service_uuids, vec!["180D", "180F"]);
+        assert_eq!(filter.min_rssi, Some(-70));
+        assert!(filter.connectable_only);
+        let m = filter.manufacturer.unwrap();
+        assert_eq!((m.company_id, m.data.as_deref(), m.mask.as_deref()), (0x004C, Some("0215"), Some("FFFF")));
+
+        let company_only = parse(&["list", "--filter-company", "0059"]).unwrap().scan_filter().unwrap().unwrap();
+        let m = company_only.manufacturer.unwrap();
+        assert_eq!((m.company_id, m.data, m.mask), (0x0059, None, None));
+
+        assert!(parse(&["list", "--min-rssi", "loud"]).unwrap().scan_filter().is_err());
+        assert!(parse(&["list", "--filter-company", "XYZ"]).unwrap().scan_filter().is_err());
+    }
+}
@@ -0,0 +1,421 @@
+// 终端输出：表格或 JSON Lines
+
+use blescanner_core::assigned_numbers;
+use blescanner_core::beacon::Beacon;
+use blescanner_core::decoder::{DecodedData, DecoderInfo};
+use blescanner_core::gatt_value::CharacteristicValue;
+use blescanner_core::history::{DeviceHistory, HistoryDevice};
+use blescanner_core::rssi::RssiHistory;
+use blescanner_core::snapshot::{ChangeKind, SnapshotDiff};
+use blescanner_core::{BleAdapter, BleDevice, GattService};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn now_ms() -> u128 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0)
+}
+
+pub struct Output {
+    pub json: bool,
+}
+
+impl Output {
+    // JSON 模式下每行一个 {"ts", "event", "payload"} 对象
+    pub fn event(&self, event: &str, payload: &Value) {
+        if self.json {
+            println!("{}", serde_json::json!({ "ts": now_ms(), "event": event, "payload": payload }));
+            return;
+        }
+        match event {
+            "device-discovered" => {
+                if let Ok(d) = serde_json::from_value::<BleDevice>(payload.clone()) {
+                    println!("{}", device_row(&d));
+                }
+            }
+            "characteristic-value" => println!(
+                "{} {} {} {}{}",
+                payload["deviceId"].as_str().unwrap_or(""),
+                payload["characteristicUuid"].as_str().unwrap_or(""),
+                payload["kind"].as_str().unwrap_or(""),
+                payload["value"].as_str().unwrap_or(""),
+                value_label(&payload["decoded"]).map(|l| format!(" {}", l)).unwrap_or_default()
+            ),
+            "device-connection-changed" => println!(
+                "{} {} -> {} ({}){}",
+                payload["deviceId"].as_str().unwrap_or(""),
+                payload["previous"].as_str().unwrap_or(""),
+                payload["state"].as_str().unwrap_or(""),
+                payload["reason"].as_str().unwrap_or(""),
+                payload["error"].as_str().map(|e| format!(": {}", e)).unwrap_or_default()
+            ),
+            "write-progress" => println!(
+                "{}/{} chunks, {}/{} bytes",
+                payload["chunk"].as_u64().unwrap_or(0),
+                payload["chunks"].as_u64().unwrap_or(0),
+                payload["bytesWritten"].as_u64().unwrap_or(0),
+                payload["totalBytes"].as_u64().unwrap_or(0)
+            ),
+            "subscription-stats" => println!(
+                "{} {} {} pkt/s, {} B/s, interval {} ms, jitter {:.1} ms{}",
+                payload["deviceId"].as_str().unwrap_or(""),
+                payload["characteristicUuid"].as_str().unwrap_or(""),
+                payload["packetsPerSec"].as_f64().map(|v| format!("{:.1}", v)).unwrap_or_default(),
+                payload["bytesPerSec"].as_f64().map(|v| format!("{:.0}", v)).unwrap_or_default(),
+                payload["meanIntervalMs"].as_f64().map(|v| format!("{:.1}", v)).unwrap_or_else(|| "-".to_string()),
+                payload["jitterMs"].as_f64().unwrap_or(0.0),
+                if payload["sequence"].is_string() {
+                    format!(", lost {} in {} gaps", payload["lostPackets"].as_u64().unwrap_or(0), payload["gaps"].as_u64().unwrap_or(0))
+                } else {
+                    String::new()
+                }
+            ),
+            // 表格模式下不输出 RSSI 聚合（用 rssi 命令查看）
+            "rssi-summary" => {}
+            "devices-updated" => {
+                for added in payload["added"].as_array().into_iter().flatten() {
+                    if let Ok(d) = serde_json::from_value::<BleDevice>(added.clone()) {
+                        println!("{}", device_row(&d));
+                    }
+                }
+                for diff in payload["updated"].as_array().into_iter().flatten() {
+                    let changes = diff["changes"]
+                        .as_object()
+                        .map(|c| c.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(" "))
+                        .unwrap_or_default();
+                    println!("~ {} {}", diff["identifier"].as_str().unwrap_or(""), changes);
+                }
+            }
+            "device-stale" | "device-lost" => println!(
+                "{} {} {} (last seen {})",
+                if event == "device-stale" { "stale" } else { "lost " },
+                payload["deviceId"].as_str().unwrap_or(""),
+                payload["address"].as_str().unwrap_or(""),
+                payload["lastSeen"].as_i64().map(format_ts).unwrap_or_default()
+            ),
+            _ => match payload {
+                Value::Null => println!("[{}]", event),
+                Value::String(s) => println!("[{}] {}", event, s),
+                other => println!("[{}] {}", event, other),
+            },
+        }
+    }
+
+    pub fn value<T: serde::Serialize>(&self, key: &str, value: &T) {
+        let v = serde_json::to_value(value).unwrap_or(Value::Null);
+        if self.json {
+            println!("{}", serde_json::json!({ key: v }));
+        } else {
+            match v {
+                Value::String(s) => println!("{}: {}", key, s),
+                other => println!("{}: {}", key, other),
+            }
+        }
+    }
+
+    // 特征值：JSON 模式输出完整对象，表格模式输出十六进制与解码摘要
+    pub fn characteristic_value(&self, value: &CharacteristicValue) {
+        if self.json {
+            println!("{}", serde_json::to_string(value).unwrap_or_default());
+            return;
+        }
+        println!("value: {}", value.value);
+        if let Some(label) = serde_json::to_value(&value.decoded).ok().as_ref().and_then(value_label) {
+            println!("decoded: {}", label);
+        }
+    }
+
+    // 快照差异：表格模式每处差异一行，+ 新增 / - 移除 / ~ 变化
+    pub fn snapshot_diff(&self, diff: &SnapshotDiff) {
+        if self.json {
+            println!("{}", serde_json::to_string(diff).unwrap_or_default());
+            return;
+        }
+        if diff.identical {
+            println!("identical");
+            return;
+        }
+        for c in &diff.changes {
+            let show = |v: &Option<Value>| v.as_ref().map(|v| v.to_string()).unwrap_or_default();
+            match c.kind {
+                ChangeKind::Added => println!("+ {}", c.path),
+                ChangeKind::Removed => println!("- {}", c.path),
+                ChangeKind::Changed => println!(
+                    "~ {} {}: {} -> {}",
+                    c.path,
+                    c.field.as_deref().unwrap_or(""),
+                    show(&c.old),
+                    show(&c.new)
+                ),
+            }
+        }
+    }
+
+    pub fn adapters(&self, adapters: &[BleAdapter]) {
+        if !self.json {
+            println!("{:<24} {:<20} {:<8} SELECTED", "IDENTIFIER", "ADDRESS", "POWERED");
+        }
+        for a in adapters {
+            if self.json {
+                println!("{}", serde_json::to_string(a).unwrap_or_default());
+            } else {
+                println!("{:<24} {:<20} {:<8} {}", a.identifier, a.address, a.powered, if a.selected { "*" } else { "" });
+            }
+        }
+    }
+
+    pub fn device_header(&self) {
+        if !self.json {
+            println!("{:<24} {:<20} {:>5} {:<5} {:<5} {:<10} MANUFACTURER", "IDENTIFIER", "ADDRESS", "RSSI", "CONN", "BOND", "ADAPTER");
+        }
+    }
+
+    pub fn devices(&self, devices: &[BleDevice]) {
+        self.device_header();
+        for d in devices {
+            if self.json {
+                println!("{}", serde_json::to_string(d).unwrap_or_default());
+            } else {
+                println!("{}", device_row(d));
+            }
+        }
+    }
+
+    pub fn decoders(&self, decoders: &[DecoderInfo]) {
+        if !self.json {
+            println!("{:<8} {:<8} {:<10} NAME", "COMPANY", "SOURCE", "PREFIX");
+        }
+        for d in decoders {
+            if self.json {
+                println!("{}", serde_json::to_string(d).unwrap_or_default());
+            } else {
+                println!("{:04X}     {:<8} {:<10} {}", d.company_id, d.source, if d.prefix.is_empty() { "-" } else { &d.prefix }, d.name);
+            }
+        }
+    }
+
+    pub fn services(&self, services: &[GattService]) {
+        for s in services {
+            if self.json {
+                println!("{}", serde_json::to_string(s).unwrap_or_default());
+                continue;
+            }
+            println!("{}{}", s.uuid, name_suffix(s.name.as_deref()));
+            for c in &s.characteristics {
+                let description = c.user_description.as_deref().map(|d| format!(" \"{}\"", d)).unwrap_or_default();
+                let security = c
+                    .security
+                    .map(|s| format!(" {{read={:?} write={:?}}}", s.read, s.write).to_lowercase())
+                    .unwrap_or_default();
+                println!(
+                    "  {}{} [{}]{}{}",
+                    c.uuid,
+                    name_suffix(c.name.as_deref()),
+                    c.properties.join(", "),
+                    security,
+                    description
+                );
+                for d in &c.descriptors {
+                    let value = d.value.as_deref().map(|v| format!(" = {}", v)).unwrap_or_default();
+                    println!("    {}{}{}", d.uuid, name_suffix(d.name.as_deref()), value);
+                }
+            }
+        }
+    }
+
+    pub fn rssi(&self, history: &RssiHistory) {
+        if self.json {
+            println!("{}", serde_json::to_string(history).unwrap_or_default());
+            return;
+        }
+        let s = &history.summary;
+        println!("{} (adapter {})", s.device_id, s.adapter_id);
+        println!(
+            "  rssi {}  smoothed {:.1}  min {}  max {}  mean {:.1}  variance {:.2}  samples {}",
+            s.rssi, s.smoothed, s.stats.min, s.stats.max, s.stats.mean, s.stats.variance, s.stats.count
+        );
+        match s.distance {
+            Some(d) => println!("  distance ~{:.2} m", d),
+            None => println!("  distance - (无 tx_power 或参考功率)"),
+        }
+        for p in &history.samples {
+            println!("  {} {:>4}", format_ts(p.timestamp as i64), p.rssi);
+        }
+    }
+
+    pub fn history(&self, devices: &[HistoryDevice]) {
+        if !self.json {
+            println!("{:<20} {:<24} {:<19} {:<19} {:>6} {:>5} MANUFACTURER", "ADDRESS", "NAME", "FIRST SEEN", "LAST SEEN", "SEEN", "RSSI");
+        }
+        for d in devices {
+            if self.json {
+                println!("{}", serde_json::to_string(d).unwrap_or_default());
+                continue;
+            }
+            let name = d.names.last().map(|n| n.name.as_str()).unwrap_or("-");
+            let manufacturer = d
+                .manufacturer_data
+                .iter()
+                .map(|m| format!("{:04X}:{}", m.company_id, m.data))
+                .collect::<Vec<_>>()
+                .join(" ");
+            println!(
+                "{:<20} {:<24} {:<19} {:<19} {:>6} {:>5} {}",
+                d.address,
+                name,
+                format_ts(d.first_seen),
+                format_ts(d.last_seen),
+                d.sightings,
+                d.last_rssi.map(|r| r.to_string()).unwrap_or_else(|| "-".into()),
+                manufacturer
+            );
+        }
+    }
+
+    pub fn device_history(&self, history: &DeviceHistory) {
+        if self.json {
+            println!("{}", serde_json::to_string(history).unwrap_or_default());
+            return;
+        }
+        let d = &history.device;
+        println!("{} ({})", d.address, d.identifier);
+        println!("  first seen {}  last seen {}  sightings {}", format_ts(d.first_seen), format_ts(d.last_seen), d.sightings);
+        for n in &d.names {
+            println!("  name {:<24} {} .. {}", n.name, format_ts(n.first_seen), format_ts(n.last_seen));
+        }
+        for m in &d.manufacturer_data {
+            let company = assigned_numbers::company_name(m.company_id);
+            println!(
+                "  manufacturer {:04X}:{}{} {} .. {}",
+                m.company_id,
+                m.data,
+                name_suffix(company),
+                format_ts(m.first_seen),
+                format_ts(m.last_seen)
+            );
+        }
+        for sd in &d.service_data {
+            println!("  service data {}:{} {} .. {}", sd.uuid, sd.data, format_ts(sd.first_seen), format_ts(sd.last_seen));
+        }
+        for s in &history.rssi {
+            println!("  rssi {} {:>4} {}", format_ts(s.timestamp), s.rssi, s.adapter_id);
+        }
+        for g in &history.gatt {
+            println!("  gatt {}", format_ts(g.timestamp));
+            for s in &g.services {
+                println!("    {}", s.uuid);
+                for c in &s.characteristics {
+                    println!("      {} [{}]", c.uuid, c.properties.join(", "));
+                }
+            }
+        }
+    }
+}
+
+// Unix 毫秒 -> UTC "YYYY-MM-DD HH:MM:SS"
+fn format_ts(ms: i64) -> String {
+    let secs = ms.div_euclid(1000);
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    // 公历日期换算（Howard Hinnant 的 civil_from_days）
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+fn device_row(d: &BleDevice) -> String {
+    let manufacturer = d
+        .manufacturer_data
+        .iter()
+        .map(|(id, data)| {
+            // 已解码的厂商数据显示字段，未解码的显示原始十六进制
+            match d.decoded.iter().find(|dec| format!("{:04X}", dec.company_id) == *id) {
+                Some(dec) => decoded_label(dec),
+                None => format!("{}:{}", id, data),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let manufacturer = match d.beacon.as_ref().map(beacon_label) {
+        Some(label) if manufacturer.is_empty() => label,
+        Some(label) => format!("{} {}", label, manufacturer),
+        None => manufacturer,
+    };
+    let identifier = if d.identifier.is_empty() { "-" } else { d.identifier.as_str() };
+    format!(
+        "{:<24} {:<20} {:>5} {:<5} {:<5} {:<10} {}",
+        identifier,
+        d.address,
+        d.rssi.map(|r| r.to_string()).unwrap_or_else(|| "-".into()),
+        if d.connected { "up" } else if d.connectable { "yes" } else { "no" },
+        if d.bonded { "yes" } else { "-" },
+        d.seen_by.join(","),
+        manufacturer
+    )
+}
+
+// 信标的简短描述，显示在设备行的厂商数据之前
+fn beacon_label(beacon: &Beacon) -> String {
+    match beacon {
+        Beacon::IBeacon { uuid, major, minor, .. } => format!("[iBeacon {} {}/{}]", uuid, major, minor),
+        Beacon::AltBeacon { uuid, major, minor, .. } => format!("[AltBeacon {} {}/{}]", uuid, major, minor),
+        Beacon::Eddystone { uid, url, tlm, eid, .. } => {
+            let mut parts = Vec::new();
+            if let Some(uid) = uid {
+                parts.push(format!("uid={}:{}", uid.namespace, uid.instance));
+            }
+            if let Some(url) = url {
+                parts.push(url.clone());
+            }
+            if let Some(eid) = eid {
+                parts.push(format!("eid={}", eid));
+            }
+            if let Some(tlm) = tlm {
+                parts.extend(tlm.battery_mv.map(|mv| format!("{}mV", mv)));
+                parts.extend(tlm.temperature.map(|t| format!("{:.1}°C", t)));
+            }
+            format!("[Eddystone {}]", parts.join(" "))
+        }
+    }
+}
+
+fn decoded_label(decoded: &DecodedData) -> String {
+    let fields = decoded
+        .fields
+        .iter()
+        .map(|f| {
+            let value = match &f.value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            format!("{}={}{}", f.name, value, f.unit.as_deref().unwrap_or(""))
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{}{{{}}}", decoded.decoder, fields)
+}
+
+// 标准特征解码结果的单行摘要，如 heartRate{bpm=72 sensorContact=true}
+fn value_label(decoded: &Value) -> Option<String> {
+    let obj = decoded.as_object()?;
+    let fields = obj
+        .iter()
+        .filter(|(k, v)| k.as_str() != "type" && !v.is_null() && v.as_array().map(|a| !a.is_empty()).unwrap_or(true))
+        .map(|(k, v)| match v {
+            Value::String(s) => format!("{}={}", k, s),
+            other => format!("{}={}", k, other),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(format!("{}{{{}}}", obj.get("type")?.as_str()?, fields))
+}
+
+// SIG 名称后缀，如 " (Heart Rate)"
+fn name_suffix(name: Option<&str>) -> String {
+    name.map(|n| format!(" ({})", n)).unwrap_or_default()
+}
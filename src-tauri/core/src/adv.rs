@@ -0,0 +1,414 @@
+// 广播数据（AD Structure）编解码
+//
+// 一个广播包由若干 AD Structure 组成，每个结构为 [长度][类型][数据...]，
+// 长度字段包含类型字节本身。SimpleBLE 不提供原始广播字节，这里根据
+// Peripheral 实际上报的字段重新编码，并逐项标记字段是“实际观测到”还是“后端不可用”，
+// 避免把猜测的数据当作真实广播展示。
+
+use serde::{Deserialize, Serialize};
+
+// 常用 AD 类型（Bluetooth Core Supplement, Part A）
+pub const AD_FLAGS: u8 = 0x01;
+pub const AD_INCOMPLETE_UUID16: u8 = 0x02;
+pub const AD_COMPLETE_UUID16: u8 = 0x03;
+pub const AD_INCOMPLETE_UUID32: u8 = 0x04;
+pub const AD_COMPLETE_UUID32: u8 = 0x05;
+pub const AD_INCOMPLETE_UUID128: u8 = 0x06;
+pub const AD_COMPLETE_UUID128: u8 = 0x07;
+pub const AD_SHORT_LOCAL_NAME: u8 = 0x08;
+pub const AD_COMPLETE_LOCAL_NAME: u8 = 0x09;
+pub const AD_TX_POWER: u8 = 0x0A;
+pub const AD_SERVICE_DATA_UUID16: u8 = 0x16;
+pub const AD_SERVICE_DATA_UUID32: u8 = 0x20;
+pub const AD_SERVICE_DATA_UUID128: u8 = 0x21;
+pub const AD_MANUFACTURER_DATA: u8 = 0xFF;
+
+// 蓝牙基础 UUID 的后 96 位，用于 16/32 位 UUID 与 128 位互转
+const BASE_UUID_SUFFIX: &str = "-0000-1000-8000-00805F9B34FB";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdStructure {
+    pub ad_type: u8,
+    pub data: Vec<u8>,
+}
+
+// 按 [len][type][data] 解析字节序列；长度为 0 的结构视为填充并结束解析
+pub fn parse_structures(bytes: &[u8]) -> Result<Vec<AdStructure>, String> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let len = bytes[i] as usize;
+        if len == 0 {
+            break;
+        }
+        if i + 1 + len > bytes.len() {
+            return Err(format!(
+                "AD 结构越界: 偏移 {} 声明长度 {}，剩余 {} 字节",
+                i,
+                len,
+                bytes.len() - i - 1
+            ));
+        }
+        out.push(AdStructure { ad_type: bytes[i + 1], data: bytes[i + 2..i + 1 + len].to_vec() });
+        i += 1 + len;
+    }
+    Ok(out)
+}
+
+pub fn encode_structures(structures: &[AdStructure]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for s in structures {
+        // 单个结构数据最多 254 字节（长度字段为 1 字节且包含类型）
+        let data = &s.data[..s.data.len().min(254)];
+        out.push((data.len() + 1) as u8);
+        out.push(s.ad_type);
+        out.extend_from_slice(data);
+    }
+    out
+}
+
+// UUID 规范化为大写 128 位字符串；4 位或 8 位短 UUID（可带 0x 前缀）会扩展到基础 UUID
+pub fn normalize_uuid(uuid: &str) -> String {
+    let u = uuid.trim().to_uppercase();
+    let u = u.strip_prefix("0X").unwrap_or(&u);
+    match u.len() {
+        4 => format!("0000{}{}", u, BASE_UUID_SUFFIX),
+        8 => format!("{}{}", u, BASE_UUID_SUFFIX),
+        _ => u.to_string(),
+    }
+}
+
+// 将 UUID 编码为广播中使用的小端字节（2、4 或 16 字节）
+pub fn uuid_to_adv_bytes(uuid: &str) -> Option<Vec<u8>> {
+    let full = normalize_uuid(uuid);
+    if full.len() != 36 {
+        return None;
+    }
+    if full.ends_with(BASE_UUID_SUFFIX) {
+        let value = u32::from_str_radix(&full[..8], 16).ok()?;
+        if value <= 0xFFFF {
+            return Some((value as u16).to_le_bytes().to_vec());
+        }
+        return Some(value.to_le_bytes().to_vec());
+    }
+    let mut bytes = hex::decode(full.replace('-', "")).ok()?;
+    bytes.reverse();
+    Some(bytes)
+}
+
+// 广播中的小端 UUID 字节还原为 128 位字符串
+pub fn uuid_from_adv_bytes(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        2 => Some(format!("0000{:04X}{}", u16::from_le_bytes([bytes[0], bytes[1]]), BASE_UUID_SUFFIX)),
+        4 => Some(format!(
+            "{:08X}{}",
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            BASE_UUID_SUFFIX
+        )),
+        16 => {
+            let mut b = bytes.to_vec();
+            b.reverse();
+            let h = hex::encode_upper(b);
+            Some(format!("{}-{}-{}-{}-{}", &h[0..8], &h[8..12], &h[12..16], &h[16..20], &h[20..32]))
+        }
+        _ => None,
+    }
+}
+
+// 从广播结构中解码出的字段
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdvertisementData {
+    pub flags: Option<u8>,
+    pub local_name: Option<String>,
+    pub complete_name: bool,
+    pub service_uuids: Vec<String>,
+    pub manufacturer_data: Vec<(u16, Vec<u8>)>,
+    pub tx_power: Option<i8>,
+    pub service_data: Vec<(String, Vec<u8>)>,
+}
+
+impl AdvertisementData {
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut adv = AdvertisementData::default();
+        for s in parse_structures(bytes)? {
+            let d = &s.data;
+            match s.ad_type {
+                AD_FLAGS => adv.flags = d.first().copied(),
+                AD_INCOMPLETE_UUID16 | AD_COMPLETE_UUID16 => {
+                    adv.service_uuids.extend(d.chunks_exact(2).filter_map(uuid_from_adv_bytes))
+                }
+                AD_INCOMPLETE_UUID32 | AD_COMPLETE_UUID32 => {
+                    adv.service_uuids.extend(d.chunks_exact(4).filter_map(uuid_from_adv_bytes))
+                }
+                AD_INCOMPLETE_UUID128 | AD_COMPLETE_UUID128 => {
+                    adv.service_uuids.extend(d.chunks_exact(16).filter_map(uuid_from_adv_bytes))
+                }
+                AD_SHORT_LOCAL_NAME | AD_COMPLETE_LOCAL_NAME => {
+                    adv.local_name = Some(String::from_utf8_lossy(d).into_owned());
+                    adv.complete_name = s.ad_type == AD_COMPLETE_LOCAL_NAME;
+                }
+                AD_TX_POWER => adv.tx_power = d.first().map(|b| *b as i8),
+                AD_SERVICE_DATA_UUID16 | AD_SERVICE_DATA_UUID32 | AD_SERVICE_DATA_UUID128 => {
+                    let n = match s.ad_type {
+                        AD_SERVICE_DATA_UUID16 => 2,
+                        AD_SERVICE_DATA_UUID32 => 4,
+                        _ => 16,
+                    };
+                    if d.len() >= n {
+                        if let Some(uuid) = uuid_from_adv_bytes(&d[..n]) {
+                            adv.service_data.push((uuid, d[n..].to_vec()));
+                        }
+                    }
+                }
+                AD_MANUFACTURER_DATA if d.len() >= 2 => {
+                    adv.manufacturer_data.push((u16::from_le_bytes([d[0], d[1]]), d[2..].to_vec()));
+                }
+                _ => {}
+            }
+        }
+        Ok(adv)
+    }
+
+    pub fn to_structures(&self) -> Vec<AdStructure> {
+        let mut out = Vec::new();
+        if let Some(flags) = self.flags {
+            out.push(AdStructure { ad_type: AD_FLAGS, data: vec![flags] });
+        }
+
+        // 按 UUID 宽度分组，每组一个完整列表结构
+        let (mut u16s, mut u32s, mut u128s) = (Vec::new(), Vec::new(), Vec::new());
+        for uuid in &self.service_uuids {
+            match uuid_to_adv_bytes(uuid) {
+                Some(b) if b.len() == 2 => u16s.extend(b),
+                Some(b) if b.len() == 4 => u32s.extend(b),
+                Some(b) => u128s.extend(b),
+                None => {}
+            }
+        }
+        for (ad_type, data) in [(AD_COMPLETE_UUID16, u16s), (AD_COMPLETE_UUID32, u32s), (AD_COMPLETE_UUID128, u128s)] {
+            if !data.is_empty() {
+                out.push(AdStructure { ad_type, data });
+            }
+        }
+
+        if let Some(name) = &self.local_name {
+            let ad_type = if self.complete_name { AD_COMPLETE_LOCAL_NAME } else { AD_SHORT_LOCAL_NAME };
+            out.push(AdStructure { ad_type, data: name.as_bytes().to_vec() });
+        }
+        if let Some(tx) = self.tx_power {
+            out.push(AdStructure { ad_type: AD_TX_POWER, data: vec![tx as u8] });
+        }
+        for (uuid, payload) in &self.service_data {
+            if let Some(mut data) = uuid_to_adv_bytes(uuid) {
+                let ad_type = match data.len() {
+                    2 => AD_SERVICE_DATA_UUID16,
+                    4 => AD_SERVICE_DATA_UUID32,
+                    _ => AD_SERVICE_DATA_UUID128,
+                };
+                data.extend_from_slice(payload);
+                out.push(AdStructure { ad_type, data });
+            }
+        }
+        for (company_id, payload) in &self.manufacturer_data {
+            let mut data = company_id.to_le_bytes().to_vec();
+            data.extend_from_slice(payload);
+            out.push(AdStructure { ad_type: AD_MANUFACTURER_DATA, data });
+        }
+        out
+    }
+
+    // 注意：SimpleBLE 合并了广播包与扫描响应，重建结果可能超过 31 字节，这里不截断
+    pub fn encode(&self) -> Vec<u8> {
+        encode_structures(&self.to_structures())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldSource {
+    // 由后端实际上报
+    Observed,
+    // 后端未提供该字段，重建数据中不包含
+    Unavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdvField {
+    pub field: String,
+    pub source: FieldSource,
+    pub value: Option<String>,
+}
+
+// Peripheral 在一次扫描回调中上报的原始字段
+#[derive(Debug, Clone, Default)]
+pub struct ObservedAdvertisement {
+    pub local_name: Option<String>,
+    pub service_uuids: Vec<String>,
+    pub manufacturer_data: Vec<(u16, Vec<u8>)>,
+    pub tx_power: Option<i16>,
+    pub service_data: Vec<(String, Vec<u8>)>,
+}
+
+impl ObservedAdvertisement {
+    // 重建广播数据并给出逐字段的观测状态
+    pub fn reconstruct(&self) -> (AdvertisementData, Vec<AdvField>) {
+        let mut fields = Vec::new();
+        let mut push = |field: &str, value: Option<String>| {
+            let source = if value.is_some() { FieldSource::Observed } else { FieldSource::Unavailable };
+            fields.push(AdvField { field: field.to_string(), source, value });
+        };
+
+        // Flags 不会由 SimpleBLE 暴露，始终不可用
+        push("flags", None);
+
+        let local_name = self.local_name.clone().filter(|n| !n.is_empty());
+        push("local_name", local_name.clone());
+
+        push(
+            "service_uuids",
+            if self.service_uuids.is_empty() { None } else { Some(self.service_uuids.join(",")) },
+        );
+
+        push(
+            "manufacturer_data",
+            if self.manufacturer_data.is_empty() {
+                None
+            } else {
+                Some(
+                    self.manufacturer_data
+                        .iter()
+                        .map(|(id, d)| format!("{:04X}:{}", id, hex::encode_upper(d)))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                )
+            },
+        );
+
+        // 未广播 TX Power 时后端返回越界的哨兵值（如 i16::MIN）
+        let tx_power = self.tx_power.and_then(|v| i8::try_from(v).ok());
+        push("tx_power", tx_power.map(|v| v.to_string()));
+
+        let service_data: Vec<(String, Vec<u8>)> =
+            self.service_data.iter().filter(|(_, d)| !d.is_empty()).cloned().collect();
+        push(
+            "service_data",
+            if service_data.is_empty() {
+                None
+            } else {
+                Some(
+                    service_data
+                        .iter()
+                        .map(|(u, d)| format!("{}:{}", u, hex::encode_upper(d)))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                )
+            },
+        );
+
+        let adv = AdvertisementData {
+            flags: None,
+            complete_name: local_name.is_some(),
+            local_name,
+            service_uuids: self.service_uuids.iter().map(|u| normalize_uuid(u)).collect(),
+            manufacturer_data: self.manufacturer_data.clone(),
+            tx_power,
+            service_data: service_data.into_iter().map(|(u, d)| (normalize_uuid(&u), d)).collect(),
+        };
+        (adv, fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // nRF Connect 抓取的 iBeacon：Flags + Apple 厂商数据
+    const IBEACON: &str = "0201061AFF4C000215E2C56DB5DFFB48D2B060D0F5A71096E000010002C5";
+    // Eddystone URL：Flags + 完整 16 位 UUID 列表 + 服务数据
+    const EDDYSTONE_URL: &str = "0201060303AAFE0D16AAFE10EB03676F6F676C6507";
+    // 心率带：Flags + UUID 列表 + 完整名称 + TX Power
+    const HRM: &str = "02010603030D180A09506F6C617220483130020AFC";
+
+    #[test]
+    fn parses_ibeacon_capture() {
+        let bytes = hex::decode(IBEACON).unwrap();
+        let adv = AdvertisementData::decode(&bytes).unwrap();
+        assert_eq!(adv.flags, Some(0x06));
+        assert_eq!(adv.manufacturer_data.len(), 1);
+        let (company, data) = &adv.manufacturer_data[0];
+        assert_eq!(*company, 0x004C);
+        assert_eq!(&data[..2], &[0x02, 0x15]);
+        assert_eq!(data.len(), 23);
+        assert_eq!(*data.last().unwrap() as i8, -59);
+    }
+
+    #[test]
+    fn parses_eddystone_service_data() {
+        let bytes = hex::decode(EDDYSTONE_URL).unwrap();
+        let adv = AdvertisementData::decode(&bytes).unwrap();
+        assert_eq!(adv.service_uuids, vec!["0000FEAA-0000-1000-8000-00805F9B34FB".to_string()]);
+        assert_eq!(adv.service_data.len(), 1);
+        assert_eq!(adv.service_data[0].0, "0000FEAA-0000-1000-8000-00805F9B34FB");
+        assert_eq!(adv.service_data[0].1[0], 0x10);
+    }
+
+    #[test]
+    fn parses_name_and_tx_power() {
+        let bytes = hex::decode(HRM).unwrap();
+        let adv = AdvertisementData::decode(&bytes).unwrap();
+        assert_eq!(adv.local_name.as_deref(), Some("Polar H10"));
+        assert!(adv.complete_name);
+        assert_eq!(adv.tx_power, Some(-4));
+        assert_eq!(adv.service_uuids, vec!["0000180D-0000-1000-8000-00805F9B34FB".to_string()]);
+    }
+
+    #[test]
+    fn round_trips_captures() {
+        for capture in [IBEACON, EDDYSTONE_URL, HRM] {
+            let bytes = hex::decode(capture).unwrap();
+            let structures = parse_structures(&bytes).unwrap();
+            assert_eq!(encode_structures(&structures), bytes);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_structure() {
+        assert!(parse_structures(&[0x05, 0xFF, 0x4C, 0x00]).is_err());
+        assert!(parse_structures(&[0x02, 0x01, 0x06, 0x00, 0x00]).unwrap().len() == 1);
+    }
+
+    #[test]
+    fn normalizes_short_and_prefixed_uuids() {
+        assert_eq!(normalize_uuid("180d"), "0000180D-0000-1000-8000-00805F9B34FB");
+        assert_eq!(normalize_uuid(" 0x180D "), "0000180D-0000-1000-8000-00805F9B34FB");
+        assert_eq!(normalize_uuid("0XFEAA"), "0000FEAA-0000-1000-8000-00805F9B34FB");
+        assert_eq!(normalize_uuid("0x0000FE9F"), "0000FE9F-0000-1000-8000-00805F9B34FB");
+        assert_eq!(normalize_uuid("6e400001-b5a3-f393-e0a9-e50e24dcca9e"), "6E400001-B5A3-F393-E0A9-E50E24DCCA9E");
+    }
+
+    #[test]
+    fn uuid128_is_little_endian_on_air() {
+        let uuid = "6E400001-B5A3-F393-E0A9-E50E24DCCA9E";
+        let bytes = uuid_to_adv_bytes(uuid).unwrap();
+        assert_eq!(bytes[0], 0x9E);
+        assert_eq!(bytes[15], 0x6E);
+        assert_eq!(uuid_from_adv_bytes(&bytes).unwrap(), uuid);
+    }
+
+    #[test]
+    fn reconstruct_marks_unavailable_fields() {
+        let observed = ObservedAdvertisement {
+            local_name: Some("Polar H10".into()),
+            service_uuids: vec!["180d".into()],
+            tx_power: Some(i16::MIN),
+            ..Default::default()
+        };
+        let (adv, fields) = observed.reconstruct();
+        let source = |name: &str| fields.iter().find(|f| f.field == name).unwrap().source;
+        assert_eq!(source("flags"), FieldSource::Unavailable);
+        assert_eq!(source("local_name"), FieldSource::Observed);
+        assert_eq!(source("tx_power"), FieldSource::Unavailable);
+        assert_eq!(source("manufacturer_data"), FieldSource::Unavailable);
+        assert_eq!(hex::encode_upper(adv.encode()), "03030D180A09506F6C617220483130");
+    }
+}
@@ -0,0 +1,175 @@
+// 设备老化：扫描期间定期检查设备最近出现时间，超时先标记为 stale，再从设备列表移除（lost）
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::EventSink;
+use crate::model::now_ms;
+use crate::registry::{self, Lifecycle};
+use crate::state::AppState;
+
+// 检查间隔（毫秒）
+pub const SWEEP_INTERVAL_MS: u64 = 1000;
+
+// 老化策略；任一超时为 0 表示关闭该阶段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgingPolicy {
+    #[serde(alias = "staleAfterSecs")]
+    pub stale_after_secs: u64,
+    #[serde(alias = "lostAfterSecs")]
+    pub lost_after_secs: u64,
+}
+
+impl Default for AgingPolicy {
+    fn default() -> Self {
+        Self { stale_after_secs: 30, lost_after_secs: 120 }
+    }
+}
+
+impl AgingPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.stale_after_secs > 0 && self.lost_after_secs > 0 && self.lost_after_secs < self.stale_after_secs {
+            return Err("lost_after_secs 不能小于 stale_after_secs".to_string());
+        }
+        Ok(())
+    }
+}
+
+// 检查一次所有设备；已连接的设备不参与老化
+pub fn sweep(state: &AppState, sink: &Arc<dyn EventSink>) {
+    let policy = state.aging.lock().unwrap().clone();
+    let now = now_ms();
+    let expired = |secs: u64, last_seen: u64| secs > 0 && last_seen > 0 && now.saturating_sub(last_seen) >= secs.saturating_mul(1000);
+
+    let mut stale = Vec::new();
+    let mut lost = Vec::new();
+    {
+        let mut devices = state.devices.lock().unwrap();
+        devices.retain_mut(|d| {
+            if d.connected {
+                return true;
+            }
+            if expired(policy.lost_after_secs, d.last_seen) {
+                lost.push((registry::device_key(d), d.identifier.clone(), d.address.clone(), d.last_seen));
+                return false;
+            }
+            if !d.stale && expired(policy.stale_after_secs, d.last_seen) {
+                d.stale = true;
+                stale.push((d.identifier.clone(), d.address.clone(), d.last_seen));
+            }
+            true
+        });
+    }
+
+    if !lost.is_empty() {
+        let mut registry = state.registry.lock().unwrap();
+        let mut rssi = state.rssi.lock().unwrap();
+        let mut coalescer = state.coalescer.lock().unwrap();
+        for key in lost.iter().filter_map(|(key, ..)| key.as_deref()) {
+            if registry.lifecycle(key) == Some(Lifecycle::Discovered) {
+                registry.set_lifecycle(key, Lifecycle::Lost);
+            }
+            rssi.forget(key);
+            coalescer.forget(key);
+        }
+    }
+    let lost = lost.into_iter().map(|(_, id, address, last_seen)| (id, address, last_seen)).collect();
+    for (event, list) in [("device-stale", stale), ("device-lost", lost)] {
+        for (device_id, address, last_seen) in list {
+            let _ = sink.emit(event, serde_json::json!({
+                "deviceId": device_id,
+                "address": address,
+                "lastSeen": last_seen
+            }));
+        }
+    }
+}
+
+// 获取老化策略
+pub async fn get_aging_policy(state: &AppState) -> Result<AgingPolicy, String> {
+    Ok(state.aging.lock().unwrap().clone())
+}
+
+// 设置老化策略，正在进行的扫描立即生效
+pub async fn set_aging_policy(state: &AppState, policy: AgingPolicy) -> Result<(), String> {
+    policy.validate()?;
+    *state.aging.lock().unwrap() = policy;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::SimulatedBackend;
+    use crate::events::RecordingSink;
+    use crate::model::BleDevice;
+
+    #[test]
+    fn validate_requires_lost_after_stale() {
+        assert!(AgingPolicy::default().validate().is_ok());
+        assert!(AgingPolicy { stale_after_secs: 60, lost_after_secs: 30 }.validate().is_err());
+        // 关闭其中一个阶段时不做比较
+        assert!(AgingPolicy { stale_after_secs: 60, lost_after_secs: 0 }.validate().is_ok());
+        assert!(AgingPolicy { stale_after_secs: 0, lost_after_secs: 30 }.validate().is_ok());
+    }
+
+    #[test]
+    fn sweep_saturates_huge_timeouts() {
+        let state = AppState::new(Arc::new(SimulatedBackend::new()));
+        let recorder = Arc::new(RecordingSink::default());
+        let sink: Arc<dyn EventSink> = recorder.clone();
+        let policy = AgingPolicy { stale_after_secs: u64::MAX, lost_after_secs: u64::MAX };
+        assert!(policy.validate().is_ok());
+        *state.aging.lock().unwrap() = policy;
+        *state.devices.lock().unwrap() = vec![seen_ago("old", "AA:00:00:00:00:01", 100_000, false)];
+
+        // 超大超时视为永不过期，且不会在持有设备锁时溢出
+        sweep(&state, &sink);
+        assert!(recorder.take("device-stale").is_empty());
+        assert!(recorder.take("device-lost").is_empty());
+        assert!(!state.devices.lock().unwrap()[0].stale);
+    }
+
+    fn seen_ago(identifier: &str, address: &str, secs: u64, connected: bool) -> BleDevice {
+        BleDevice {
+            identifier: identifier.into(),
+            address: address.into(),
+            rssi: Some(-70),
+            last_seen: now_ms() - secs * 1000,
+            connected,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sweep_marks_stale_then_removes_lost() {
+        let state = AppState::new(Arc::new(SimulatedBackend::new()));
+        let recorder = Arc::new(RecordingSink::default());
+        let sink: Arc<dyn EventSink> = recorder.clone();
+        *state.devices.lock().unwrap() = vec![
+            seen_ago("fresh", "AA:00:00:00:00:01", 1, false),
+            seen_ago("quiet", "AA:00:00:00:00:02", 40, false),
+            seen_ago("gone", "AA:00:00:00:00:03", 200, false),
+            seen_ago("linked", "AA:00:00:00:00:04", 200, true),
+        ];
+        state.rssi.lock().unwrap().record(&state.devices.lock().unwrap()[2]);
+
+        sweep(&state, &sink);
+        let ids = |events: Vec<serde_json::Value>| events.iter().map(|e| e["deviceId"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(ids(recorder.take("device-stale")), vec!["quiet"]);
+        assert_eq!(ids(recorder.take("device-lost")), vec!["gone"]);
+        assert!(state.rssi.lock().unwrap().history("AA:00:00:00:00:03").is_none());
+
+        let remaining: Vec<(String, bool)> = state.devices.lock().unwrap().iter().map(|d| (d.identifier.clone(), d.stale)).collect();
+        assert_eq!(remaining, vec![("fresh".into(), false), ("quiet".into(), true), ("linked".into(), false)]);
+
+        // 已标记 stale 的设备不重复推送；超过 lost 超时后移除
+        sweep(&state, &sink);
+        assert!(recorder.take("device-stale").is_empty());
+        state.devices.lock().unwrap()[1].last_seen = now_ms() - 121_000;
+        sweep(&state, &sink);
+        assert_eq!(ids(recorder.take("device-lost")), vec!["quiet"]);
+        assert_eq!(state.devices.lock().unwrap().len(), 2);
+    }
+}
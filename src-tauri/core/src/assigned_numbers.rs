@@ -0,0 +1,440 @@
+// 蓝牙 SIG 分配编号：公司 ID、16 位服务/特征/描述符 UUID、外观值与 AD 类型的名称查询。
+// 公司 ID 与特征名称清单放在 data/ 下，编译时嵌入，前端经 get_assigned_numbers 获取；其余表格较小，直接写在这里
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::adv::normalize_uuid;
+
+const COMPANY_IDENTIFIERS: &str = include_str!("../data/company_identifiers.txt");
+const CHARACTERISTIC_NAMES: &str = include_str!("../data/characteristic_names.txt");
+
+const BASE_UUID_SUFFIX: &str = "-0000-1000-8000-00805F9B34FB";
+
+const SERVICES: &[(u16, &str)] = &[
+    (0x1800, "Generic Access"),
+    (0x1801, "Generic Attribute"),
+    (0x1802, "Immediate Alert"),
+    (0x1803, "Link Loss"),
+    (0x1804, "Tx Power"),
+    (0x1805, "Current Time"),
+    (0x1806, "Reference Time Update"),
+    (0x1807, "Next DST Change"),
+    (0x1808, "Glucose"),
+    (0x1809, "Health Thermometer"),
+    (0x180A, "Device Information"),
+    (0x180D, "Heart Rate"),
+    (0x180E, "Phone Alert Status"),
+    (0x180F, "Battery"),
+    (0x1810, "Blood Pressure"),
+    (0x1811, "Alert Notification"),
+    (0x1812, "Human Interface Device"),
+    (0x1813, "Scan Parameters"),
+    (0x1814, "Running Speed and Cadence"),
+    (0x1815, "Automation IO"),
+    (0x1816, "Cycling Speed and Cadence"),
+    (0x1818, "Cycling Power"),
+    (0x1819, "Location and Navigation"),
+    (0x181A, "Environmental Sensing"),
+    (0x181B, "Body Composition"),
+    (0x181C, "User Data"),
+    (0x181D, "Weight Scale"),
+    (0x181E, "Bond Management"),
+    (0x181F, "Continuous Glucose Monitoring"),
+    (0x1820, "Internet Protocol Support"),
+    (0x1821, "Indoor Positioning"),
+    (0x1822, "Pulse Oximeter"),
+    (0x1823, "HTTP Proxy"),
+    (0x1824, "Transport Discovery"),
+    (0x1825, "Object Transfer"),
+    (0x1826, "Fitness Machine"),
+    (0x1827, "Mesh Provisioning"),
+    (0x1828, "Mesh Proxy"),
+    (0x1829, "Reconnection Configuration"),
+    (0x183A, "Insulin Delivery"),
+    (0x183B, "Binary Sensor"),
+    (0x183C, "Emergency Configuration"),
+    (0x183D, "Authorization Control"),
+    (0x183E, "Physical Activity Monitor"),
+    (0x183F, "Elapsed Time"),
+    (0x1840, "Generic Health Sensor"),
+    (0x1843, "Audio Input Control"),
+    (0x1844, "Volume Control"),
+    (0x1845, "Volume Offset Control"),
+    (0x1846, "Coordinated Set Identification"),
+    (0x1847, "Device Time"),
+    (0x1848, "Media Control"),
+    (0x1849, "Generic Media Control"),
+    (0x184A, "Constant Tone Extension"),
+    (0x184B, "Telephone Bearer"),
+    (0x184C, "Generic Telephone Bearer"),
+    (0x184D, "Microphone Control"),
+    (0x184E, "Audio Stream Control"),
+    (0x184F, "Broadcast Audio Scan"),
+    (0x1850, "Published Audio Capabilities"),
+    (0x1851, "Basic Audio Announcement"),
+    (0x1852, "Broadcast Audio Announcement"),
+    (0x1853, "Common Audio"),
+    (0x1854, "Hearing Access"),
+    (0x1855, "Telephony and Media Audio"),
+    (0x1856, "Public Broadcast Announcement"),
+    (0x1857, "Electronic Shelf Label"),
+    (0x1858, "Gaming Audio"),
+    (0x1859, "Mesh Proxy Solicitation"),
+    // 成员服务 UUID（0xFxxx）中常见的几项
+    (0xFD6F, "Exposure Notification"),
+    (0xFE2C, "Google Fast Pair"),
+    (0xFE59, "Nordic Secure DFU"),
+    (0xFE9F, "Google"),
+    (0xFEAA, "Eddystone"),
+    (0xFEED, "Tile"),
+];
+
+const DESCRIPTORS: &[(u16, &str)] = &[
+    (0x2900, "Characteristic Extended Properties"),
+    (0x2901, "Characteristic User Description"),
+    (0x2902, "Client Characteristic Configuration"),
+    (0x2903, "Server Characteristic Configuration"),
+    (0x2904, "Characteristic Presentation Format"),
+    (0x2905, "Characteristic Aggregate Format"),
+    (0x2906, "Valid Range"),
+    (0x2907, "External Report Reference"),
+    (0x2908, "Report Reference"),
+    (0x2909, "Number of Digitals"),
+    (0x290A, "Value Trigger Setting"),
+    (0x290B, "Environmental Sensing Configuration"),
+    (0x290C, "Environmental Sensing Measurement"),
+    (0x290D, "Environmental Sensing Trigger Setting"),
+    (0x290E, "Time Trigger Setting"),
+    (0x290F, "Complete BR-EDR Transport Block Data"),
+    (0x2910, "Observation Schedule"),
+    (0x2911, "Valid Range and Accuracy"),
+    (0x2912, "Measurement Description"),
+    (0x2913, "Manufacturer Limits"),
+    (0x2914, "Process Tolerances"),
+    (0x2915, "IMD Trigger Setting"),
+];
+
+// 单位（org.bluetooth.unit），用于特征呈现格式描述符；取常用部分，值为显示符号
+const UNITS: &[(u16, &str)] = &[
+    (0x2700, ""),
+    (0x2701, "m"),
+    (0x2702, "kg"),
+    (0x2703, "s"),
+    (0x2704, "A"),
+    (0x2705, "K"),
+    (0x2706, "mol"),
+    (0x2707, "cd"),
+    (0x2710, "m²"),
+    (0x2711, "m³"),
+    (0x2712, "m/s"),
+    (0x2713, "m/s²"),
+    (0x2722, "Hz"),
+    (0x2723, "N"),
+    (0x2724, "Pa"),
+    (0x2725, "J"),
+    (0x2726, "W"),
+    (0x2727, "C"),
+    (0x2728, "V"),
+    (0x2729, "F"),
+    (0x272A, "Ω"),
+    (0x272B, "S"),
+    (0x272D, "T"),
+    (0x272F, "°C"),
+    (0x2730, "lm"),
+    (0x2731, "lx"),
+    (0x2760, "min"),
+    (0x2761, "h"),
+    (0x2762, "d"),
+    (0x2763, "°"),
+    (0x2767, "L"),
+    (0x2780, "bar"),
+    (0x2781, "mmHg"),
+    (0x27A2, "in"),
+    (0x27A3, "ft"),
+    (0x27A4, "mi"),
+    (0x27A5, "psi"),
+    (0x27A6, "km/h"),
+    (0x27A7, "mph"),
+    (0x27A8, "rpm"),
+    (0x27A9, "cal"),
+    (0x27AA, "kcal"),
+    (0x27AB, "kWh"),
+    (0x27AC, "°F"),
+    (0x27AD, "%"),
+    (0x27AE, "‰"),
+    (0x27AF, "bpm"),
+    (0x27B0, "Ah"),
+    (0x27B1, "mg/dL"),
+    (0x27B2, "mmol/L"),
+    (0x27B3, "y"),
+    (0x27B4, "mo"),
+    (0x27B8, "lb"),
+];
+
+const AD_TYPES: &[(u8, &str)] = &[
+    (0x01, "Flags"),
+    (0x02, "Incomplete List of 16-bit Service UUIDs"),
+    (0x03, "Complete List of 16-bit Service UUIDs"),
+    (0x04, "Incomplete List of 32-bit Service UUIDs"),
+    (0x05, "Complete List of 32-bit Service UUIDs"),
+    (0x06, "Incomplete List of 128-bit Service UUIDs"),
+    (0x07, "Complete List of 128-bit Service UUIDs"),
+    (0x08, "Shortened Local Name"),
+    (0x09, "Complete Local Name"),
+    (0x0A, "Tx Power Level"),
+    (0x0D, "Class of Device"),
+    (0x0E, "Simple Pairing Hash C-192"),
+    (0x0F, "Simple Pairing Randomizer R-192"),
+    (0x10, "Device ID / Security Manager TK Value"),
+    (0x11, "Security Manager Out of Band Flags"),
+    (0x12, "Peripheral Connection Interval Range"),
+    (0x14, "List of 16-bit Service Solicitation UUIDs"),
+    (0x15, "List of 128-bit Service Solicitation UUIDs"),
+    (0x16, "Service Data - 16-bit UUID"),
+    (0x17, "Public Target Address"),
+    (0x18, "Random Target Address"),
+    (0x19, "Appearance"),
+    (0x1A, "Advertising Interval"),
+    (0x1B, "LE Bluetooth Device Address"),
+    (0x1C, "LE Role"),
+    (0x1D, "Simple Pairing Hash C-256"),
+    (0x1E, "Simple Pairing Randomizer R-256"),
+    (0x1F, "List of 32-bit Service Solicitation UUIDs"),
+    (0x20, "Service Data - 32-bit UUID"),
+    (0x21, "Service Data - 128-bit UUID"),
+    (0x22, "LE Secure Connections Confirmation Value"),
+    (0x23, "LE Secure Connections Random Value"),
+    (0x24, "URI"),
+    (0x25, "Indoor Positioning"),
+    (0x26, "Transport Discovery Data"),
+    (0x27, "LE Supported Features"),
+    (0x28, "Channel Map Update Indication"),
+    (0x29, "PB-ADV"),
+    (0x2A, "Mesh Message"),
+    (0x2B, "Mesh Beacon"),
+    (0x2C, "BIGInfo"),
+    (0x2D, "Broadcast_Code"),
+    (0x2E, "Resolvable Set Identifier"),
+    (0x2F, "Advertising Interval - long"),
+    (0x30, "Broadcast Name"),
+    (0x31, "Encrypted Advertising Data"),
+    (0x32, "Periodic Advertising Response Timing Information"),
+    (0x34, "Electronic Shelf Label"),
+    (0x3D, "3D Information Data"),
+    (0xFF, "Manufacturer Specific Data"),
+];
+
+// 外观类别（外观值高 10 位）
+const APPEARANCE_CATEGORIES: &[(u16, &str)] = &[
+    (0, "Unknown"),
+    (1, "Phone"),
+    (2, "Computer"),
+    (3, "Watch"),
+    (4, "Clock"),
+    (5, "Display"),
+    (6, "Remote Control"),
+    (7, "Eye-glasses"),
+    (8, "Tag"),
+    (9, "Keyring"),
+    (10, "Media Player"),
+    (11, "Barcode Scanner"),
+    (12, "Thermometer"),
+    (13, "Heart Rate Sensor"),
+    (14, "Blood Pressure"),
+    (15, "Human Interface Device"),
+    (16, "Glucose Meter"),
+    (17, "Running Walking Sensor"),
+    (18, "Cycling"),
+    (19, "Control Device"),
+    (20, "Network Device"),
+    (21, "Sensor"),
+    (22, "Light Fixtures"),
+    (23, "Fan"),
+    (24, "HVAC"),
+    (25, "Air Conditioning"),
+    (26, "Humidifier"),
+    (27, "Heating"),
+    (28, "Access Control"),
+    (29, "Motorized Device"),
+    (30, "Power Device"),
+    (31, "Light Source"),
+    (32, "Window Covering"),
+    (33, "Audio Sink"),
+    (34, "Audio Source"),
+    (35, "Motorized Vehicle"),
+    (36, "Domestic Appliance"),
+    (37, "Wearable Audio Device"),
+    (38, "Aircraft"),
+    (39, "AV Equipment"),
+    (40, "Display Equipment"),
+    (41, "Hearing aid"),
+    (42, "Gaming"),
+    (43, "Signage"),
+    (49, "Pulse Oximeter"),
+    (50, "Weight Scale"),
+    (51, "Personal Mobility Device"),
+    (52, "Continuous Glucose Monitor"),
+    (53, "Insulin Pump"),
+    (54, "Medication Delivery"),
+    (55, "Spirometer"),
+    (81, "Outdoor Sports Activity"),
+];
+
+// 外观子类别（完整 16 位外观值）
+const APPEARANCE_SUBCATEGORIES: &[(u16, &str)] = &[
+    (0x00C1, "Sports Watch"),
+    (0x00C2, "Smartwatch"),
+    (0x0301, "Ear Thermometer"),
+    (0x0341, "Heart Rate Belt"),
+    (0x0381, "Arm Blood Pressure"),
+    (0x0382, "Wrist Blood Pressure"),
+    (0x03C1, "Keyboard"),
+    (0x03C2, "Mouse"),
+    (0x03C3, "Joystick"),
+    (0x03C4, "Gamepad"),
+    (0x03C5, "Digitizer Tablet"),
+    (0x03C6, "Card Reader"),
+    (0x03C7, "Digital Pen"),
+    (0x03C8, "Barcode Scanner"),
+    (0x0441, "In-Shoe Running Walking Sensor"),
+    (0x0442, "On-Shoe Running Walking Sensor"),
+    (0x0443, "On-Hip Running Walking Sensor"),
+    (0x0481, "Cycling Computer"),
+    (0x0482, "Speed Sensor"),
+    (0x0483, "Cadence Sensor"),
+    (0x0484, "Power Sensor"),
+    (0x0485, "Speed and Cadence Sensor"),
+    (0x0841, "Standalone Speaker"),
+    (0x0842, "Soundbar"),
+    (0x0843, "Bookshelf Speaker"),
+    (0x0844, "Standmounted Speaker"),
+    (0x0845, "Speakerphone"),
+    (0x0941, "Earbud"),
+    (0x0942, "Headset"),
+    (0x0943, "Headphones"),
+    (0x0944, "Neck Band"),
+    (0x0A41, "In-ear hearing aid"),
+    (0x0A42, "Behind-ear hearing aid"),
+    (0x0A43, "Cochlear Implant"),
+    (0x0C41, "Fingertip Pulse Oximeter"),
+    (0x0C42, "Wrist Worn Pulse Oximeter"),
+];
+
+// 解析 "0xNNNN<TAB>名称" 格式的清单，忽略注释、表头与空行
+fn parse_table(text: &'static str) -> HashMap<u16, &'static str> {
+    text.lines()
+        .filter_map(|line| {
+            let (id, name) = line.split_once('\t')?;
+            let id = u16::from_str_radix(id.trim().strip_prefix("0x")?, 16).ok()?;
+            let name = name.trim();
+            (!name.is_empty()).then_some((id, name))
+        })
+        .collect()
+}
+
+fn companies() -> &'static HashMap<u16, &'static str> {
+    static TABLE: OnceLock<HashMap<u16, &'static str>> = OnceLock::new();
+    TABLE.get_or_init(|| parse_table(COMPANY_IDENTIFIERS))
+}
+
+fn characteristics() -> &'static HashMap<u16, &'static str> {
+    static TABLE: OnceLock<HashMap<u16, &'static str>> = OnceLock::new();
+    TABLE.get_or_init(|| parse_table(CHARACTERISTIC_NAMES))
+}
+
+fn lookup<K: PartialEq>(table: &'static [(K, &'static str)], key: K) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, name)| *name)
+}
+
+// 基于蓝牙基础 UUID 的 16 位短 UUID；自定义 128 位 UUID 返回 None
+pub fn short_uuid(uuid: &str) -> Option<u16> {
+    let full = normalize_uuid(uuid);
+    let short = full.strip_prefix("0000")?.strip_suffix(BASE_UUID_SUFFIX)?;
+    u16::from_str_radix(short, 16).ok()
+}
+
+pub fn company_name(company_id: u16) -> Option<&'static str> {
+    companies().get(&company_id).copied()
+}
+
+pub fn service_name(uuid: &str) -> Option<&'static str> {
+    lookup(SERVICES, short_uuid(uuid)?)
+}
+
+pub fn characteristic_name(uuid: &str) -> Option<&'static str> {
+    characteristics().get(&short_uuid(uuid)?).copied()
+}
+
+pub fn descriptor_name(uuid: &str) -> Option<&'static str> {
+    lookup(DESCRIPTORS, short_uuid(uuid)?)
+}
+
+pub fn unit_symbol(unit: u16) -> Option<&'static str> {
+    lookup(UNITS, unit)
+}
+
+pub fn ad_type_name(ad_type: u8) -> Option<&'static str> {
+    lookup(AD_TYPES, ad_type)
+}
+
+// 外观值名称：优先子类别，否则取类别（子类别为 0 时即“通用”类别）
+pub fn appearance_name(appearance: u16) -> Option<&'static str> {
+    lookup(APPEARANCE_SUBCATEGORIES, appearance).or_else(|| lookup(APPEARANCE_CATEGORIES, appearance >> 6))
+}
+
+// 前端使用的名称表，key 为 4 位大写十六进制
+#[derive(Debug, Clone, Serialize)]
+pub struct AssignedNumbers {
+    pub companies: BTreeMap<String, &'static str>,
+    pub characteristics: BTreeMap<String, &'static str>,
+}
+
+pub async fn get_assigned_numbers() -> Result<AssignedNumbers, String> {
+    let keyed = |table: &HashMap<u16, &'static str>| table.iter().map(|(id, name)| (format!("{:04X}", id), *name)).collect();
+    Ok(AssignedNumbers { companies: keyed(companies()), characteristics: keyed(characteristics()) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_uuid_accepts_every_form() {
+        for uuid in ["180D", "180d", "0x180D", "0X180d", " 0000180D ", "0000180d-0000-1000-8000-00805f9b34fb"] {
+            assert_eq!(short_uuid(uuid), Some(0x180D), "{}", uuid);
+        }
+        // 32 位与自定义 128 位 UUID 没有 16 位形式
+        assert_eq!(short_uuid("0001180D"), None);
+        assert_eq!(short_uuid("6E400001-B5A3-F393-E0A9-E50E24DCCA9E"), None);
+        assert_eq!(short_uuid("xyz"), None);
+    }
+
+    #[test]
+    fn names_from_embedded_tables() {
+        assert_eq!(company_name(0x004C), Some("Apple, Inc."));
+        assert_eq!(company_name(0xFFFF), None);
+        assert_eq!(characteristic_name("0x2A37"), Some("Heart Rate Measurement"));
+        assert_eq!(service_name("0000180F-0000-1000-8000-00805F9B34FB"), Some("Battery"));
+        assert!(companies().len() > 3000);
+    }
+
+    #[test]
+    fn appearance_falls_back_to_category() {
+        assert_eq!(appearance_name(0x00C1), Some("Sports Watch"));
+        assert_eq!(appearance_name(0x00C0), Some("Watch"));
+        // 未登记的子类别退回所属类别
+        assert_eq!(appearance_name(0x00FF), Some("Watch"));
+        assert_eq!(appearance_name(0x0000), Some("Unknown"));
+        assert_eq!(appearance_name(0xFFC0), None);
+    }
+
+    #[tokio::test]
+    async fn assigned_numbers_are_keyed_by_padded_hex() {
+        let tables = get_assigned_numbers().await.unwrap();
+        assert_eq!(tables.companies.get("0059"), Some(&"Nordic Semiconductor ASA"));
+        assert_eq!(tables.characteristics.get("2A37"), Some(&"Heart Rate Measurement"));
+        assert!(tables.companies.keys().all(|k| k.len() == 4));
+    }
+}
@@ -0,0 +1,150 @@
+// BLE 后端抽象
+//
+// 所有命令只通过这里的 trait 访问蓝牙：BleBackend 负责枚举适配器，
+// BleCentral 对应一个适配器（扫描、已配对列表），BlePeripheral 对应一个外设（连接、GATT）。
+// SimpleBLE 是真实实现；simulated 为纯内存实现，可在没有蓝牙硬件的 CI 上运行。
+
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub mod sim;
+#[cfg(feature = "simpleble")]
+pub mod simpleble;
+
+// 与命令层保持一致，错误统一为可直接返回前端的字符串
+pub type BleResult<T> = Result<T, String>;
+
+// 访问特征所需的安全级别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecurityLevel {
+    #[default]
+    None,
+    Encryption,
+    Authentication,
+    Authorization,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityRequirements {
+    pub read: SecurityLevel,
+    pub write: SecurityLevel,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharacteristicInfo {
+    pub uuid: String,
+    pub can_read: bool,
+    pub can_write_request: bool,
+    pub can_write_command: bool,
+    pub can_notify: bool,
+    pub can_indicate: bool,
+    // 以下属性位并非所有后端都能提供，取不到时为 false
+    #[serde(default)]
+    pub can_broadcast: bool,
+    #[serde(default)]
+    pub can_write_signed: bool,
+    #[serde(default)]
+    pub has_extended_properties: bool,
+    // 描述符 UUID（CCCD、用户描述、呈现格式等）
+    #[serde(default)]
+    pub descriptors: Vec<String>,
+    // 后端不暴露安全要求时为 None
+    #[serde(default)]
+    pub security: Option<SecurityRequirements>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    pub uuid: String,
+    // 广播中携带的服务数据（扫描阶段），连接后枚举的服务通常为空
+    pub data: Vec<u8>,
+    pub characteristics: Vec<CharacteristicInfo>,
+}
+
+pub enum ScanEvent {
+    Start,
+    Stop,
+    Found(Arc<dyn BlePeripheral>),
+    Updated(Arc<dyn BlePeripheral>),
+}
+
+// 外设连接回调
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    Connected,
+    Disconnected,
+}
+
+pub trait BleBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn bluetooth_enabled(&self) -> BleResult<bool>;
+    fn adapters(&self) -> BleResult<Vec<Arc<dyn BleCentral>>>;
+}
+
+pub trait BleCentral: Send + Sync {
+    fn identifier(&self) -> BleResult<String>;
+    fn address(&self) -> BleResult<String>;
+    fn scan_start(&self) -> BleResult<()>;
+    fn scan_stop(&self) -> BleResult<()>;
+    // 每次调用返回一个新的事件流，只接收调用之后发生的事件
+    fn scan_events(&self) -> BoxStream<'static, BleResult<ScanEvent>>;
+    fn scan_results(&self) -> BleResult<Vec<Arc<dyn BlePeripheral>>>;
+    // 系统中保存了绑定信息的外设
+    fn paired_peripherals(&self) -> BleResult<Vec<Arc<dyn BlePeripheral>>>;
+}
+
+pub trait BlePeripheral: Send + Sync {
+    fn identifier(&self) -> BleResult<String>;
+    fn address(&self) -> BleResult<String>;
+    fn rssi(&self) -> BleResult<i16>;
+    fn tx_power(&self) -> BleResult<i16>;
+    fn mtu(&self) -> BleResult<u16>;
+    fn is_connectable(&self) -> BleResult<bool>;
+    fn is_connected(&self) -> BleResult<bool>;
+    // 当前是否已与外设配对（由协议栈报告）；是否已绑定见 BleCentral::paired_peripherals
+    fn is_paired(&self) -> BleResult<bool>;
+    fn manufacturer_data(&self) -> BleResult<HashMap<u16, Vec<u8>>>;
+    fn services(&self) -> BleResult<Vec<ServiceInfo>>;
+    fn connect(&self) -> BleResult<()>;
+    fn disconnect(&self) -> BleResult<()>;
+    // 在已建立的连接上发起配对并保存绑定信息（未连接时返回错误）；unpair 删除绑定
+    fn pair(&self) -> BleResult<()>;
+    fn unpair(&self) -> BleResult<()>;
+    // 连接/断开回调流，包括链路意外断开；每次调用返回一个新的流
+    fn connection_events(&self) -> BoxStream<'static, ConnectionEvent>;
+    fn read(&self, service: &str, characteristic: &str) -> BleResult<Vec<u8>>;
+    fn write_request(&self, service: &str, characteristic: &str, data: &[u8]) -> BleResult<()>;
+    fn write_command(&self, service: &str, characteristic: &str, data: &[u8]) -> BleResult<()>;
+    // Prepare Write：按偏移排队一段数据，返回外设回显的数据（Reliable Write 据此校验）；
+    // execute_write 提交（commit = true）或取消当前连接上排队的所有数据
+    fn prepare_write(&self, service: &str, characteristic: &str, offset: u16, data: &[u8]) -> BleResult<Vec<u8>>;
+    fn execute_write(&self, commit: bool) -> BleResult<()>;
+    // 通知/指示流在退订或链路断开时结束
+    fn notify(&self, service: &str, characteristic: &str) -> BleResult<BoxStream<'static, Vec<u8>>>;
+    fn indicate(&self, service: &str, characteristic: &str) -> BleResult<BoxStream<'static, Vec<u8>>>;
+    fn unsubscribe(&self, service: &str, characteristic: &str) -> BleResult<()>;
+    fn read_descriptor(&self, service: &str, characteristic: &str, descriptor: &str) -> BleResult<Vec<u8>>;
+    fn write_descriptor(&self, service: &str, characteristic: &str, descriptor: &str, data: &[u8]) -> BleResult<()>;
+}
+
+// 按名称选择后端："sim"/"simulated" 为内置演示场景的模拟后端，其余为 SimpleBLE
+pub fn by_name(name: Option<&str>) -> Arc<dyn BleBackend> {
+    match name {
+        Some(v) if v.eq_ignore_ascii_case("sim") || v.eq_ignore_ascii_case("simulated") => {
+            Arc::new(sim::SimulatedBackend::demo())
+        }
+        #[cfg(feature = "simpleble")]
+        _ => Arc::new(simpleble::SimpleBleBackend),
+        // 未编译 SimpleBLE 时只能使用模拟后端
+        #[cfg(not(feature = "simpleble"))]
+        _ => Arc::new(sim::SimulatedBackend::demo()),
+    }
+}
+
+// 根据环境变量 BLESCANNER_BACKEND 选择后端
+pub fn from_env() -> Arc<dyn BleBackend> {
+    by_name(std::env::var("BLESCANNER_BACKEND").ok().as_deref())
+}
@@ -0,0 +1,1214 @@
+// 内存模拟后端
+//
+// 用于无蓝牙硬件的开发与测试：可脚本化设备、广播内容、RSSI 抖动、GATT 表、
+// 通知推送以及链路断开。扫描期间由后台线程按广播间隔重复上报设备。
+
+use super::{
+    BleBackend, BleCentral, BlePeripheral, BleResult, CharacteristicInfo, ConnectionEvent, ScanEvent, SecurityLevel,
+    SecurityRequirements, ServiceInfo,
+};
+use crate::adv::normalize_uuid;
+use crate::long_write::MAX_ATTRIBUTE_LEN;
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct SimCharacteristic {
+    pub info: CharacteristicInfo,
+    pub value: Vec<u8>,
+    // 订阅后按间隔循环推送的通知内容
+    pub periodic: Option<(Duration, Vec<Vec<u8>>)>,
+    // 描述符及其初始值
+    pub descriptors: Vec<(String, Vec<u8>)>,
+}
+
+impl SimCharacteristic {
+    pub fn new(uuid: &str) -> Self {
+        Self {
+            info: CharacteristicInfo { uuid: normalize_uuid(uuid), ..Default::default() },
+            value: Vec::new(),
+            periodic: None,
+            descriptors: Vec::new(),
+        }
+    }
+
+    pub fn read(mut self) -> Self {
+        self.info.can_read = true;
+        self
+    }
+
+    pub fn write(mut self) -> Self {
+        self.info.can_write_request = true;
+        self
+    }
+
+    pub fn write_without_response(mut self) -> Self {
+        self.info.can_write_command = true;
+        self
+    }
+
+    // 支持通知/指示的特征自动带有 CCCD (0x2902)
+    pub fn notify(mut self) -> Self {
+        self.info.can_notify = true;
+        self.descriptor("2902", &[0x00, 0x00])
+    }
+
+    pub fn indicate(mut self) -> Self {
+        self.info.can_indicate = true;
+        self.descriptor("2902", &[0x00, 0x00])
+    }
+
+    pub fn broadcast(mut self) -> Self {
+        self.info.can_broadcast = true;
+        self
+    }
+
+    pub fn write_signed(mut self) -> Self {
+        self.info.can_write_signed = true;
+        self
+    }
+
+    // 扩展属性 (0x2900)：bit0 可靠写，bit1 可写辅助描述符
+    fn extended_property(mut self, bit: u8) -> Self {
+        self.info.has_extended_properties = true;
+        self = self.descriptor("2900", &[0x00, 0x00]);
+        if let Some((_, v)) = self.descriptors.iter_mut().find(|(u, _)| u.starts_with("00002900")) {
+            v[0] |= bit;
+        }
+        self
+    }
+
+    pub fn reliable_write(self) -> Self {
+        self.extended_property(0x01)
+    }
+
+    pub fn writable_auxiliaries(self) -> Self {
+        self.extended_property(0x02)
+    }
+
+    // 读写所需安全级别；未配对时访问返回错误
+    pub fn security(mut self, read: SecurityLevel, write: SecurityLevel) -> Self {
+        self.info.security = Some(SecurityRequirements { read, write });
+        self
+    }
+
+    pub fn descriptor(mut self, uuid: &str, value: &[u8]) -> Self {
+        let uuid = normalize_uuid(uuid);
+        if !self.info.descriptors.contains(&uuid) {
+            self.info.descriptors.push(uuid.clone());
+            self.descriptors.push((uuid, value.to_vec()));
+        }
+        self
+    }
+
+    pub fn value(mut self, value: &[u8]) -> Self {
+        self.value = value.to_vec();
+        self
+    }
+
+    pub fn periodic(mut self, interval: Duration, values: Vec<Vec<u8>>) -> Self {
+        self.periodic = Some((interval, values));
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimService {
+    pub uuid: String,
+    pub characteristics: Vec<SimCharacteristic>,
+}
+
+impl SimService {
+    pub fn new(uuid: &str) -> Self {
+        Self { uuid: normalize_uuid(uuid), characteristics: Vec::new() }
+    }
+
+    pub fn characteristic(mut self, c: SimCharacteristic) -> Self {
+        self.characteristics.push(c);
+        self
+    }
+}
+
+// 设备脚本：广播内容与 GATT 表
+#[derive(Debug, Clone)]
+pub struct SimDevice {
+    pub identifier: String,
+    pub address: String,
+    pub rssi: i16,
+    pub rssi_drift: i16,
+    pub tx_power: Option<i16>,
+    pub connectable: bool,
+    pub paired: bool,
+    pub mtu: u16,
+    pub manufacturer_data: HashMap<u16, Vec<u8>>,
+    pub advertised_services: Vec<(String, Vec<u8>)>,
+    pub gatt: Vec<SimService>,
+}
+
+impl SimDevice {
+    pub fn new(identifier: &str, address: &str) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            address: address.to_string(),
+            rssi: -60,
+            rssi_drift: 0,
+            tx_power: None,
+            connectable: true,
+            paired: false,
+            mtu: 23,
+            manufacturer_data: HashMap::new(),
+            advertised_services: Vec::new(),
+            gatt: Vec::new(),
+        }
+    }
+
+    pub fn rssi(mut self, rssi: i16) -> Self {
+        self.rssi = rssi;
+        self
+    }
+
+    // 每次广播在基准 RSSI 上叠加 ±amplitude 的伪随机抖动
+    pub fn rssi_drift(mut self, amplitude: i16) -> Self {
+        self.rssi_drift = amplitude.abs();
+        self
+    }
+
+    pub fn tx_power(mut self, tx_power: i16) -> Self {
+        self.tx_power = Some(tx_power);
+        self
+    }
+
+    pub fn connectable(mut self, connectable: bool) -> Self {
+        self.connectable = connectable;
+        self
+    }
+
+    pub fn paired(mut self, paired: bool) -> Self {
+        self.paired = paired;
+        self
+    }
+
+    pub fn mtu(mut self, mtu: u16) -> Self {
+        self.mtu = mtu;
+        self
+    }
+
+    pub fn manufacturer_data(mut self, company_id: u16, data: &[u8]) -> Self {
+        self.manufacturer_data.insert(company_id, data.to_vec());
+        self
+    }
+
+    pub fn advertised_service(mut self, uuid: &str, data: &[u8]) -> Self {
+        self.advertised_services.push((normalize_uuid(uuid), data.to_vec()));
+        self
+    }
+
+    pub fn gatt_service(mut self, service: SimService) -> Self {
+        self.gatt.push(service);
+        self
+    }
+}
+
+type CharKey = (String, String);
+type DescKey = (String, String, String);
+
+fn char_key(service: &str, characteristic: &str) -> CharKey {
+    (normalize_uuid(service), normalize_uuid(characteristic))
+}
+
+fn desc_key(service: &str, characteristic: &str, descriptor: &str) -> DescKey {
+    (normalize_uuid(service), normalize_uuid(characteristic), normalize_uuid(descriptor))
+}
+
+const CCCD: &str = "00002902-0000-1000-8000-00805F9B34FB";
+
+struct PeripheralState {
+    device: SimDevice,
+    rssi: i16,
+    seed: u64,
+    connected: bool,
+    fail_connect: Option<String>,
+    corrupt_echo: bool, // Prepare Write 回显翻转首字节，模拟传输错误
+    values: HashMap<CharKey, Vec<u8>>,
+    descriptor_values: HashMap<DescKey, Vec<u8>>,
+    subscribers: HashMap<CharKey, Vec<UnboundedSender<Vec<u8>>>>,
+    connection_listeners: Vec<UnboundedSender<ConnectionEvent>>,
+    writes: Vec<(CharKey, Vec<u8>)>,
+    // Prepare Write 队列，Execute Write 时按特征合并写入
+    prepared: Vec<(CharKey, u16, Vec<u8>)>,
+}
+
+impl PeripheralState {
+    fn notify_connection(&mut self, event: ConnectionEvent) {
+        self.connection_listeners.retain(|tx| tx.unbounded_send(event).is_ok());
+    }
+
+    fn set_cccd(&mut self, key: &CharKey, value: [u8; 2]) {
+        if let Some(v) = self.descriptor_values.get_mut(&(key.0.clone(), key.1.clone(), CCCD.to_string())) {
+            *v = value.to_vec();
+        }
+    }
+
+    fn reset_cccds(&mut self) {
+        for (k, v) in self.descriptor_values.iter_mut() {
+            if k.2 == CCCD {
+                *v = vec![0x00, 0x00];
+            }
+        }
+    }
+}
+
+pub struct SimPeripheral {
+    state: Mutex<PeripheralState>,
+}
+
+impl SimPeripheral {
+    fn new(device: SimDevice, seed: u64) -> Self {
+        let mut values = HashMap::new();
+        let mut descriptor_values = HashMap::new();
+        for s in &device.gatt {
+            for c in &s.characteristics {
+                values.insert((s.uuid.clone(), c.info.uuid.clone()), c.value.clone());
+                for (d, value) in &c.descriptors {
+                    descriptor_values.insert((s.uuid.clone(), c.info.uuid.clone(), d.clone()), value.clone());
+                }
+            }
+        }
+        Self {
+            state: Mutex::new(PeripheralState {
+                rssi: device.rssi,
+                device,
+                seed,
+                connected: false,
+                fail_connect: None,
+                corrupt_echo: false,
+                values,
+                descriptor_values,
+                subscribers: HashMap::new(),
+                connection_listeners: Vec::new(),
+                writes: Vec::new(),
+                prepared: Vec::new(),
+            }),
+        }
+    }
+
+    // 线性同余发生器，保证测试可复现
+    fn drift(&self) {
+        let mut st = self.state.lock().unwrap();
+        let amplitude = st.device.rssi_drift;
+        if amplitude == 0 {
+            return;
+        }
+        st.seed = st.seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let offset = ((st.seed >> 33) % (2 * amplitude as u64 + 1)) as i16 - amplitude;
+        st.rssi = st.device.rssi + offset;
+    }
+
+    pub fn set_rssi(&self, rssi: i16) {
+        let mut st = self.state.lock().unwrap();
+        st.device.rssi = rssi;
+        st.rssi = rssi;
+    }
+
+    pub fn set_value(&self, service: &str, characteristic: &str, value: &[u8]) {
+        self.state.lock().unwrap().values.insert(char_key(service, characteristic), value.to_vec());
+    }
+
+    // 后续 connect() 返回该错误，传 None 恢复正常
+    pub fn fail_next_connects(&self, error: Option<&str>) {
+        self.state.lock().unwrap().fail_connect = error.map(|e| e.to_string());
+    }
+
+    // 后续 Prepare Write 的回显与写入数据不一致，传 false 恢复正常
+    pub fn corrupt_prepare_echo(&self, corrupt: bool) {
+        self.state.lock().unwrap().corrupt_echo = corrupt;
+    }
+
+    // 推送一条通知/指示给所有订阅者，同时更新特征值
+    pub fn push_notification(&self, service: &str, characteristic: &str, data: &[u8]) {
+        let key = char_key(service, characteristic);
+        let mut st = self.state.lock().unwrap();
+        if !st.connected {
+            return;
+        }
+        st.values.insert(key.clone(), data.to_vec());
+        if let Some(subs) = st.subscribers.get_mut(&key) {
+            subs.retain(|tx| tx.unbounded_send(data.to_vec()).is_ok());
+        }
+    }
+
+    // 模拟链路意外断开：所有订阅流随之结束
+    pub fn drop_link(&self) {
+        let mut st = self.state.lock().unwrap();
+        st.subscribers.clear();
+        st.prepared.clear();
+        st.reset_cccds();
+        if st.connected {
+            st.connected = false;
+            st.notify_connection(ConnectionEvent::Disconnected);
+        }
+    }
+
+    pub fn written_values(&self) -> Vec<(String, String, Vec<u8>)> {
+        let st = self.state.lock().unwrap();
+        st.writes.iter().map(|((s, c), d)| (s.clone(), c.clone(), d.clone())).collect()
+    }
+
+    fn characteristic(&self, key: &CharKey) -> BleResult<SimCharacteristic> {
+        let st = self.state.lock().unwrap();
+        st.device
+            .gatt
+            .iter()
+            .filter(|s| s.uuid == key.0)
+            .flat_map(|s| s.characteristics.iter())
+            .find(|c| c.info.uuid == key.1)
+            .cloned()
+            .ok_or_else(|| format!("特征不存在: {}/{}", key.0, key.1))
+    }
+
+    // 模拟后端只区分是否已配对：任何非 None 的安全级别都要求先配对
+    fn ensure_access(&self, c: &SimCharacteristic, write: bool) -> BleResult<()> {
+        let level = c.info.security.map(|s| if write { s.write } else { s.read }).unwrap_or_default();
+        if level != SecurityLevel::None && !self.state.lock().unwrap().device.paired {
+            return Err(format!("特征需要 {:?} 级别的安全连接，请先配对: {}", level, c.info.uuid));
+        }
+        Ok(())
+    }
+
+    fn ensure_connected(&self) -> BleResult<()> {
+        if self.state.lock().unwrap().connected {
+            Ok(())
+        } else {
+            Err("设备未连接".to_string())
+        }
+    }
+
+    fn subscribe(self: &Arc<Self>, service: &str, characteristic: &str, indicate: bool) -> BleResult<BoxStream<'static, Vec<u8>>> {
+        self.ensure_connected()?;
+        let key = char_key(service, characteristic);
+        let c = self.characteristic(&key)?;
+        if (indicate && !c.info.can_indicate) || (!indicate && !c.info.can_notify) {
+            return Err(format!("特征不支持{}: {}", if indicate { "指示" } else { "通知" }, key.1));
+        }
+        self.ensure_access(&c, false)?;
+        let (tx, rx) = unbounded();
+        {
+            let mut st = self.state.lock().unwrap();
+            st.subscribers.entry(key.clone()).or_default().push(tx.clone());
+            st.set_cccd(&key, if indicate { [0x02, 0x00] } else { [0x01, 0x00] });
+        }
+
+        if let Some((interval, values)) = c.periodic.filter(|(_, v)| !v.is_empty()) {
+            let weak = Arc::downgrade(self);
+            std::thread::spawn(move || {
+                let mut i = 0;
+                loop {
+                    std::thread::sleep(interval);
+                    let p = match weak.upgrade() {
+                        Some(p) => p,
+                        None => break,
+                    };
+                    if tx.is_closed() || !p.state.lock().unwrap().connected {
+                        break;
+                    }
+                    p.push_notification(&key.0, &key.1, &values[i % values.len()]);
+                    i += 1;
+                }
+            });
+        }
+        Ok(rx.boxed())
+    }
+}
+
+// trait 对象需要持有 Arc 以便周期通知线程弱引用外设
+struct SimPeripheralHandle(Arc<SimPeripheral>);
+
+impl BlePeripheral for SimPeripheralHandle {
+    fn identifier(&self) -> BleResult<String> {
+        Ok(self.0.state.lock().unwrap().device.identifier.clone())
+    }
+
+    fn address(&self) -> BleResult<String> {
+        Ok(self.0.state.lock().unwrap().device.address.clone())
+    }
+
+    fn rssi(&self) -> BleResult<i16> {
+        Ok(self.0.state.lock().unwrap().rssi)
+    }
+
+    fn tx_power(&self) -> BleResult<i16> {
+        Ok(self.0.state.lock().unwrap().device.tx_power.unwrap_or(i16::MIN))
+    }
+
+    fn mtu(&self) -> BleResult<u16> {
+        self.0.ensure_connected()?;
+        Ok(self.0.state.lock().unwrap().device.mtu)
+    }
+
+    fn is_connectable(&self) -> BleResult<bool> {
+        Ok(self.0.state.lock().unwrap().device.connectable)
+    }
+
+    fn is_connected(&self) -> BleResult<bool> {
+        Ok(self.0.state.lock().unwrap().connected)
+    }
+
+    fn is_paired(&self) -> BleResult<bool> {
+        Ok(self.0.state.lock().unwrap().device.paired)
+    }
+
+    fn manufacturer_data(&self) -> BleResult<HashMap<u16, Vec<u8>>> {
+        Ok(self.0.state.lock().unwrap().device.manufacturer_data.clone())
+    }
+
+    // 未连接时返回广播中的服务（带服务数据），连接后返回完整 GATT 表
+    fn services(&self) -> BleResult<Vec<ServiceInfo>> {
+        let st = self.0.state.lock().unwrap();
+        if !st.connected {
+            return Ok(st
+                .device
+                .advertised_services
+                .iter()
+                .map(|(uuid, data)| ServiceInfo { uuid: uuid.clone(), data: data.clone(), characteristics: Vec::new() })
+                .collect());
+        }
+        Ok(st
+            .device
+            .gatt
+            .iter()
+            .map(|s| ServiceInfo {
+                uuid: s.uuid.clone(),
+                data: Vec::new(),
+                characteristics: s.characteristics.iter().map(|c| c.info.clone()).collect(),
+            })
+            .collect())
+    }
+
+    fn connect(&self) -> BleResult<()> {
+        let mut st = self.0.state.lock().unwrap();
+        if !st.device.connectable {
+            return Err("设备不可连接".to_string());
+        }
+        if let Some(e) = st.fail_connect.clone() {
+            return Err(e);
+        }
+        if !st.connected {
+            st.connected = true;
+            st.notify_connection(ConnectionEvent::Connected);
+        }
+        Ok(())
+    }
+
+    fn disconnect(&self) -> BleResult<()> {
+        self.0.drop_link();
+        Ok(())
+    }
+
+    fn pair(&self) -> BleResult<()> {
+        let mut st = self.0.state.lock().unwrap();
+        if !st.connected {
+            return Err("设备未连接".to_string());
+        }
+        st.device.paired = true;
+        Ok(())
+    }
+
+    fn unpair(&self) -> BleResult<()> {
+        self.0.state.lock().unwrap().device.paired = false;
+        Ok(())
+    }
+
+    fn connection_events(&self) -> BoxStream<'static, ConnectionEvent> {
+        let (tx, rx) = unbounded();
+        self.0.state.lock().unwrap().connection_listeners.push(tx);
+        rx.boxed()
+    }
+
+    fn read(&self, service: &str, characteristic: &str) -> BleResult<Vec<u8>> {
+        self.0.ensure_connected()?;
+        let key = char_key(service, characteristic);
+        let c = self.0.characteristic(&key)?;
+        if !c.info.can_read {
+            return Err(format!("特征不可读: {}", key.1));
+        }
+        self.0.ensure_access(&c, false)?;
+        Ok(self.0.state.lock().unwrap().values.get(&key).cloned().unwrap_or_default())
+    }
+
+    fn write_request(&self, service: &str, characteristic: &str, data: &[u8]) -> BleResult<()> {
+        self.0.ensure_connected()?;
+        let key = char_key(service, characteristic);
+        let c = self.0.characteristic(&key)?;
+        if !c.info.can_write_request {
+            return Err(format!("特征不支持写请求: {}", key.1));
+        }
+        self.0.ensure_access(&c, true)?;
+        let mut st = self.0.state.lock().unwrap();
+        st.values.insert(key.clone(), data.to_vec());
+        st.writes.push((key, data.to_vec()));
+        Ok(())
+    }
+
+    fn write_command(&self, service: &str, characteristic: &str, data: &[u8]) -> BleResult<()> {
+        self.0.ensure_connected()?;
+        let key = char_key(service, characteristic);
+        let c = self.0.characteristic(&key)?;
+        if !c.info.can_write_command {
+            return Err(format!("特征不支持写命令: {}", key.1));
+        }
+        self.0.ensure_access(&c, true)?;
+        let mut st = self.0.state.lock().unwrap();
+        // 写命令无法分段，超过 MTU - 3 的数据会被协议栈拒绝
+        let limit = st.device.mtu.saturating_sub(3);
+        if data.len() > limit as usize {
+            return Err(format!("写命令数据 {} 字节超过 MTU - 3 ({})", data.len(), limit));
+        }
+        st.values.insert(key.clone(), data.to_vec());
+        st.writes.push((key, data.to_vec()));
+        Ok(())
+    }
+
+    // 偏移必须紧接该特征已排队的数据，单个特征合计不超过 512 字节
+    fn prepare_write(&self, service: &str, characteristic: &str, offset: u16, data: &[u8]) -> BleResult<Vec<u8>> {
+        self.0.ensure_connected()?;
+        let key = char_key(service, characteristic);
+        let c = self.0.characteristic(&key)?;
+        if !c.info.can_write_request {
+            return Err(format!("特征不支持写请求: {}", key.1));
+        }
+        self.0.ensure_access(&c, true)?;
+        let mut st = self.0.state.lock().unwrap();
+        let limit = st.device.mtu.saturating_sub(5);
+        if data.len() > limit as usize {
+            return Err(format!("Prepare Write 数据 {} 字节超过 MTU - 5 ({})", data.len(), limit));
+        }
+        let queued: usize = st.prepared.iter().filter(|(k, _, _)| *k == key).map(|(_, _, d)| d.len()).sum();
+        if offset as usize != queued {
+            return Err(format!("无效的写入偏移: {}（期望 {}）", offset, queued));
+        }
+        if queued + data.len() > MAX_ATTRIBUTE_LEN {
+            return Err(format!("写入长度超过属性最大长度 {} 字节", MAX_ATTRIBUTE_LEN));
+        }
+        st.prepared.push((key, offset, data.to_vec()));
+        let mut echo = data.to_vec();
+        if st.corrupt_echo {
+            if let Some(b) = echo.first_mut() {
+                *b ^= 0xFF;
+            }
+        }
+        Ok(echo)
+    }
+
+    fn execute_write(&self, commit: bool) -> BleResult<()> {
+        self.0.ensure_connected()?;
+        let mut st = self.0.state.lock().unwrap();
+        let prepared = std::mem::take(&mut st.prepared);
+        if !commit {
+            return Ok(());
+        }
+        let mut merged: Vec<(CharKey, Vec<u8>)> = Vec::new();
+        for (key, _, data) in prepared {
+            match merged.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, value)) => value.extend_from_slice(&data),
+                None => merged.push((key, data)),
+            }
+        }
+        for (key, value) in merged {
+            st.values.insert(key.clone(), value.clone());
+            st.writes.push((key, value));
+        }
+        Ok(())
+    }
+
+    fn notify(&self, service: &str, characteristic: &str) -> BleResult<BoxStream<'static, Vec<u8>>> {
+        self.0.subscribe(service, characteristic, false)
+    }
+
+    fn indicate(&self, service: &str, characteristic: &str) -> BleResult<BoxStream<'static, Vec<u8>>> {
+        self.0.subscribe(service, characteristic, true)
+    }
+
+    fn unsubscribe(&self, service: &str, characteristic: &str) -> BleResult<()> {
+        let key = char_key(service, characteristic);
+        let mut st = self.0.state.lock().unwrap();
+        st.subscribers.remove(&key);
+        st.set_cccd(&key, [0x00, 0x00]);
+        Ok(())
+    }
+
+    fn read_descriptor(&self, service: &str, characteristic: &str, descriptor: &str) -> BleResult<Vec<u8>> {
+        self.0.ensure_connected()?;
+        let key = desc_key(service, characteristic, descriptor);
+        self.0.characteristic(&(key.0.clone(), key.1.clone()))?;
+        self.0.state.lock().unwrap().descriptor_values.get(&key).cloned().ok_or_else(|| format!("描述符不存在: {}", key.2))
+    }
+
+    // CCCD 只接受 2 字节；写入不会建立通知流，订阅仍需通过 notify/indicate
+    fn write_descriptor(&self, service: &str, characteristic: &str, descriptor: &str, data: &[u8]) -> BleResult<()> {
+        self.0.ensure_connected()?;
+        let key = desc_key(service, characteristic, descriptor);
+        self.0.characteristic(&(key.0.clone(), key.1.clone()))?;
+        if key.2 == CCCD && data.len() != 2 {
+            return Err("CCCD 写入长度必须为 2 字节".to_string());
+        }
+        let mut st = self.0.state.lock().unwrap();
+        match st.descriptor_values.get_mut(&key) {
+            Some(v) => {
+                *v = data.to_vec();
+                Ok(())
+            }
+            None => Err(format!("描述符不存在: {}", key.2)),
+        }
+    }
+}
+
+struct AdapterState {
+    devices: Vec<Arc<SimPeripheral>>,
+    scanning: bool,
+    generation: u64,
+    seen: HashSet<String>,
+    listeners: Vec<UnboundedSender<BleResult<ScanEvent>>>,
+}
+
+pub struct SimAdapter {
+    identifier: String,
+    address: String,
+    interval: Mutex<Duration>,
+    state: Mutex<AdapterState>,
+}
+
+impl SimAdapter {
+    fn new(identifier: &str, address: &str) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            address: address.to_string(),
+            interval: Mutex::new(Duration::from_millis(500)),
+            state: Mutex::new(AdapterState {
+                devices: Vec::new(),
+                scanning: false,
+                generation: 0,
+                seen: HashSet::new(),
+                listeners: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn add_device(&self, device: SimDevice) -> Arc<SimPeripheral> {
+        let mut st = self.state.lock().unwrap();
+        let seed = 0x5EED ^ (st.devices.len() as u64 + 1);
+        let p = Arc::new(SimPeripheral::new(device, seed));
+        st.devices.push(p.clone());
+        p
+    }
+
+    // 设备停止广播（离开范围），已连接的链路同时断开
+    pub fn remove_device(&self, identifier: &str) {
+        let mut st = self.state.lock().unwrap();
+        st.devices.retain(|p| {
+            let keep = p.state.lock().unwrap().device.identifier != identifier;
+            if !keep {
+                p.drop_link();
+            }
+            keep
+        });
+        st.seen.remove(identifier);
+    }
+
+    pub fn device(&self, identifier: &str) -> Option<Arc<SimPeripheral>> {
+        let st = self.state.lock().unwrap();
+        st.devices.iter().find(|p| p.state.lock().unwrap().device.identifier == identifier).cloned()
+    }
+
+    pub fn set_advertising_interval(&self, interval: Duration) {
+        *self.interval.lock().unwrap() = interval;
+    }
+
+    fn emit(st: &mut AdapterState, make: impl Fn() -> ScanEvent) {
+        st.listeners.retain(|tx| tx.unbounded_send(Ok(make())).is_ok());
+    }
+
+    // 执行一轮广播：每个设备上报一次 Found（首次）或 Updated
+    pub fn advertise_once(&self) {
+        let mut st = self.state.lock().unwrap();
+        if !st.scanning {
+            return;
+        }
+        for p in st.devices.clone() {
+            p.drift();
+            let id = p.state.lock().unwrap().device.identifier.clone();
+            let first = st.seen.insert(id);
+            let handle: Arc<dyn BlePeripheral> = Arc::new(SimPeripheralHandle(p));
+            Self::emit(&mut st, || if first { ScanEvent::Found(handle.clone()) } else { ScanEvent::Updated(handle.clone()) });
+        }
+    }
+}
+
+// 包装 Arc<SimAdapter> 以便后台广播线程持有
+struct SimCentral(Arc<SimAdapter>);
+
+impl BleCentral for SimCentral {
+    fn identifier(&self) -> BleResult<String> {
+        Ok(self.0.identifier.clone())
+    }
+
+    fn address(&self) -> BleResult<String> {
+        Ok(self.0.address.clone())
+    }
+
+    fn scan_start(&self) -> BleResult<()> {
+        let generation = {
+            let mut st = self.0.state.lock().unwrap();
+            if st.scanning {
+                return Ok(());
+            }
+            st.scanning = true;
+            st.generation += 1;
+            st.seen.clear();
+            SimAdapter::emit(&mut st, || ScanEvent::Start);
+            st.generation
+        };
+        self.0.advertise_once();
+
+        let adapter = self.0.clone();
+        std::thread::spawn(move || loop {
+            let interval = *adapter.interval.lock().unwrap();
+            std::thread::sleep(interval);
+            {
+                let st = adapter.state.lock().unwrap();
+                if !st.scanning || st.generation != generation {
+                    break;
+                }
+            }
+            adapter.advertise_once();
+        });
+        Ok(())
+    }
+
+    fn scan_stop(&self) -> BleResult<()> {
+        let mut st = self.0.state.lock().unwrap();
+        if st.scanning {
+            st.scanning = false;
+            SimAdapter::emit(&mut st, || ScanEvent::Stop);
+        }
+        Ok(())
+    }
+
+    fn scan_events(&self) -> BoxStream<'static, BleResult<ScanEvent>> {
+        let (tx, rx) = unbounded();
+        self.0.state.lock().unwrap().listeners.push(tx);
+        rx.boxed()
+    }
+
+    fn scan_results(&self) -> BleResult<Vec<Arc<dyn BlePeripheral>>> {
+        let st = self.0.state.lock().unwrap();
+        Ok(st
+            .devices
+            .iter()
+            .filter(|p| st.seen.contains(&p.state.lock().unwrap().device.identifier))
+            .map(|p| Arc::new(SimPeripheralHandle(p.clone())) as Arc<dyn BlePeripheral>)
+            .collect())
+    }
+
+    fn paired_peripherals(&self) -> BleResult<Vec<Arc<dyn BlePeripheral>>> {
+        let st = self.0.state.lock().unwrap();
+        Ok(st
+            .devices
+            .iter()
+            .filter(|p| p.state.lock().unwrap().device.paired)
+            .map(|p| Arc::new(SimPeripheralHandle(p.clone())) as Arc<dyn BlePeripheral>)
+            .collect())
+    }
+}
+
+pub struct SimulatedBackend {
+    enabled: AtomicBool,
+    adapters: Mutex<Vec<Arc<SimAdapter>>>,
+}
+
+impl Default for SimulatedBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedBackend {
+    pub fn new() -> Self {
+        Self { enabled: AtomicBool::new(true), adapters: Mutex::new(Vec::new()) }
+    }
+
+    pub fn add_adapter(&self, identifier: &str, address: &str) -> Arc<SimAdapter> {
+        let adapter = Arc::new(SimAdapter::new(identifier, address));
+        self.adapters.lock().unwrap().push(adapter.clone());
+        adapter
+    }
+
+    pub fn set_bluetooth_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    // 内置演示场景：心率带、iBeacon 与串口透传设备
+    pub fn demo() -> Self {
+        let backend = Self::new();
+        let adapter = backend.add_adapter("sim0", "00:11:22:33:44:55");
+        adapter.add_device(
+            SimDevice::new("SIM-HRM", "C0:FF:EE:00:00:01")
+                .rssi(-58)
+                .rssi_drift(4)
+                .tx_power(-4)
+                .advertised_service("180D", &[])
+                .gatt_service(
+                    SimService::new("180D")
+                        .characteristic(
+                            SimCharacteristic::new("2A37")
+                                .notify()
+                                .periodic(Duration::from_secs(1), vec![vec![0x00, 72], vec![0x00, 74], vec![0x00, 71]]),
+                        )
+                        .characteristic(SimCharacteristic::new("2A38").read().value(&[0x01])),
+                )
+                .gatt_service(SimService::new("180F").characteristic(SimCharacteristic::new("2A19").read().notify().value(&[87])))
+                .gatt_service(
+                    SimService::new("180A").characteristic(SimCharacteristic::new("2A29").read().value(b"BleScanner Sim")),
+                ),
+        );
+        adapter.add_device(
+            SimDevice::new("", "C0:FF:EE:00:00:02")
+                .rssi(-71)
+                .rssi_drift(6)
+                .connectable(false)
+                .manufacturer_data(
+                    0x004C,
+                    &hex::decode("0215E2C56DB5DFFB48D2B060D0F5A71096E000010002C5").unwrap_or_default(),
+                ),
+        );
+        adapter.add_device(
+            SimDevice::new("SIM-UART", "C0:FF:EE:00:00:03")
+                .rssi(-66)
+                .rssi_drift(3)
+                .mtu(247)
+                .manufacturer_data(0x0059, &[0x01, 0x02])
+                .gatt_service(
+                    SimService::new("6E400001-B5A3-F393-E0A9-E50E24DCCA9E")
+                        .characteristic(
+                            SimCharacteristic::new("6E400002-B5A3-F393-E0A9-E50E24DCCA9E")
+                                .write()
+                                .write_without_response()
+                                .reliable_write(),
+                        )
+                        .characteristic(
+                            SimCharacteristic::new("6E400003-B5A3-F393-E0A9-E50E24DCCA9E")
+                                .notify()
+                                .descriptor("2901", b"UART TX"),
+                        )
+                        // 自定义板载温度：sint16，指数 -2，摄氏度，有效范围 -40.00 ~ 85.00
+                        .characteristic(
+                            SimCharacteristic::new("6E400004-B5A3-F393-E0A9-E50E24DCCA9E")
+                                .read()
+                                .value(&[0x3C, 0x0A])
+                                .descriptor("2901", b"Board Temperature")
+                                .descriptor("2904", &[0x0E, 0xFE, 0x2F, 0x27, 0x01, 0x00, 0x00])
+                                .descriptor("2906", &[0x60, 0xF0, 0x34, 0x21]),
+                        )
+                        // 配置特征：读需加密，写需认证（模拟后端中均要求先配对）
+                        .characteristic(
+                            SimCharacteristic::new("6E400005-B5A3-F393-E0A9-E50E24DCCA9E")
+                                .read()
+                                .write()
+                                .value(&[0x01])
+                                .security(SecurityLevel::Encryption, SecurityLevel::Authentication),
+                        ),
+                ),
+        );
+        // Eddystone-URL 信标：https://example.com
+        adapter.add_device(
+            SimDevice::new("SIM-EDDY", "C0:FF:EE:00:00:04")
+                .rssi(-77)
+                .rssi_drift(5)
+                .connectable(false)
+                .advertised_service("FEAA", &hex::decode("10EE036578616D706C6507").unwrap_or_default()),
+        );
+        // 自定义厂商数据（0xFFFF 为测试保留的公司 ID），可配合 decoders.example.json 解码
+        adapter.add_device(
+            SimDevice::new("SIM-TH", "C0:FF:EE:00:00:05")
+                .rssi(-62)
+                .rssi_drift(3)
+                .connectable(false)
+                .manufacturer_data(0xFFFF, &hex::decode("01572C098112345678").unwrap_or_default()),
+        );
+        // 第二个适配器：距离较远，只能收到 iBeacon，用于演示多适配器合并
+        let second = backend.add_adapter("sim1", "00:11:22:33:44:66");
+        second.add_device(
+            SimDevice::new("", "C0:FF:EE:00:00:02")
+                .rssi(-84)
+                .rssi_drift(6)
+                .connectable(false)
+                .manufacturer_data(
+                    0x004C,
+                    &hex::decode("0215E2C56DB5DFFB48D2B060D0F5A71096E000010002C5").unwrap_or_default(),
+                ),
+        );
+        backend
+    }
+}
+
+impl BleBackend for SimulatedBackend {
+    fn name(&self) -> &'static str {
+        "simulated"
+    }
+
+    fn bluetooth_enabled(&self) -> BleResult<bool> {
+        Ok(self.enabled.load(Ordering::SeqCst))
+    }
+
+    fn adapters(&self) -> BleResult<Vec<Arc<dyn BleCentral>>> {
+        Ok(self
+            .adapters
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|a| Arc::new(SimCentral(a.clone())) as Arc<dyn BleCentral>)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventSink, NullSink};
+    use crate::payload::PayloadEncoding;
+    use crate::scan::{self, StartScanArgs};
+    use crate::state::AppState;
+    use crate::{device, gatt};
+    use futures::executor::block_on;
+
+    fn hrm_backend() -> (SimulatedBackend, Arc<SimAdapter>, Arc<SimPeripheral>) {
+        let backend = SimulatedBackend::new();
+        let adapter = backend.add_adapter("sim0", "00:00:00:00:00:01");
+        adapter.set_advertising_interval(Duration::from_secs(3600));
+        let hrm = adapter.add_device(
+            SimDevice::new("HRM", "AA:BB:CC:DD:EE:01")
+                .rssi(-60)
+                .rssi_drift(5)
+                .gatt_service(
+                    SimService::new("180D")
+                        .characteristic(SimCharacteristic::new("2A37").notify())
+                        .characteristic(SimCharacteristic::new("2A39").write()),
+                )
+                .gatt_service(SimService::new("180F").characteristic(SimCharacteristic::new("2A19").read().value(&[90]))),
+        );
+        (backend, adapter, hrm)
+    }
+
+    #[test]
+    fn scan_reports_found_then_updated_with_drift() {
+        let (backend, adapter, _) = hrm_backend();
+        let central = backend.adapters().unwrap().remove(0);
+        let mut events = central.scan_events();
+        central.scan_start().unwrap();
+        adapter.advertise_once();
+        central.scan_stop().unwrap();
+
+        let kinds: Vec<&str> = block_on(async {
+            let mut out = Vec::new();
+            while let Some(Ok(e)) = events.next().await {
+                out.push(match e {
+                    ScanEvent::Start => "start",
+                    ScanEvent::Found(p) => {
+                        assert!((p.rssi().unwrap() - -60).abs() <= 5);
+                        "found"
+                    }
+                    ScanEvent::Updated(_) => "updated",
+                    ScanEvent::Stop => break,
+                });
+            }
+            out
+        });
+        assert_eq!(kinds, vec!["start", "found", "updated"]);
+        assert_eq!(central.scan_results().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn gatt_requires_connection_and_respects_properties() {
+        let (backend, _, hrm) = hrm_backend();
+        let central = backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        let p = central.scan_results().unwrap().remove(0);
+
+        assert!(p.read("180F", "2A19").is_err());
+        p.connect().unwrap();
+        assert_eq!(p.read("180F", "2A19").unwrap(), vec![90]);
+        assert_eq!(p.read("0000180F-0000-1000-8000-00805F9B34FB", "2a19").unwrap(), vec![90]);
+        assert!(p.read("180D", "2A39").is_err());
+        p.write_request("180D", "2A39", &[0x01]).unwrap();
+        assert_eq!(hrm.written_values().len(), 1);
+        assert_eq!(p.services().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn notifications_end_when_link_drops() {
+        let (backend, adapter, hrm) = hrm_backend();
+        let central = backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        let p = adapter.device("HRM").map(|d| Arc::new(SimPeripheralHandle(d)) as Arc<dyn BlePeripheral>).unwrap();
+        let mut connection = p.connection_events();
+        p.connect().unwrap();
+        let mut stream = p.notify("180D", "2A37").unwrap();
+        hrm.push_notification("180D", "2A37", &[0x00, 0x48]);
+        hrm.drop_link();
+        hrm.push_notification("180D", "2A37", &[0x00, 0x49]);
+
+        let values: Vec<Vec<u8>> = block_on(async {
+            let mut out = Vec::new();
+            while let Some(v) = stream.next().await {
+                out.push(v);
+            }
+            out
+        });
+        assert_eq!(values, vec![vec![0x00, 0x48]]);
+        assert!(!p.is_connected().unwrap());
+        let events = block_on(async { vec![connection.next().await, connection.next().await] });
+        assert_eq!(events, vec![Some(ConnectionEvent::Connected), Some(ConnectionEvent::Disconnected)]);
+    }
+
+    #[test]
+    fn cccd_tracks_subscription_state() {
+        let (backend, _, _) = hrm_backend();
+        let central = backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        let p = central.scan_results().unwrap().remove(0);
+        p.connect().unwrap();
+
+        assert_eq!(p.read_descriptor("180D", "2A37", "2902").unwrap(), vec![0x00, 0x00]);
+        let _stream = p.notify("180D", "2A37").unwrap();
+        assert_eq!(p.read_descriptor("180D", "2A37", "2902").unwrap(), vec![0x01, 0x00]);
+        p.unsubscribe("180D", "2A37").unwrap();
+        assert_eq!(p.read_descriptor("180D", "2A37", "2902").unwrap(), vec![0x00, 0x00]);
+        assert!(p.write_descriptor("180D", "2A37", "2902", &[0x01]).is_err());
+        assert!(p.read_descriptor("180F", "2A19", "2902").is_err());
+    }
+
+    #[test]
+    fn prepared_writes_apply_on_execute() {
+        let (backend, _, hrm) = hrm_backend();
+        let central = backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        let p = central.scan_results().unwrap().remove(0);
+        p.connect().unwrap();
+
+        assert_eq!(p.prepare_write("180D", "2A39", 0, &[1, 2, 3]).unwrap(), vec![1, 2, 3]);
+        assert!(p.prepare_write("180D", "2A39", 5, &[4]).is_err());
+        p.execute_write(false).unwrap();
+        assert!(hrm.written_values().is_empty());
+
+        p.prepare_write("180D", "2A39", 0, &[1, 2, 3]).unwrap();
+        p.prepare_write("180D", "2A39", 3, &[4, 5]).unwrap();
+        p.execute_write(true).unwrap();
+        assert_eq!(hrm.written_values(), vec![("0000180D-0000-1000-8000-00805F9B34FB".to_string(), "00002A39-0000-1000-8000-00805F9B34FB".to_string(), vec![1, 2, 3, 4, 5])]);
+        // 默认 MTU 23：Prepare Write 每块最多 18 字节
+        assert!(p.prepare_write("180D", "2A39", 0, &[0; 19]).is_err());
+    }
+
+    #[test]
+    fn secured_characteristics_require_pairing() {
+        let backend = SimulatedBackend::new();
+        let adapter = backend.add_adapter("sim0", "00:00:00:00:00:01");
+        adapter.set_advertising_interval(Duration::from_secs(3600));
+        adapter.add_device(
+            SimDevice::new("LOCK", "C0:FF:EE:00:10:01").gatt_service(
+                SimService::new("FFF0").characteristic(
+                    SimCharacteristic::new("FFF1")
+                        .read()
+                        .write()
+                        .reliable_write()
+                        .security(SecurityLevel::Encryption, SecurityLevel::Authentication),
+                ),
+            ),
+        );
+        let central = backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        let p = central.scan_results().unwrap().remove(0);
+        p.connect().unwrap();
+
+        let info = &p.services().unwrap()[0].characteristics[0];
+        assert!(info.has_extended_properties);
+        assert_eq!(info.security, Some(SecurityRequirements { read: SecurityLevel::Encryption, write: SecurityLevel::Authentication }));
+        assert_eq!(p.read_descriptor("FFF0", "FFF1", "2900").unwrap(), vec![0x01, 0x00]);
+        assert!(p.read("FFF0", "FFF1").is_err());
+        assert!(p.write_request("FFF0", "FFF1", &[0x01]).is_err());
+        p.pair().unwrap();
+        assert!(p.read("FFF0", "FFF1").is_ok());
+        p.write_request("FFF0", "FFF1", &[0x01]).unwrap();
+    }
+
+    #[test]
+    fn writes_are_rejected_not_panicking_below_minimum_mtu() {
+        let backend = SimulatedBackend::new();
+        let adapter = backend.add_adapter("sim0", "00:00:00:00:00:01");
+        adapter.add_device(
+            SimDevice::new("TINY", "AA:BB:CC:DD:EE:03")
+                .mtu(2)
+                .gatt_service(SimService::new("FFF0").characteristic(SimCharacteristic::new("FFF1").write().write_without_response())),
+        );
+        let central = backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        let p = central.scan_results().unwrap().remove(0);
+        p.connect().unwrap();
+
+        assert!(p.write_command("FFF0", "FFF1", &[0x01]).is_err());
+        assert!(p.prepare_write("FFF0", "FFF1", 0, &[0x01]).is_err());
+        p.write_command("FFF0", "FFF1", &[]).unwrap();
+    }
+
+    // 命令层集成：经 AppState、注册表与扫描循环驱动演示场景
+    async fn scanned_demo() -> (Arc<SimulatedBackend>, AppState, Arc<dyn EventSink>) {
+        let backend = Arc::new(SimulatedBackend::demo());
+        let state = AppState::new(backend.clone());
+        let sink: Arc<dyn EventSink> = Arc::new(NullSink);
+        let args = StartScanArgs { duration_secs: Some(1), ..Default::default() };
+        scan::start_scan(&state, sink.clone(), args).await.unwrap().await.unwrap();
+        (backend, state, sink)
+    }
+
+    #[tokio::test]
+    async fn scan_command_lists_demo_devices() {
+        let (_, state, _) = scanned_demo().await;
+        let devices = state.devices.lock().unwrap().clone();
+        let hrm = devices.iter().find(|d| d.address == "C0:FF:EE:00:00:01").expect("SIM-HRM");
+        assert_eq!(hrm.identifier, "SIM-HRM");
+        assert!(hrm.connectable && !hrm.connected);
+        assert!(hrm.services.iter().any(|s| s.contains("180D")));
+        assert!(devices.iter().any(|d| d.address == "C0:FF:EE:00:00:03"));
+        assert!(!*state.scanning.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn connect_read_and_write_through_commands() {
+        let (backend, state, sink) = scanned_demo().await;
+
+        device::connect_device(&state, sink.clone(), "SIM-UART".into(), None).await.unwrap();
+        assert!(state.devices.lock().unwrap().iter().any(|d| d.address == "C0:FF:EE:00:00:03" && d.connected));
+
+        let value = gatt::read_characteristic(&state, "SIM-UART".into(), "6E400001-B5A3-F393-E0A9-E50E24DCCA9E".into(), "6E400004-B5A3-F393-E0A9-E50E24DCCA9E".into())
+            .await
+            .unwrap();
+        assert_eq!(value.value, "3C0A");
+        assert!(value.decoded.is_some());
+
+        let (svc, chr) = ("6E400001-B5A3-F393-E0A9-E50E24DCCA9E".to_string(), "6E400002-B5A3-F393-E0A9-E50E24DCCA9E".to_string());
+        gatt::write_characteristic(&state, "SIM-UART".into(), svc.clone(), chr.clone(), "68656C6C6F".into(), None, Some(PayloadEncoding::Hex)).await.unwrap();
+        gatt::write_characteristic(&state, "SIM-UART".into(), svc.clone(), chr.clone(), "hi".into(), Some("command".into()), None).await.unwrap();
+        let uart = backend.adapters.lock().unwrap()[0].device("SIM-UART").unwrap();
+        let written: Vec<Vec<u8>> = uart.written_values().into_iter().map(|(_, _, v)| v).collect();
+        assert_eq!(written, vec![b"hello".to_vec(), b"hi".to_vec()]);
+        // 只读特征拒绝写入
+        assert!(gatt::write_characteristic(&state, "SIM-UART".into(), svc, "6E400004-B5A3-F393-E0A9-E50E24DCCA9E".into(), "00".into(), None, None).await.is_err());
+        assert!(gatt::read_characteristic(&state, "C0:FF:EE:00:00:99".into(), "180F".into(), "2A19".into()).await.is_err());
+
+        device::disconnect_device(&state, sink, "SIM-UART".into()).await.unwrap();
+        assert!(!state.devices.lock().unwrap().iter().any(|d| d.address == "C0:FF:EE:00:00:03" && d.connected));
+    }
+}
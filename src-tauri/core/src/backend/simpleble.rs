@@ -0,0 +1,247 @@
+// SimpleBLE 后端（通过 simplersble 绑定）
+
+use super::{BleBackend, BleCentral, BlePeripheral, BleResult, CharacteristicInfo, ConnectionEvent, ScanEvent, ServiceInfo};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct SimpleBleBackend;
+
+impl BleBackend for SimpleBleBackend {
+    fn name(&self) -> &'static str {
+        "simpleble"
+    }
+
+    fn bluetooth_enabled(&self) -> BleResult<bool> {
+        simplersble::Adapter::bluetooth_enabled().map_err(|e| format!("SimpleBLE 错误: {}", e))
+    }
+
+    fn adapters(&self) -> BleResult<Vec<Arc<dyn BleCentral>>> {
+        let adapters = simplersble::Adapter::get_adapters().map_err(|e| format!("SimpleBLE 错误: {}", e))?;
+        Ok(adapters
+            .iter()
+            .map(|a| Arc::new(SimpleBleCentral { inner: a.clone() }) as Arc<dyn BleCentral>)
+            .collect())
+    }
+}
+
+pub struct SimpleBleCentral {
+    inner: simplersble::Adapter,
+}
+
+fn wrap(p: simplersble::peripheral::Peripheral) -> Arc<dyn BlePeripheral> {
+    Arc::new(SimpleBlePeripheral { inner: p })
+}
+
+impl BleCentral for SimpleBleCentral {
+    fn identifier(&self) -> BleResult<String> {
+        self.inner.identifier().map_err(|e| e.to_string())
+    }
+
+    fn address(&self) -> BleResult<String> {
+        self.inner.address().map_err(|e| e.to_string())
+    }
+
+    fn scan_start(&self) -> BleResult<()> {
+        self.inner.scan_start().map_err(|e| e.to_string())
+    }
+
+    fn scan_stop(&self) -> BleResult<()> {
+        self.inner.scan_stop().map_err(|e| e.to_string())
+    }
+
+    fn scan_events(&self) -> BoxStream<'static, BleResult<ScanEvent>> {
+        self.inner
+            .on_scan_event()
+            .map(|res| {
+                res.map(|event| match event {
+                    simplersble::ScanEvent::Start => ScanEvent::Start,
+                    simplersble::ScanEvent::Stop => ScanEvent::Stop,
+                    simplersble::ScanEvent::Found(p) => ScanEvent::Found(wrap(p)),
+                    simplersble::ScanEvent::Updated(p) => ScanEvent::Updated(wrap(p)),
+                })
+                .map_err(|e| e.to_string())
+            })
+            .boxed()
+    }
+
+    fn scan_results(&self) -> BleResult<Vec<Arc<dyn BlePeripheral>>> {
+        let list = self.inner.scan_get_results().map_err(|e| e.to_string())?;
+        Ok(list.iter().map(|p| wrap(p.clone())).collect())
+    }
+
+    fn paired_peripherals(&self) -> BleResult<Vec<Arc<dyn BlePeripheral>>> {
+        let list = self.inner.get_paired_peripherals().map_err(|e| e.to_string())?;
+        Ok(list.iter().map(|p| wrap(p.clone())).collect())
+    }
+}
+
+pub struct SimpleBlePeripheral {
+    inner: simplersble::peripheral::Peripheral,
+}
+
+// 将 SimpleBLE 的值变化流转换为字节流
+fn value_stream<S, E>(stream: S) -> BoxStream<'static, Vec<u8>>
+where
+    S: futures::Stream<Item = Result<simplersble::peripheral::ValueChangedEvent, E>> + Send + 'static,
+{
+    stream
+        .filter_map(|item| async move {
+            match item {
+                Ok(simplersble::peripheral::ValueChangedEvent::ValueUpdated(data)) => Some(data),
+                _ => None,
+            }
+        })
+        .boxed()
+}
+
+impl BlePeripheral for SimpleBlePeripheral {
+    fn identifier(&self) -> BleResult<String> {
+        self.inner.identifier().map_err(|e| e.to_string())
+    }
+
+    fn address(&self) -> BleResult<String> {
+        self.inner.address().map_err(|e| e.to_string())
+    }
+
+    fn rssi(&self) -> BleResult<i16> {
+        self.inner.rssi().map_err(|e| e.to_string())
+    }
+
+    fn tx_power(&self) -> BleResult<i16> {
+        self.inner.tx_power().map_err(|e| e.to_string())
+    }
+
+    fn mtu(&self) -> BleResult<u16> {
+        self.inner.mtu().map_err(|e| e.to_string())
+    }
+
+    fn is_connectable(&self) -> BleResult<bool> {
+        self.inner.is_connectable().map_err(|e| e.to_string())
+    }
+
+    fn is_connected(&self) -> BleResult<bool> {
+        self.inner.is_connected().map_err(|e| e.to_string())
+    }
+
+    fn is_paired(&self) -> BleResult<bool> {
+        self.inner.is_paired().map_err(|e| e.to_string())
+    }
+
+    fn manufacturer_data(&self) -> BleResult<HashMap<u16, Vec<u8>>> {
+        self.inner.manufacturer_data().map_err(|e| e.to_string())
+    }
+
+    fn services(&self) -> BleResult<Vec<ServiceInfo>> {
+        let services = self.inner.services().map_err(|e| e.to_string())?;
+        Ok(services
+            .iter()
+            .map(|s| ServiceInfo {
+                uuid: s.uuid(),
+                data: s.data(),
+                characteristics: s
+                    .characteristics()
+                    .iter()
+                    .map(|c| {
+                        let descriptors: Vec<String> = c.descriptors().iter().map(|d| d.uuid()).collect();
+                        CharacteristicInfo {
+                            uuid: c.uuid(),
+                            can_read: c.can_read(),
+                            can_write_request: c.can_write_request(),
+                            can_write_command: c.can_write_command(),
+                            can_notify: c.can_notify(),
+                            can_indicate: c.can_indicate(),
+                            // SimpleBLE 只提供上面五种属性；扩展属性由 0x2900 描述符是否存在推断，不提供安全要求
+                            has_extended_properties: descriptors
+                                .iter()
+                                .any(|d| crate::assigned_numbers::short_uuid(d) == Some(0x2900)),
+                            descriptors,
+                            ..Default::default()
+                        }
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    fn connect(&self) -> BleResult<()> {
+        self.inner.connect().map_err(|e| e.to_string())
+    }
+
+    fn disconnect(&self) -> BleResult<()> {
+        self.inner.disconnect().map_err(|e| e.to_string())
+    }
+
+    // SimpleBLE 没有显式配对接口：由系统在需要加密时发起配对，这里只校验结果；连接由调用方负责
+    fn pair(&self) -> BleResult<()> {
+        if !self.inner.is_connected().map_err(|e| e.to_string())? {
+            return Err("设备未连接".to_string());
+        }
+        if self.inner.is_paired().map_err(|e| e.to_string())? {
+            Ok(())
+        } else {
+            Err("系统未完成配对，请在访问加密特征时按系统提示确认配对".to_string())
+        }
+    }
+
+    fn unpair(&self) -> BleResult<()> {
+        self.inner.unpair().map_err(|e| e.to_string())
+    }
+
+    fn connection_events(&self) -> BoxStream<'static, ConnectionEvent> {
+        self.inner
+            .on_connection_event()
+            .filter_map(|item| async move {
+                match item {
+                    Ok(simplersble::peripheral::ConnectionEvent::Connected) => Some(ConnectionEvent::Connected),
+                    Ok(simplersble::peripheral::ConnectionEvent::Disconnected) => Some(ConnectionEvent::Disconnected),
+                    Err(_) => None,
+                }
+            })
+            .boxed()
+    }
+
+    fn read(&self, service: &str, characteristic: &str) -> BleResult<Vec<u8>> {
+        self.inner.read(service, characteristic).map_err(|e| e.to_string())
+    }
+
+    fn write_request(&self, service: &str, characteristic: &str, data: &[u8]) -> BleResult<()> {
+        self.inner.write_request(service, characteristic, data).map_err(|e| e.to_string())
+    }
+
+    fn write_command(&self, service: &str, characteristic: &str, data: &[u8]) -> BleResult<()> {
+        self.inner.write_command(service, characteristic, data).map_err(|e| e.to_string())
+    }
+
+    // SimpleBLE 未暴露 Prepare/Execute Write，长值只能分块写入
+    fn prepare_write(&self, _service: &str, _characteristic: &str, _offset: u16, _data: &[u8]) -> BleResult<Vec<u8>> {
+        Err("SimpleBLE 后端不支持 Prepared Write".to_string())
+    }
+
+    fn execute_write(&self, _commit: bool) -> BleResult<()> {
+        Err("SimpleBLE 后端不支持 Prepared Write".to_string())
+    }
+
+    fn notify(&self, service: &str, characteristic: &str) -> BleResult<BoxStream<'static, Vec<u8>>> {
+        let stream = self.inner.notify(service, characteristic).map_err(|e| e.to_string())?;
+        Ok(value_stream(stream))
+    }
+
+    fn indicate(&self, service: &str, characteristic: &str) -> BleResult<BoxStream<'static, Vec<u8>>> {
+        let stream = self.inner.indicate(service, characteristic).map_err(|e| e.to_string())?;
+        Ok(value_stream(stream))
+    }
+
+    fn unsubscribe(&self, service: &str, characteristic: &str) -> BleResult<()> {
+        self.inner.unsubscribe(service, characteristic).map_err(|e| e.to_string())
+    }
+
+    fn read_descriptor(&self, service: &str, characteristic: &str, descriptor: &str) -> BleResult<Vec<u8>> {
+        self.inner.descriptor_read(service, characteristic, descriptor).map_err(|e| e.to_string())
+    }
+
+    fn write_descriptor(&self, service: &str, characteristic: &str, descriptor: &str, data: &[u8]) -> BleResult<()> {
+        self.inner.descriptor_write(service, characteristic, descriptor, data).map_err(|e| e.to_string())
+    }
+}
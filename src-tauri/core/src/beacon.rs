@@ -0,0 +1,326 @@
+// 信标解码：从厂商数据识别 iBeacon / AltBeacon，从 0xFEAA 服务数据识别 Eddystone UID/URL/TLM/EID
+use serde::{Deserialize, Serialize};
+
+use crate::adv::normalize_uuid;
+
+// Apple 公司 ID，iBeacon 帧类型 0x02、长度 0x15
+const APPLE_COMPANY_ID: u16 = 0x004C;
+// Eddystone 服务 UUID
+const EDDYSTONE_UUID: &str = "0000FEAA-0000-1000-8000-00805F9B34FB";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Beacon {
+    IBeacon {
+        uuid: String,
+        major: u16,
+        minor: u16,
+        measured_power: i8, // 1 米处 RSSI
+    },
+    AltBeacon {
+        manufacturer_id: u16,
+        uuid: String, // Beacon ID 前 16 字节
+        major: u16,   // Beacon ID 第 17~18 字节
+        minor: u16,   // Beacon ID 第 19~20 字节
+        measured_power: i8, // 1 米处参考 RSSI
+        reserved: u8,
+    },
+    // Eddystone 设备轮流广播不同帧，各帧内容分别保留
+    Eddystone {
+        tx_power: Option<i8>, // 0 米处发射功率，来自 UID/URL/EID 帧
+        uid: Option<EddystoneUid>,
+        url: Option<String>,
+        tlm: Option<EddystoneTlm>,
+        eid: Option<String>, // 十六进制
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EddystoneUid {
+    pub namespace: String, // 10 字节十六进制
+    pub instance: String,  // 6 字节十六进制
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EddystoneTlm {
+    pub version: u8,
+    pub battery_mv: Option<u16>,  // 0 表示不支持
+    pub temperature: Option<f64>, // 摄氏度，0x8000 表示不支持
+    pub adv_count: u32,
+    pub uptime_secs: f64,
+}
+
+impl Beacon {
+    // 1 米处参考功率（Eddystone 由 0 米发射功率推算）
+    pub fn measured_power(&self) -> Option<i16> {
+        match self {
+            Beacon::IBeacon { measured_power, .. } | Beacon::AltBeacon { measured_power, .. } => {
+                Some(i16::from(*measured_power))
+            }
+            Beacon::Eddystone { tx_power, .. } => tx_power.map(|tx| i16::from(tx) - 41),
+        }
+    }
+}
+
+// 按 8-4-4-4-12 格式化 16 字节 UUID
+fn format_uuid(bytes: &[u8]) -> String {
+    let h = hex::encode_upper(bytes);
+    format!("{}-{}-{}-{}-{}", &h[0..8], &h[8..12], &h[12..16], &h[16..20], &h[20..32])
+}
+
+fn be16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn be32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn decode_ibeacon(data: &[u8]) -> Option<Beacon> {
+    if data.len() < 23 || data[0] != 0x02 || data[1] != 0x15 {
+        return None;
+    }
+    Some(Beacon::IBeacon {
+        uuid: format_uuid(&data[2..18]),
+        major: be16(&data[18..20]),
+        minor: be16(&data[20..22]),
+        measured_power: data[22] as i8,
+    })
+}
+
+// AltBeacon：beacon code 0xBEAC + 20 字节 ID + 参考 RSSI + 保留字节，可由任意公司广播
+fn decode_altbeacon(company_id: u16, data: &[u8]) -> Option<Beacon> {
+    if data.len() < 24 || data[0] != 0xBE || data[1] != 0xAC {
+        return None;
+    }
+    Some(Beacon::AltBeacon {
+        manufacturer_id: company_id,
+        uuid: format_uuid(&data[2..18]),
+        major: be16(&data[18..20]),
+        minor: be16(&data[20..22]),
+        measured_power: data[22] as i8,
+        reserved: data[23],
+    })
+}
+
+// Eddystone-URL 压缩编码
+fn decode_url(data: &[u8]) -> Option<String> {
+    const SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+    const EXPANSIONS: [&str; 14] = [
+        ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/", ".com", ".org", ".edu", ".net", ".info", ".biz",
+        ".gov",
+    ];
+    let (scheme, rest) = data.split_first()?;
+    let mut url = SCHEMES.get(usize::from(*scheme))?.to_string();
+    for &b in rest {
+        match b {
+            0x00..=0x0D => url.push_str(EXPANSIONS[usize::from(b)]),
+            0x21..=0x7E => url.push(char::from(b)),
+            _ => return None,
+        }
+    }
+    Some(url)
+}
+
+// 解码单个 Eddystone 帧，返回只含该帧内容的记录
+fn decode_eddystone(data: &[u8]) -> Option<Beacon> {
+    let mut tx_power = None;
+    let mut uid = None;
+    let mut url = None;
+    let mut tlm = None;
+    let mut eid = None;
+    match *data.first()? {
+        0x00 if data.len() >= 18 => {
+            tx_power = Some(data[1] as i8);
+            uid = Some(EddystoneUid { namespace: hex::encode_upper(&data[2..12]), instance: hex::encode_upper(&data[12..18]) });
+        }
+        0x10 if data.len() >= 3 => {
+            tx_power = Some(data[1] as i8);
+            url = Some(decode_url(&data[2..])?);
+        }
+        // 仅支持未加密的 TLM（版本 0）
+        0x20 if data.len() >= 14 && data[1] == 0x00 => {
+            let battery = be16(&data[2..4]);
+            let temperature = be16(&data[4..6]);
+            tlm = Some(EddystoneTlm {
+                version: data[1],
+                battery_mv: Some(battery).filter(|mv| *mv != 0),
+                temperature: Some(temperature).filter(|t| *t != 0x8000).map(|t| f64::from(t as i16) / 256.0),
+                adv_count: be32(&data[6..10]),
+                uptime_secs: f64::from(be32(&data[10..14])) / 10.0,
+            });
+        }
+        0x30 if data.len() >= 10 => {
+            tx_power = Some(data[1] as i8);
+            eid = Some(hex::encode_upper(&data[2..10]));
+        }
+        _ => return None,
+    }
+    Some(Beacon::Eddystone { tx_power, uid, url, tlm, eid })
+}
+
+// 从一次广播的厂商数据与服务数据中识别信标
+pub fn decode(manufacturer_data: &[(u16, Vec<u8>)], service_data: &[(String, Vec<u8>)]) -> Option<Beacon> {
+    for (company_id, data) in manufacturer_data {
+        let beacon = if *company_id == APPLE_COMPANY_ID { decode_ibeacon(data) } else { None };
+        if let Some(b) = beacon.or_else(|| decode_altbeacon(*company_id, data)) {
+            return Some(b);
+        }
+    }
+    service_data
+        .iter()
+        .filter(|(uuid, _)| normalize_uuid(uuid) == EDDYSTONE_UUID)
+        .find_map(|(_, data)| decode_eddystone(data))
+}
+
+// 合并新旧记录：Eddystone 的各帧内容累积保留，其他类型以最新为准；本次广播没有信标数据时保留旧值
+pub fn merge(previous: Option<Beacon>, latest: Option<Beacon>) -> Option<Beacon> {
+    match (previous, latest) {
+        (
+            Some(Beacon::Eddystone { tx_power, uid, url, tlm, eid }),
+            Some(Beacon::Eddystone { tx_power: tx2, uid: uid2, url: url2, tlm: tlm2, eid: eid2 }),
+        ) => Some(Beacon::Eddystone {
+            tx_power: tx2.or(tx_power),
+            uid: uid2.or(uid),
+            url: url2.or(url),
+            tlm: tlm2.or(tlm),
+            eid: eid2.or(eid),
+        }),
+        (previous, latest) => latest.or(previous),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(h: &str) -> Vec<u8> {
+        hex::decode(h).unwrap()
+    }
+
+    fn eddystone(frame: &str) -> Option<Beacon> {
+        decode(&[], &[("FEAA".to_string(), bytes(frame))])
+    }
+
+    #[test]
+    fn decodes_ibeacon() {
+        let beacon = decode(&[(0x004C, bytes("0215E2C56DB5DFFB48D2B060D0F5A71096E000010002C5"))], &[]).unwrap();
+        assert_eq!(
+            beacon,
+            Beacon::IBeacon { uuid: "E2C56DB5-DFFB-48D2-B060-D0F5A71096E0".into(), major: 1, minor: 2, measured_power: -59 }
+        );
+        assert_eq!(beacon.measured_power(), Some(-59));
+        // 其他公司的相同载荷不是 iBeacon
+        assert_eq!(decode(&[(0x0059, bytes("0215E2C56DB5DFFB48D2B060D0F5A71096E000010002C5"))], &[]), None);
+    }
+
+    #[test]
+    fn decodes_altbeacon() {
+        let data = bytes("BEAC2F234454CF6D4A0FADF2F4911BA9FFA600010002C500");
+        let beacon = decode(&[(0x0118, data)], &[]).unwrap();
+        assert_eq!(
+            beacon,
+            Beacon::AltBeacon {
+                manufacturer_id: 0x0118,
+                uuid: "2F234454-CF6D-4A0F-ADF2-F4911BA9FFA6".into(),
+                major: 1,
+                minor: 2,
+                measured_power: -59,
+                reserved: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_eddystone_frames() {
+        let uid = eddystone("00E70102030405060708090A0B0C0D0E0F100000").unwrap();
+        assert_eq!(
+            uid,
+            Beacon::Eddystone {
+                tx_power: Some(-25),
+                uid: Some(EddystoneUid { namespace: "0102030405060708090A".into(), instance: "0B0C0D0E0F10".into() }),
+                url: None,
+                tlm: None,
+                eid: None,
+            }
+        );
+        assert_eq!(uid.measured_power(), Some(-66));
+
+        match eddystone("10EB03676F6F676C6507").unwrap() {
+            Beacon::Eddystone { tx_power, url, .. } => {
+                assert_eq!(tx_power, Some(-21));
+                assert_eq!(url.as_deref(), Some("https://google.com"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match eddystone("10EB00676F6F676C6500").unwrap() {
+            Beacon::Eddystone { url, .. } => assert_eq!(url.as_deref(), Some("http://www.google.com/")),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let tlm = EddystoneTlm { version: 0, battery_mv: Some(3000), temperature: Some(25.5), adv_count: 100, uptime_secs: 360.0 };
+        match eddystone("20000BB819800000006400000E10") {
+            Some(Beacon::Eddystone { tlm: Some(t), tx_power: None, .. }) => assert_eq!(t, tlm),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match eddystone("30F01122334455667788").unwrap() {
+            Beacon::Eddystone { tx_power, eid, .. } => {
+                assert_eq!(tx_power, Some(-16));
+                assert_eq!(eid.as_deref(), Some("1122334455667788"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tlm_sentinels_and_negative_temperature() {
+        match eddystone("200000008000000000010000000A") {
+            Some(Beacon::Eddystone { tlm: Some(t), .. }) => {
+                assert_eq!(t.battery_mv, None);
+                assert_eq!(t.temperature, None);
+                assert_eq!(t.adv_count, 1);
+                assert_eq!(t.uptime_secs, 1.0);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match eddystone("20000BB8FF80000000010000000A") {
+            Some(Beacon::Eddystone { tlm: Some(t), .. }) => assert_eq!(t.temperature, Some(-0.5)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_unsupported_frames() {
+        assert_eq!(decode(&[(0x004C, bytes("0215E2C56DB5DFFB48D2B060D0F5A71096E000010002"))], &[]), None);
+        assert_eq!(decode(&[(0x0118, bytes("BEAC2F234454CF6D4A0FADF2F4911BA9FFA600010002C5"))], &[]), None);
+        assert_eq!(eddystone("00E70102030405060708090A0B0C0D0E0F"), None);
+        assert_eq!(eddystone("10EB"), None);
+        assert_eq!(eddystone("10EB04676F6F676C65"), None); // 未知 scheme
+        assert_eq!(eddystone("10EB03676F0F"), None); // 保留的扩展码
+        assert_eq!(eddystone("20000BB819800000006400000E"), None);
+        assert_eq!(eddystone("20010BB819800000006400000E10"), None); // 加密 TLM
+        assert_eq!(eddystone("30F0112233"), None);
+        assert_eq!(eddystone(""), None);
+        // 非 Eddystone 的服务数据不解码
+        assert_eq!(decode(&[], &[("180F".to_string(), bytes("00E70102030405060708090A0B0C0D0E0F10"))]), None);
+    }
+
+    #[test]
+    fn merge_accumulates_eddystone_frames() {
+        let uid = eddystone("00E70102030405060708090A0B0C0D0E0F10");
+        let tlm = eddystone("20000BB819800000006400000E10");
+        let merged = merge(uid.clone(), tlm).unwrap();
+        match &merged {
+            Beacon::Eddystone { tx_power, uid, tlm, .. } => {
+                assert_eq!(*tx_power, Some(-25));
+                assert!(uid.is_some() && tlm.is_some());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        // 本次没有信标数据时保留旧值，其他类型以最新为准
+        assert_eq!(merge(Some(merged.clone()), None), Some(merged.clone()));
+        let ibeacon = decode(&[(0x004C, bytes("0215E2C56DB5DFFB48D2B060D0F5A71096E000010002C5"))], &[]);
+        assert_eq!(merge(Some(merged), ibeacon.clone()), ibeacon);
+    }
+}
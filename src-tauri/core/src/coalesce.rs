@@ -0,0 +1,277 @@
+// 设备事件合并：扫描期间按时间窗口合并同一设备的多次广播，批量推送 devices-updated，只携带变化的字段
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::events::EventSink;
+use crate::model::BleDevice;
+use crate::registry;
+use crate::state::AppState;
+
+// 只有这些字段变化时不单独推送（每次广播都会变）
+const VOLATILE_FIELDS: &[&str] = &["last_seen"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoalesceSettings {
+    // 合并窗口（毫秒）；0 表示关闭合并，每次广播都推送 device-discovered
+    #[serde(alias = "windowMs")]
+    pub window_ms: u64,
+}
+
+impl Default for CoalesceSettings {
+    fn default() -> Self {
+        Self { window_ms: 250 }
+    }
+}
+
+// 事件计数（自启动以来累计）
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoalesceStats {
+    pub received: u64,  // 通过过滤器的广播
+    pub filtered: u64,  // 被扫描过滤器丢弃的广播
+    pub coalesced: u64, // 在同一窗口内被后续广播覆盖的更新
+    pub dropped: u64,   // 合并后没有可见变化、未推送的更新
+    pub batches: u64,   // 已推送的 devices-updated 批次
+    pub added: u64,     // 批次中的新设备
+    pub updated: u64,   // 批次中的设备差异
+}
+
+// devices-updated 中的单个设备差异
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceDiff {
+    pub identifier: String,
+    pub changes: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DevicesUpdated {
+    pub added: Vec<BleDevice>,
+    pub updated: Vec<DeviceDiff>,
+}
+
+#[derive(Debug, Default)]
+pub struct Coalescer {
+    pub settings: CoalesceSettings,
+    stats: CoalesceStats,
+    pending: Vec<String>, // 注册表键
+    pending_set: HashSet<String>,
+    emitted: HashMap<String, Map<String, Value>>, // 前端已知的设备快照，key = 注册表键
+}
+
+impl Coalescer {
+    // 新一轮扫描开始：清空快照，首次出现的设备以完整记录推送
+    pub fn reset(&mut self) {
+        self.emitted.clear();
+        self.pending.clear();
+        self.pending_set.clear();
+    }
+
+    pub fn forget(&mut self, key: &str) {
+        self.emitted.remove(key);
+        if self.pending_set.remove(key) {
+            self.pending.retain(|k| k != key);
+        }
+    }
+
+    // 与上次推送的快照比较，返回新设备或变化字段
+    fn diff(&mut self, device: &BleDevice) -> Option<Result<BleDevice, DeviceDiff>> {
+        let key = registry::device_key(device)?;
+        let current = match serde_json::to_value(device) {
+            Ok(Value::Object(map)) => map,
+            _ => return None,
+        };
+        let previous = match self.emitted.get(&key) {
+            Some(p) => p,
+            None => {
+                self.emitted.insert(key, current);
+                return Some(Ok(device.clone()));
+            }
+        };
+        let changes: Map<String, Value> =
+            current.iter().filter(|(k, v)| previous.get(*k) != Some(*v)).map(|(k, v)| (k.clone(), v.clone())).collect();
+        if changes.keys().all(|k| VOLATILE_FIELDS.contains(&k.as_str())) {
+            return None;
+        }
+        self.emitted.insert(key, current);
+        Some(Err(DeviceDiff { identifier: device.identifier.clone(), changes }))
+    }
+}
+
+pub(crate) fn count_filtered(state: &AppState) {
+    state.coalescer.lock().unwrap().stats.filtered += 1;
+}
+
+// 扫描循环中每条通过过滤器的广播调用；关闭合并时直接推送 device-discovered
+pub(crate) fn publish(state: &AppState, sink: &Arc<dyn EventSink>, device: &BleDevice) {
+    let immediate = {
+        let mut c = state.coalescer.lock().unwrap();
+        c.stats.received += 1;
+        match registry::device_key(device) {
+            Some(key) if c.settings.window_ms > 0 => {
+                if c.pending_set.insert(key.clone()) {
+                    c.pending.push(key);
+                } else {
+                    c.stats.coalesced += 1;
+                }
+                false
+            }
+            _ => true,
+        }
+    };
+    if immediate {
+        if let Err(e) = sink.emit("device-discovered", device) {
+            eprintln!("Failed to emit device-discovered event: {}", e);
+        }
+    }
+}
+
+// 推送窗口内累积的更新；设备内容取自合并后的设备表
+pub(crate) fn flush(state: &AppState, sink: &Arc<dyn EventSink>) {
+    let mut c = state.coalescer.lock().unwrap();
+    if c.pending.is_empty() {
+        return;
+    }
+    let pending = std::mem::take(&mut c.pending);
+    c.pending_set.clear();
+
+    let mut batch = DevicesUpdated::default();
+    {
+        let devices = state.devices.lock().unwrap();
+        for key in pending {
+            let device = match devices.iter().find(|d| registry::device_key(d).as_ref() == Some(&key)) {
+                Some(d) => d,
+                None => continue,
+            };
+            match c.diff(device) {
+                Some(Ok(added)) => batch.added.push(added),
+                Some(Err(diff)) => batch.updated.push(diff),
+                None => c.stats.dropped += 1,
+            }
+        }
+    }
+    if batch.added.is_empty() && batch.updated.is_empty() {
+        return;
+    }
+    c.stats.batches += 1;
+    c.stats.added += batch.added.len() as u64;
+    c.stats.updated += batch.updated.len() as u64;
+    drop(c);
+    if let Err(e) = sink.emit("devices-updated", &batch) {
+        eprintln!("Failed to emit devices-updated event: {}", e);
+    }
+}
+
+pub async fn get_coalesce_settings(state: &AppState) -> Result<CoalesceSettings, String> {
+    Ok(state.coalescer.lock().unwrap().settings.clone())
+}
+
+// 修改合并窗口：开启/关闭立即生效，窗口长度在下一次开始扫描时生效
+pub async fn set_coalesce_settings(state: &AppState, settings: CoalesceSettings) -> Result<(), String> {
+    state.coalescer.lock().unwrap().settings = settings;
+    Ok(())
+}
+
+pub async fn get_event_stats(state: &AppState) -> Result<CoalesceStats, String> {
+    Ok(state.coalescer.lock().unwrap().stats.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::SimulatedBackend;
+    use crate::events::RecordingSink;
+
+    fn device(rssi: i16, last_seen: u64) -> BleDevice {
+        BleDevice {
+            identifier: "Tag".into(),
+            address: "AA:BB:CC:DD:EE:01".into(),
+            rssi: Some(rssi),
+            last_seen,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_then_changed_fields() {
+        let mut c = Coalescer::default();
+        assert!(matches!(c.diff(&device(-60, 1)), Some(Ok(d)) if d.identifier == "Tag"));
+
+        // 只有 last_seen 变化时不推送
+        assert!(c.diff(&device(-60, 2)).is_none());
+
+        let diff = match c.diff(&device(-70, 3)) {
+            Some(Err(diff)) => diff,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(diff.identifier, "Tag");
+        let mut keys: Vec<&str> = diff.changes.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["last_seen", "rssi"]);
+        assert_eq!(diff.changes["rssi"], -70);
+
+        // 遗忘后重新作为新设备推送
+        c.forget("AA:BB:CC:DD:EE:01");
+        assert!(matches!(c.diff(&device(-70, 4)), Some(Ok(_))));
+        c.reset();
+        assert!(matches!(c.diff(&device(-70, 5)), Some(Ok(_))));
+    }
+
+    #[test]
+    fn service_data_change_is_reported_as_update() {
+        let mut c = Coalescer::default();
+        let mut d = device(-60, 1);
+        assert!(matches!(c.diff(&d), Some(Ok(_))));
+
+        d.service_data.insert("0000FEAA-0000-1000-8000-00805F9B34FB".into(), "20000BB8".into());
+        let diff = match c.diff(&d) {
+            Some(Err(diff)) => diff,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(diff.changes.keys().collect::<Vec<_>>(), vec!["service_data"]);
+        assert_eq!(diff.changes["service_data"]["0000FEAA-0000-1000-8000-00805F9B34FB"], "20000BB8");
+        assert!(c.diff(&d).is_none());
+    }
+
+    #[test]
+    fn flush_batches_the_window() {
+        let state = AppState::new(Arc::new(SimulatedBackend::new()));
+        let recorder = Arc::new(RecordingSink::default());
+        let sink: Arc<dyn EventSink> = recorder.clone();
+
+        for (rssi, last_seen) in [(-60, 1), (-61, 2), (-62, 3)] {
+            let d = device(rssi, last_seen);
+            *state.devices.lock().unwrap() = vec![d.clone()];
+            publish(&state, &sink, &d);
+        }
+        flush(&state, &sink);
+        let batches = recorder.take("devices-updated");
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0]["added"][0]["rssi"], -62);
+
+        // 没有可见变化的更新被丢弃
+        let d = device(-62, 4);
+        *state.devices.lock().unwrap() = vec![d.clone()];
+        publish(&state, &sink, &d);
+        flush(&state, &sink);
+        assert!(recorder.take("devices-updated").is_empty());
+
+        let stats = state.coalescer.lock().unwrap().stats.clone();
+        assert_eq!((stats.received, stats.coalesced, stats.dropped, stats.batches, stats.added), (4, 2, 1, 1, 1));
+    }
+
+    #[test]
+    fn zero_window_emits_immediately() {
+        let state = AppState::new(Arc::new(SimulatedBackend::new()));
+        let recorder = Arc::new(RecordingSink::default());
+        let sink: Arc<dyn EventSink> = recorder.clone();
+        state.coalescer.lock().unwrap().settings.window_ms = 0;
+        publish(&state, &sink, &device(-60, 1));
+        assert_eq!(recorder.take("device-discovered").len(), 1);
+        flush(&state, &sink);
+        assert!(recorder.take("devices-updated").is_empty());
+    }
+}
@@ -0,0 +1,558 @@
+// 连接状态机：每个设备处于 Disconnected / Connecting / Connected / Disconnecting / Failed 之一，
+// 由连接请求与后端连接回调驱动；链路意外断开后按重连策略自动重连
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::backend::{BlePeripheral, ConnectionEvent};
+use crate::events::EventSink;
+use crate::model::BleDevice;
+use crate::registry::{self, Lifecycle};
+use crate::state::AppState;
+use crate::subscriptions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Disconnecting,
+    Failed,
+}
+
+// 自动重连策略（全局默认，可按设备覆盖）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReconnectPolicy {
+    pub enabled: bool,
+    #[serde(alias = "initialDelayMs")]
+    pub initial_delay_ms: u64,
+    #[serde(alias = "maxDelayMs")]
+    pub max_delay_ms: u64,
+    pub multiplier: f64,
+    #[serde(alias = "maxAttempts")]
+    pub max_attempts: u32, // 0 表示不限次数
+    #[serde(alias = "reconnectOnAdvertisement")]
+    pub reconnect_on_advertisement: bool, // 等待期间扫描到该设备的广播时立即重连
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            initial_delay_ms: 1000,
+            max_delay_ms: 60_000,
+            multiplier: 2.0,
+            max_attempts: 10,
+            reconnect_on_advertisement: true,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    // 第 attempt 次（从 0 开始）重连前的等待时间：指数退避，不超过 max_delay_ms
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.min(32) as i32);
+        let ms = (self.initial_delay_ms as f64 * factor).min(self.max_delay_ms as f64);
+        Duration::from_millis(ms as u64)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.multiplier >= 1.0 && self.multiplier.is_finite()) {
+            return Err(format!("退避倍数必须 >= 1: {}", self.multiplier));
+        }
+        if self.initial_delay_ms == 0 {
+            return Err("初始重连间隔必须大于 0".to_string());
+        }
+        if self.max_delay_ms < self.initial_delay_ms {
+            return Err("最大重连间隔不能小于初始间隔".to_string());
+        }
+        Ok(())
+    }
+}
+
+struct Tracker {
+    device_id: String, // 调用方最近使用的设备 ID，用于 ConnectionInfo
+    state: ConnectionState,
+    adapter_id: String,
+    auto_reconnect: bool, // 用户希望保持连接：connect 置位，disconnect 清除
+    attempt: u32,
+    last_error: Option<String>,
+    watcher: Option<JoinHandle<()>>,
+    retry: Option<JoinHandle<()>>,
+    wake: Arc<Notify>,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self {
+            device_id: String::new(),
+            state: ConnectionState::Disconnected,
+            adapter_id: String::new(),
+            auto_reconnect: false,
+            attempt: 0,
+            last_error: None,
+            watcher: None,
+            retry: None,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+}
+
+impl Tracker {
+    fn retry_pending(&self) -> bool {
+        self.retry.as_ref().map(|h| !h.is_finished()).unwrap_or(false)
+    }
+
+    fn cancel_retry(&mut self) {
+        if let Some(handle) = self.retry.take() {
+            handle.abort();
+        }
+    }
+}
+
+// 返回给前端的连接信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionInfo {
+    pub device_id: String,
+    pub state: ConnectionState,
+    pub adapter_id: String,
+    pub auto_reconnect: bool,
+    pub attempt: u32,
+    pub last_error: Option<String>,
+}
+
+// 按注册表键（大写地址或 id:<identifier>）索引，同一设备以不同大小写的地址或 identifier 访问时落到同一条记录
+#[derive(Default)]
+pub struct Connections {
+    devices: HashMap<String, Tracker>, // key = 注册表键
+    default_policy: ReconnectPolicy,
+    policies: HashMap<String, ReconnectPolicy>, // key = 注册表键；设备尚未发现时暂存在原始 deviceId 下
+}
+
+// 设备 ID 对应的连接记录键；注册表与设备表都没有该设备时使用原始 ID
+pub(crate) fn conn_key(state: &AppState, device_id: &str) -> String {
+    registry::key_for(state, device_id).unwrap_or_else(|| device_id.to_string())
+}
+
+impl Connections {
+    fn tracker(&mut self, key: &str) -> &mut Tracker {
+        self.devices.entry(key.to_string()).or_default()
+    }
+
+    pub fn state(&self, key: &str) -> ConnectionState {
+        self.devices.get(key).map(|t| t.state).unwrap_or(ConnectionState::Disconnected)
+    }
+
+    pub fn policy(&self, key: &str) -> ReconnectPolicy {
+        self.policies.get(key).unwrap_or(&self.default_policy).clone()
+    }
+
+    pub fn info(&self, key: &str) -> ConnectionInfo {
+        let t = self.devices.get(key);
+        ConnectionInfo {
+            device_id: t.map(|t| t.device_id.clone()).filter(|id| !id.is_empty()).unwrap_or_else(|| key.to_string()),
+            state: self.state(key),
+            adapter_id: t.map(|t| t.adapter_id.clone()).unwrap_or_default(),
+            auto_reconnect: t.map(|t| t.auto_reconnect).unwrap_or(false),
+            attempt: t.map(|t| t.attempt).unwrap_or(0),
+            last_error: t.and_then(|t| t.last_error.clone()),
+        }
+    }
+}
+
+// 状态迁移：同步注册表生命周期与设备记录，并广播 device-connection-changed
+pub fn transition(
+    state: &AppState,
+    sink: &Arc<dyn EventSink>,
+    device_id: &str,
+    to: ConnectionState,
+    reason: &str,
+    error: Option<String>,
+) {
+    let key = conn_key(state, device_id);
+    let (from, adapter_id, attempt) = {
+        let mut conns = state.connections.lock().unwrap();
+        let t = conns.tracker(&key);
+        let from = t.state;
+        if from == to && error.is_none() {
+            return;
+        }
+        t.state = to;
+        if error.is_some() {
+            t.last_error = error.clone();
+        } else if to == ConnectionState::Connected {
+            t.last_error = None;
+        }
+        (from, t.adapter_id.clone(), t.attempt)
+    };
+    eprintln!("设备 {} 连接状态: {:?} -> {:?} ({})", device_id, from, to, reason);
+
+    {
+        let mut reg = state.registry.lock().unwrap();
+        match to {
+            ConnectionState::Connecting => reg.set_lifecycle(device_id, Lifecycle::Connecting),
+            ConnectionState::Connected => reg.mark_connected(device_id, &adapter_id),
+            ConnectionState::Disconnected | ConnectionState::Failed => reg.set_lifecycle(device_id, Lifecycle::Discovered),
+            ConnectionState::Disconnecting => {}
+        }
+    }
+
+    let connected = to == ConnectionState::Connected;
+    {
+        let mut devices = state.devices.lock().unwrap();
+        if let Some(device) = devices.iter_mut().find(|d| registry::device_key(d).as_ref() == Some(&key)) {
+            device.connected = connected;
+        }
+    }
+
+    let _ = sink.emit("device-connection-changed", serde_json::json!({
+        "deviceId": device_id,
+        "state": to,
+        "previous": from,
+        "reason": reason,
+        "adapterId": adapter_id,
+        "attempt": attempt,
+        "error": error,
+        "connected": connected
+    }));
+
+    // 链路恢复（首次连接或重连成功）后恢复该设备之前的订阅；断开失败回到已连接时订阅仍有效
+    if connected && from != ConnectionState::Disconnecting {
+        subscriptions::restore(state, sink, device_id);
+    }
+}
+
+// 用户发起连接前：记录适配器并启用自动重连意图，取消尚未执行的重连
+pub fn begin_connect(state: &AppState, device_id: &str, adapter_id: &str) {
+    let key = conn_key(state, device_id);
+    let mut conns = state.connections.lock().unwrap();
+    // 设备发现前按原始 ID 设置的策略迁移到注册表键下
+    if key != device_id {
+        if let Some(policy) = conns.policies.remove(device_id) {
+            conns.policies.insert(key.clone(), policy);
+        }
+    }
+    let t = conns.tracker(&key);
+    t.device_id = device_id.to_string();
+    t.adapter_id = adapter_id.to_string();
+    t.auto_reconnect = true;
+    t.attempt = 0;
+    t.cancel_retry();
+}
+
+// 用户发起断开：不再自动重连
+pub fn begin_disconnect(state: &AppState, device_id: &str) {
+    let key = conn_key(state, device_id);
+    let mut conns = state.connections.lock().unwrap();
+    let t = conns.tracker(&key);
+    t.auto_reconnect = false;
+    t.cancel_retry();
+}
+
+// 停止监听连接回调（用户断开或首次连接失败后）
+pub fn stop_watch(state: &AppState, device_id: &str) {
+    let key = conn_key(state, device_id);
+    let mut conns = state.connections.lock().unwrap();
+    if let Some(t) = conns.devices.get_mut(&key) {
+        t.auto_reconnect = false;
+        if let Some(handle) = t.watcher.take() {
+            handle.abort();
+        }
+    }
+}
+
+// 监听外设的连接回调，替换该设备旧的监听任务
+pub fn watch(state: &AppState, sink: Arc<dyn EventSink>, device_id: &str, peripheral: Arc<dyn BlePeripheral>) {
+    let mut events = peripheral.connection_events();
+    let state_clone = state.clone();
+    let id = device_id.to_string();
+    let key = conn_key(state, device_id);
+    let handle = tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            let current = state_clone.connections.lock().unwrap().state(&key);
+            match event {
+                // 系统自行恢复了连接
+                ConnectionEvent::Connected => {
+                    if matches!(current, ConnectionState::Connecting | ConnectionState::Disconnected) {
+                        transition(&state_clone, &sink, &id, ConnectionState::Connected, "callback", None);
+                    }
+                }
+                // 仅已连接状态下的断开视为链路丢失；用户断开由 disconnect_device 处理
+                ConnectionEvent::Disconnected => {
+                    if current == ConnectionState::Connected {
+                        transition(&state_clone, &sink, &id, ConnectionState::Disconnected, "link-lost", None);
+                        schedule_reconnect(&state_clone, sink.clone(), &id);
+                    }
+                }
+            }
+        }
+    });
+    let key = conn_key(state, device_id);
+    let mut conns = state.connections.lock().unwrap();
+    if let Some(old) = conns.tracker(&key).watcher.replace(handle) {
+        old.abort();
+    }
+}
+
+// 按策略安排自动重连；策略未启用或用户已断开时不做处理
+pub fn schedule_reconnect(state: &AppState, sink: Arc<dyn EventSink>, device_id: &str) {
+    let key = conn_key(state, device_id);
+    {
+        let conns = state.connections.lock().unwrap();
+        let wanted = conns.devices.get(&key).map(|t| t.auto_reconnect).unwrap_or(false);
+        if !wanted || !conns.policy(&key).enabled {
+            return;
+        }
+    }
+
+    let state_clone = state.clone();
+    let id = device_id.to_string();
+    let task_key = key.clone();
+    let handle = tokio::spawn(async move {
+        let key = task_key;
+        loop {
+            let (policy, attempt, wanted, wake) = {
+                let mut conns = state_clone.connections.lock().unwrap();
+                let policy = conns.policy(&key);
+                let t = conns.tracker(&key);
+                (policy, t.attempt, t.auto_reconnect, t.wake.clone())
+            };
+            if !wanted {
+                return;
+            }
+            if policy.max_attempts > 0 && attempt >= policy.max_attempts {
+                let msg = format!("自动重连 {} 次后仍未成功", attempt);
+                transition(&state_clone, &sink, &id, ConnectionState::Failed, "max-attempts", Some(msg));
+                return;
+            }
+
+            let delay = policy.delay(attempt);
+            let _ = sink.emit("device-reconnect-scheduled", serde_json::json!({
+                "deviceId": id,
+                "attempt": attempt + 1,
+                "delayMs": delay.as_millis() as u64
+            }));
+            if policy.reconnect_on_advertisement {
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = wake.notified() => eprintln!("扫描到设备 {} 的广播，提前重连", id),
+                }
+            } else {
+                tokio::time::sleep(delay).await;
+            }
+
+            if state_clone.connections.lock().unwrap().state(&key) == ConnectionState::Connected {
+                return;
+            }
+            state_clone.connections.lock().unwrap().tracker(&key).attempt = attempt + 1;
+            transition(&state_clone, &sink, &id, ConnectionState::Connecting, "reconnect", None);
+
+            // 每次都从注册表重新取句柄，重新扫描后句柄可能已更新
+            let result = registry::resolve(&state_clone, &id).and_then(|p| p.connect().map(|_| p));
+            match result {
+                Ok(peripheral) => {
+                    state_clone.connections.lock().unwrap().tracker(&key).attempt = 0;
+                    transition(&state_clone, &sink, &id, ConnectionState::Connected, "reconnect", None);
+                    watch(&state_clone, sink.clone(), &id, peripheral);
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("自动重连设备 {} 失败: {}", id, e);
+                    transition(&state_clone, &sink, &id, ConnectionState::Disconnected, "reconnect-failed", Some(e));
+                }
+            }
+        }
+    });
+
+    let mut conns = state.connections.lock().unwrap();
+    let t = conns.tracker(&key);
+    t.cancel_retry();
+    t.retry = Some(handle);
+}
+
+// 扫描到广播时调用：等待重连的设备立即发起重连
+pub fn on_advertisement(state: &AppState, device: &BleDevice) {
+    let Some(key) = registry::device_key(device) else { return };
+    let conns = state.connections.lock().unwrap();
+    if let Some(t) = conns.devices.get(&key) {
+        if t.state == ConnectionState::Disconnected && t.retry_pending() && conns.policy(&key).reconnect_on_advertisement {
+            t.wake.notify_one();
+        }
+    }
+}
+
+// 查询单个设备的连接状态
+pub async fn get_connection_state(state: &AppState, device_id: String) -> Result<ConnectionInfo, String> {
+    let key = conn_key(state, &device_id);
+    let mut info = state.connections.lock().unwrap().info(&key);
+    info.device_id = device_id;
+    Ok(info)
+}
+
+// 列出所有跟踪中的设备连接状态
+pub async fn list_connections(state: &AppState) -> Result<Vec<ConnectionInfo>, String> {
+    let conns = state.connections.lock().unwrap();
+    let mut list: Vec<ConnectionInfo> = conns.devices.keys().map(|id| conns.info(id)).collect();
+    list.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+    Ok(list)
+}
+
+// 读取重连策略；device_id 为空时返回全局默认策略
+pub async fn get_reconnect_policy(state: &AppState, device_id: Option<String>) -> Result<ReconnectPolicy, String> {
+    let key = device_id.map(|id| conn_key(state, &id));
+    let conns = state.connections.lock().unwrap();
+    Ok(match key {
+        Some(key) => conns.policy(&key),
+        None => conns.default_policy.clone(),
+    })
+}
+
+// 设置重连策略；device_id 为空时修改全局默认策略
+pub async fn set_reconnect_policy(
+    state: &AppState,
+    device_id: Option<String>,
+    policy: ReconnectPolicy,
+) -> Result<(), String> {
+    policy.validate()?;
+    let key = device_id.map(|id| conn_key(state, &id));
+    let mut conns = state.connections.lock().unwrap();
+    match key {
+        Some(key) => {
+            conns.policies.insert(key, policy);
+        }
+        None => conns.default_policy = policy,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::{SimDevice, SimPeripheral, SimulatedBackend};
+    use crate::device;
+    use crate::events::NullSink;
+
+    #[test]
+    fn delay_grows_exponentially_up_to_cap() {
+        let policy = ReconnectPolicy { initial_delay_ms: 100, max_delay_ms: 1000, multiplier: 3.0, ..Default::default() };
+        let delays: Vec<u64> = (0..4).map(|n| policy.delay(n).as_millis() as u64).collect();
+        assert_eq!(delays, vec![100, 300, 900, 1000]);
+        assert_eq!(policy.delay(u32::MAX), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn validate_rejects_bad_policies() {
+        assert!(ReconnectPolicy::default().validate().is_ok());
+        assert!(ReconnectPolicy { multiplier: 0.5, ..Default::default() }.validate().is_err());
+        assert!(ReconnectPolicy { multiplier: f64::NAN, ..Default::default() }.validate().is_err());
+        assert!(ReconnectPolicy { initial_delay_ms: 0, ..Default::default() }.validate().is_err());
+        assert!(ReconnectPolicy { initial_delay_ms: 500, max_delay_ms: 100, ..Default::default() }.validate().is_err());
+    }
+
+    async fn connected_hrm() -> (AppState, Arc<dyn EventSink>, Arc<SimPeripheral>) {
+        let backend = Arc::new(SimulatedBackend::new());
+        let hrm = backend
+            .add_adapter("sim0", "00:00:00:00:00:01")
+            .add_device(SimDevice::new("HRM", "AA:BB:CC:DD:EE:01").paired(true));
+        let state = AppState::new(backend);
+        let sink: Arc<dyn EventSink> = Arc::new(NullSink);
+        let policy = ReconnectPolicy {
+            enabled: true,
+            initial_delay_ms: 10,
+            max_delay_ms: 20,
+            max_attempts: 2,
+            reconnect_on_advertisement: false,
+            ..Default::default()
+        };
+        set_reconnect_policy(&state, None, policy).await.unwrap();
+        device::connect_device(&state, sink.clone(), "HRM".into(), None).await.unwrap();
+        assert_eq!(info(&state).state, ConnectionState::Connected);
+        (state, sink, hrm)
+    }
+
+    fn info(state: &AppState) -> ConnectionInfo {
+        let key = conn_key(state, "HRM");
+        state.connections.lock().unwrap().info(&key)
+    }
+
+    async fn wait_for(state: &AppState, want: ConnectionState) -> bool {
+        for _ in 0..100 {
+            if info(state).state == want {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn link_loss_reconnects() {
+        let (state, _sink, hrm) = connected_hrm().await;
+        hrm.drop_link();
+        assert!(wait_for(&state, ConnectionState::Disconnected).await);
+        assert!(wait_for(&state, ConnectionState::Connected).await);
+        let info = info(&state);
+        assert_eq!(info.attempt, 0);
+        assert!(info.auto_reconnect && info.last_error.is_none());
+        assert_eq!(state.registry.lock().unwrap().lifecycle("HRM"), Some(Lifecycle::Connected));
+    }
+
+    #[tokio::test]
+    async fn exhausted_attempts_end_in_failed() {
+        let (state, _sink, hrm) = connected_hrm().await;
+        hrm.fail_next_connects(Some("out of range"));
+        hrm.drop_link();
+        assert!(wait_for(&state, ConnectionState::Failed).await);
+        let info = info(&state);
+        assert_eq!(info.attempt, 2);
+        assert!(info.last_error.unwrap().contains("2 次"));
+    }
+
+    #[tokio::test]
+    async fn user_disconnect_suppresses_reconnect() {
+        let (state, sink, hrm) = connected_hrm().await;
+        device::disconnect_device(&state, sink, "HRM".into()).await.unwrap();
+        assert_eq!(info(&state).state, ConnectionState::Disconnected);
+        assert!(!info(&state).auto_reconnect);
+
+        // 已超过重连间隔，仍保持断开
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        hrm.drop_link();
+        assert!(!wait_for(&state, ConnectionState::Connecting).await);
+        assert_eq!(info(&state).state, ConnectionState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn advertisement_wakes_reconnect_across_id_forms() {
+        let backend = Arc::new(SimulatedBackend::new());
+        let hrm = backend
+            .add_adapter("sim0", "00:00:00:00:00:01")
+            .add_device(SimDevice::new("HRM", "AA:BB:CC:DD:EE:01").paired(true));
+        let state = AppState::new(backend);
+        let sink: Arc<dyn EventSink> = Arc::new(NullSink);
+        let policy = ReconnectPolicy { enabled: true, initial_delay_ms: 10_000, max_delay_ms: 10_000, ..Default::default() };
+        // 按小写地址设置策略并连接，之后以 identifier 查询与唤醒
+        set_reconnect_policy(&state, Some("aa:bb:cc:dd:ee:01".into()), policy.clone()).await.unwrap();
+        device::connect_device(&state, sink, "aa:bb:cc:dd:ee:01".into(), None).await.unwrap();
+        assert_eq!(get_reconnect_policy(&state, Some("HRM".into())).await.unwrap(), policy);
+        let by_name = get_connection_state(&state, "HRM".into()).await.unwrap();
+        assert_eq!((by_name.device_id.as_str(), by_name.state), ("HRM", ConnectionState::Connected));
+        assert_eq!(list_connections(&state).await.unwrap().len(), 1);
+
+        hrm.drop_link();
+        assert!(wait_for(&state, ConnectionState::Disconnected).await);
+        let seen = BleDevice { identifier: "HRM".into(), address: "AA:BB:CC:DD:EE:01".into(), ..Default::default() };
+        on_advertisement(&state, &seen);
+        // 未到 10 秒的退避间隔，由广播提前触发重连
+        assert!(wait_for(&state, ConnectionState::Connected).await);
+        assert_eq!(info(&state).attempt, 0);
+    }
+}
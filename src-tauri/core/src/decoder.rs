@@ -0,0 +1,373 @@
+// 厂商数据解码：按公司 ID（及可选前缀字节）查找解码器，把厂商数据解析为具名字段。
+// 除 Rust 内置解码器外，可从 JSON 布局文件声明解码器，启动时加载，运行中可重新加载
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::BleDevice;
+use crate::state::AppState;
+
+// 解码出的单个字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedField {
+    pub name: String,
+    pub value: Value, // 数值、枚举名称或字符串
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+// 一条厂商数据的解码结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedData {
+    pub company_id: u16,
+    pub decoder: String,
+    pub fields: Vec<DecodedField>,
+}
+
+pub trait ManufacturerDecoder: Send + Sync {
+    fn name(&self) -> &str;
+    fn company_id(&self) -> u16;
+    // 厂商数据以这些字节开头时才使用该解码器；同一公司 ID 下前缀越长优先级越高
+    fn prefix(&self) -> &[u8] {
+        &[]
+    }
+    fn decode(&self, data: &[u8]) -> Vec<DecodedField>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldFormat {
+    #[default]
+    Number,
+    Hex,
+    Utf8,
+}
+
+// 布局字段；offset 从厂商数据首字节（公司 ID 之后、包含前缀）算起
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: usize,
+    pub length: usize,
+    #[serde(default)]
+    pub format: FieldFormat,
+    #[serde(default)]
+    pub endian: Endian,
+    #[serde(default)]
+    pub signed: bool,
+    #[serde(default)]
+    pub mask: Option<u64>, // 取位段：先按掩码取值再右移到最低位
+    #[serde(default)]
+    pub scale: Option<f64>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default, rename = "enum")]
+    pub values: BTreeMap<i64, String>, // 数值 -> 名称
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    #[serde(alias = "companyId")]
+    pub company_id: u16,
+    #[serde(default)]
+    pub prefix: Option<String>, // 十六进制
+    pub fields: Vec<FieldLayout>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LayoutFile {
+    #[serde(default)]
+    pub decoders: Vec<Layout>,
+}
+
+// 编译后的布局解码器
+struct LayoutDecoder {
+    layout: Layout,
+    prefix: Vec<u8>,
+}
+
+impl LayoutDecoder {
+    fn new(layout: Layout) -> Result<Self, String> {
+        let prefix = match layout.prefix.as_deref() {
+            Some(p) => hex::decode(p.trim_start_matches("0x")).map_err(|_| format!("解码器 {} 的 prefix 不是有效的十六进制", layout.name))?,
+            None => Vec::new(),
+        };
+        for f in &layout.fields {
+            if f.name.is_empty() {
+                return Err(format!("解码器 {} 存在未命名的字段", layout.name));
+            }
+            if f.format == FieldFormat::Number && !(1..=8).contains(&f.length) {
+                return Err(format!("解码器 {} 的字段 {} 长度必须在 1~8 之间", layout.name, f.name));
+            }
+            if f.mask == Some(0) {
+                return Err(format!("解码器 {} 的字段 {} 的 mask 不能为 0", layout.name, f.name));
+            }
+            if f.mask.is_some_and(|m| f.length < 8 && m >> (f.length * 8) != 0) {
+                return Err(format!("解码器 {} 的字段 {} 的 mask 超出字段长度", layout.name, f.name));
+            }
+        }
+        Ok(Self { layout, prefix })
+    }
+}
+
+// 按布局读取一个数值字段，应用掩码、枚举与缩放
+fn decode_number(f: &FieldLayout, bytes: &[u8]) -> Value {
+    let mut raw: u64 = 0;
+    let ordered: Vec<u8> = match f.endian {
+        Endian::Little => bytes.iter().rev().copied().collect(),
+        Endian::Big => bytes.to_vec(),
+    };
+    for b in ordered {
+        raw = (raw << 8) | u64::from(b);
+    }
+    // 取出位段及其宽度；有符号字段按位段宽度做符号扩展
+    let (field, bits) = match f.mask {
+        Some(mask) => {
+            let shift = mask.trailing_zeros();
+            ((raw & mask) >> shift, 64 - (mask >> shift).leading_zeros())
+        }
+        None => (raw, (f.length * 8) as u32),
+    };
+    let value = if f.signed && bits < 64 { ((field << (64 - bits)) as i64) >> (64 - bits) } else { field as i64 };
+    if let Some(name) = f.values.get(&value) {
+        return Value::String(name.clone());
+    }
+    match f.scale {
+        Some(scale) => serde_json::Number::from_f64(value as f64 * scale).map(Value::Number).unwrap_or(Value::Null),
+        None => Value::from(value),
+    }
+}
+
+impl ManufacturerDecoder for LayoutDecoder {
+    fn name(&self) -> &str {
+        &self.layout.name
+    }
+
+    fn company_id(&self) -> u16 {
+        self.layout.company_id
+    }
+
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    // 超出数据长度的字段跳过
+    fn decode(&self, data: &[u8]) -> Vec<DecodedField> {
+        self.layout
+            .fields
+            .iter()
+            .filter_map(|f| {
+                let bytes = data.get(f.offset..f.offset.checked_add(f.length)?)?;
+                let value = match f.format {
+                    FieldFormat::Number => decode_number(f, bytes),
+                    FieldFormat::Hex => Value::String(hex::encode_upper(bytes)),
+                    FieldFormat::Utf8 => Value::String(String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string()),
+                };
+                Some(DecodedField { name: f.name.clone(), value, unit: f.unit.clone() })
+            })
+            .collect()
+    }
+}
+
+// 已注册解码器的概要
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoderInfo {
+    pub name: String,
+    pub company_id: u16,
+    pub prefix: String, // 十六进制
+    pub source: String, // builtin / layout
+}
+
+#[derive(Default)]
+pub struct DecoderRegistry {
+    builtin: Vec<Arc<dyn ManufacturerDecoder>>,
+    layouts: Vec<Arc<dyn ManufacturerDecoder>>,
+    path: Option<PathBuf>, // 布局文件路径，重新加载时使用
+}
+
+impl DecoderRegistry {
+    // 注册内置解码器；重新加载布局文件不影响内置解码器
+    pub fn register(&mut self, decoder: Arc<dyn ManufacturerDecoder>) {
+        self.builtin.push(decoder);
+    }
+
+    // 读取并编译布局文件，全部成功才替换当前布局
+    pub fn load_layouts(&mut self, path: &Path) -> Result<usize, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("无法读取解码器布局文件 {}: {}", path.display(), e))?;
+        let file: LayoutFile =
+            serde_json::from_str(&text).map_err(|e| format!("解码器布局文件 {} 格式错误: {}", path.display(), e))?;
+        let layouts = file
+            .decoders
+            .into_iter()
+            .map(|l| LayoutDecoder::new(l).map(|d| Arc::new(d) as Arc<dyn ManufacturerDecoder>))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.layouts = layouts;
+        self.path = Some(path.to_path_buf());
+        Ok(self.layouts.len())
+    }
+
+    // 选出匹配的解码器：布局优先于内置，同类中前缀最长者优先
+    fn find(&self, company_id: u16, data: &[u8]) -> Option<Arc<dyn ManufacturerDecoder>> {
+        let matching = |list: &[Arc<dyn ManufacturerDecoder>]| {
+            list.iter()
+                .filter(|d| d.company_id() == company_id && data.starts_with(d.prefix()))
+                .max_by_key(|d| d.prefix().len())
+                .cloned()
+        };
+        matching(&self.layouts).or_else(|| matching(&self.builtin))
+    }
+
+    pub fn decode(&self, device: &BleDevice) -> Vec<DecodedData> {
+        let mut decoded: Vec<DecodedData> = device
+            .manufacturer_data
+            .iter()
+            .filter_map(|(company, data)| {
+                let company_id = u16::from_str_radix(company, 16).ok()?;
+                let bytes = hex::decode(data).ok()?;
+                let decoder = self.find(company_id, &bytes)?;
+                Some(DecodedData { company_id, decoder: decoder.name().to_string(), fields: decoder.decode(&bytes) })
+            })
+            .collect();
+        decoded.sort_by_key(|d| d.company_id);
+        decoded
+    }
+
+    pub fn list(&self) -> Vec<DecoderInfo> {
+        let info = |d: &Arc<dyn ManufacturerDecoder>, source: &str| DecoderInfo {
+            name: d.name().to_string(),
+            company_id: d.company_id(),
+            prefix: hex::encode_upper(d.prefix()),
+            source: source.to_string(),
+        };
+        self.builtin.iter().map(|d| info(d, "builtin")).chain(self.layouts.iter().map(|d| info(d, "layout"))).collect()
+    }
+}
+
+// 默认布局文件路径：环境变量 BLESCANNER_DECODERS 优先
+pub fn path_from_env() -> Option<PathBuf> {
+    std::env::var_os("BLESCANNER_DECODERS").filter(|p| !p.is_empty()).map(PathBuf::from)
+}
+
+// 设置布局文件并加载；文件尚不存在时只记录路径，创建后可通过 reload_decoders 加载
+pub fn open(state: &AppState, path: &Path) -> Result<(), String> {
+    let mut registry = state.decoders.lock().unwrap();
+    if !path.exists() {
+        registry.path = Some(path.to_path_buf());
+        return Ok(());
+    }
+    let count = registry.load_layouts(path)?;
+    eprintln!("已加载 {} 个厂商数据解码器: {}", count, path.display());
+    Ok(())
+}
+
+// 扫描循环中为设备填充解码结果
+pub(crate) fn apply(state: &AppState, device: &mut BleDevice) {
+    device.decoded = state.decoders.lock().unwrap().decode(device);
+}
+
+pub async fn list_decoders(state: &AppState) -> Result<Vec<DecoderInfo>, String> {
+    Ok(state.decoders.lock().unwrap().list())
+}
+
+// 重新加载布局文件，并重新解码设备表中已有的设备
+pub async fn reload_decoders(state: &AppState) -> Result<Vec<DecoderInfo>, String> {
+    let list = {
+        let mut registry = state.decoders.lock().unwrap();
+        let path = registry.path.clone().ok_or("未设置解码器布局文件")?;
+        registry.load_layouts(&path)?;
+        registry.list()
+    };
+    let registry = state.decoders.lock().unwrap();
+    for device in state.devices.lock().unwrap().iter_mut() {
+        device.decoded = registry.decode(device);
+    }
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field(spec: Value) -> FieldLayout {
+        let mut spec = spec;
+        spec["name"] = json!("f");
+        spec.as_object_mut().unwrap().entry("offset").or_insert(json!(0));
+        serde_json::from_value(spec).unwrap()
+    }
+
+    fn number(spec: Value, bytes: &[u8]) -> Value {
+        decode_number(&field(spec), bytes)
+    }
+
+    fn layout(fields: Value) -> Result<LayoutDecoder, String> {
+        LayoutDecoder::new(serde_json::from_value(json!({ "name": "test", "companyId": 0x0059, "fields": fields })).unwrap())
+    }
+
+    #[test]
+    fn numbers_respect_endianness_and_sign() {
+        assert_eq!(number(json!({ "length": 2 }), &[0x34, 0x12]), json!(0x1234));
+        assert_eq!(number(json!({ "length": 2, "endian": "big" }), &[0x34, 0x12]), json!(0x3412));
+        assert_eq!(number(json!({ "length": 2, "signed": true }), &[0xFE, 0xFF]), json!(-2));
+        assert_eq!(number(json!({ "length": 2 }), &[0xFE, 0xFF]), json!(0xFFFE));
+        assert_eq!(number(json!({ "length": 1, "signed": true }), &[0x80]), json!(-128));
+        assert_eq!(number(json!({ "length": 8, "signed": true }), &[0xFF; 8]), json!(-1));
+    }
+
+    #[test]
+    fn masks_extract_bit_fields() {
+        assert_eq!(number(json!({ "length": 1, "mask": 0xF0 }), &[0xA5]), json!(0x0A));
+        assert_eq!(number(json!({ "length": 2, "endian": "big", "mask": 0x0FF0 }), &[0x12, 0x34]), json!(0x23));
+        // 有符号位段按掩码宽度做符号扩展
+        assert_eq!(number(json!({ "length": 1, "mask": 0xF0, "signed": true }), &[0xA5]), json!(-6));
+        assert_eq!(number(json!({ "length": 1, "mask": 0x0F, "signed": true }), &[0xA5]), json!(5));
+        assert_eq!(number(json!({ "length": 1, "mask": 0x01, "signed": true }), &[0x01]), json!(-1));
+    }
+
+    #[test]
+    fn scale_and_enum() {
+        assert_eq!(number(json!({ "length": 2, "signed": true, "scale": 0.01 }), &[0x0C, 0xFE]), json!(-5.0));
+        assert_eq!(number(json!({ "length": 1, "enum": { "1": "on", "0": "off" } }), &[1]), json!("on"));
+        assert_eq!(number(json!({ "length": 1, "enum": { "1": "on" }, "scale": 2.0 }), &[3]), json!(6.0));
+    }
+
+    #[test]
+    fn fields_beyond_the_data_are_skipped() {
+        let decoder = layout(json!([
+            { "name": "a", "offset": 0, "length": 1 },
+            { "name": "b", "offset": 2, "length": 2 },
+            { "name": "s", "offset": 1, "length": 3, "format": "utf8" },
+            { "name": "h", "offset": usize::MAX, "length": 2, "format": "hex" }
+        ]))
+        .unwrap();
+        let names = |data: &[u8]| decoder.decode(data).into_iter().map(|f| f.name).collect::<Vec<_>>();
+        assert_eq!(names(&[1, 2, 3]), vec!["a"]);
+        assert_eq!(names(&[1, b'h', b'i', 0]), vec!["a", "b", "s"]);
+        assert_eq!(decoder.decode(&[1, b'h', b'i', 0])[2].value, json!("hi"));
+    }
+
+    #[test]
+    fn invalid_layouts_are_rejected() {
+        assert!(layout(json!([{ "name": "a", "offset": 0, "length": 9 }])).is_err());
+        assert!(layout(json!([{ "name": "a", "offset": 0, "length": 0 }])).is_err());
+        assert!(layout(json!([{ "name": "", "offset": 0, "length": 1 }])).is_err());
+        assert!(layout(json!([{ "name": "a", "offset": 0, "length": 1, "mask": 0 }])).is_err());
+        assert!(layout(json!([{ "name": "a", "offset": 0, "length": 1, "mask": 0x100 }])).is_err());
+        assert!(layout(json!([{ "name": "a", "offset": 0, "length": 8, "mask": u64::MAX }])).is_ok());
+        let bad_prefix = json!({ "name": "test", "companyId": 1, "prefix": "zz", "fields": [] });
+        assert!(LayoutDecoder::new(serde_json::from_value(bad_prefix).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use std::sync::Arc;
+
+use crate::backend::BlePeripheral;
+use crate::connection::{self, ConnectionState};
+use crate::events::EventSink;
+use crate::registry;
+use crate::state::{find_adapter, is_bonded, refresh_bonds, AppState};
+
+// 检查设备连接状态
+pub async fn check_device_connection(state: &AppState, device_id: String) -> Result<bool, String> {
+    eprintln!("检查设备连接状态: {}", device_id);
+
+    let peripheral = registry::resolve(state, &device_id)?;
+    match peripheral.is_connected() {
+        Ok(is_connected) => {
+            eprintln!("设备 {} 连接状态: {}", device_id, is_connected);
+            Ok(is_connected)
+        }
+        Err(e) => {
+            eprintln!("检查设备连接状态失败: {}", e);
+            Err(format!("检查连接状态失败: {}", e))
+        }
+    }
+}
+
+// 连接到设备
+pub async fn connect_device(
+    state: &AppState,
+    sink: Arc<dyn EventSink>,
+    device_id: String,
+    adapter_id_hint: Option<String>,
+) -> Result<(), String> {
+    eprintln!("尝试连接到设备: {}", device_id);
+
+    // 显式指定的适配器优先，否则使用注册表中记录的适配器
+    let (adapter_id, peripheral) = registry::resolve_on(state, &device_id, adapter_id_hint.as_deref()).map_err(|_| {
+        format!("未找到设备: {}，可能的原因：\n1. 设备不在当前扫描结果中\n2. 传递的deviceId不正确\n3. 需要重新扫描", device_id)
+    })?;
+
+    // 先监听连接回调，再发起连接，避免错过回调
+    connection::begin_connect(state, &device_id, &adapter_id);
+    connection::watch(state, sink.clone(), &device_id, peripheral.clone());
+
+    // 先检查设备是否已连接
+    if let Ok(true) = peripheral.is_connected() {
+        eprintln!("设备 {} 已经连接", device_id);
+        connection::transition(state, &sink, &device_id, ConnectionState::Connected, "user", None);
+        return Ok(());
+    }
+
+    // 直接尝试连接（与SimpleBLE示例一致，不做额外检查）
+    eprintln!("开始连接设备: {} (适配器 {})", device_id, adapter_id);
+    connection::transition(state, &sink, &device_id, ConnectionState::Connecting, "user", None);
+    match peripheral.connect() {
+        Ok(_) => {
+            eprintln!("连接命令发送成功");
+            connection::transition(state, &sink, &device_id, ConnectionState::Connected, "user", None);
+            sync_pairing(state, &sink, &device_id, &adapter_id, peripheral.as_ref());
+            eprintln!("成功连接到设备: {}", device_id);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("连接设备失败: {}", e);
+            let msg = format!("连接设备失败: {}", e);
+            connection::stop_watch(state, &device_id);
+            connection::transition(state, &sink, &device_id, ConnectionState::Failed, "connect-failed", Some(msg.clone()));
+            Err(msg)
+        }
+    }
+}
+
+// 断开设备连接
+pub async fn disconnect_device(
+    state: &AppState,
+    sink: Arc<dyn EventSink>,
+    device_id: String,
+) -> Result<(), String> {
+    eprintln!("尝试断开设备连接: {}", device_id);
+
+    // 用户主动断开，取消自动重连
+    connection::begin_disconnect(state, &device_id);
+
+    let peripheral = match registry::resolve(state, &device_id) {
+        Ok(p) => p,
+        Err(_) => {
+            // 如果适配器无法定位该设备，则标记为已断开并返回成功，避免前端提示未找到设备
+            eprintln!("未在适配器中定位到设备 {}，标记为已断开", device_id);
+            connection::stop_watch(state, &device_id);
+            state.subscriptions.lock().unwrap().forget_device(&device_id);
+            connection::transition(state, &sink, &device_id, ConnectionState::Disconnected, "user", None);
+            return Ok(());
+        }
+    };
+
+    match peripheral.is_connected() {
+        Ok(false) => {
+            eprintln!("设备 {} 已经断开，进行状态同步", device_id);
+        }
+        connected => {
+            if let Err(e) = connected {
+                eprintln!("无法获取连接状态，仍尝试断开: {}", e);
+            }
+            connection::transition(state, &sink, &device_id, ConnectionState::Disconnecting, "user", None);
+            if let Err(e) = peripheral.disconnect() {
+                let msg = format!("断开设备连接失败: {}", e);
+                connection::transition(state, &sink, &device_id, ConnectionState::Connected, "disconnect-failed", Some(msg.clone()));
+                return Err(msg);
+            }
+            eprintln!("断开连接命令发送成功");
+            tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
+            match peripheral.is_connected() {
+                Ok(true) => {
+                    let msg = "断开命令执行后设备仍保持连接状态".to_string();
+                    connection::transition(state, &sink, &device_id, ConnectionState::Connected, "disconnect-failed", Some(msg.clone()));
+                    return Err(msg);
+                }
+                Ok(false) => eprintln!("成功断开设备连接: {}", device_id),
+                Err(e) => eprintln!("无法检查断开状态，但命令执行成功: {}", e),
+            }
+        }
+    }
+
+    connection::stop_watch(state, &device_id);
+    // 用户主动断开：订阅随之结束，不在下次连接时恢复
+    state.subscriptions.lock().unwrap().forget_device(&device_id);
+    connection::transition(state, &sink, &device_id, ConnectionState::Disconnected, "user", None);
+    Ok(())
+}
+
+// 读取配对状态并刷新绑定列表，同步到设备记录；有变化时广播 device-pairing-changed
+fn sync_pairing(state: &AppState, sink: &Arc<dyn EventSink>, device_id: &str, adapter_id: &str, peripheral: &dyn BlePeripheral) {
+    let paired = peripheral.is_paired().unwrap_or(false);
+    if let Ok(adapter) = find_adapter(state, adapter_id) {
+        if let Err(e) = refresh_bonds(state, adapter.as_ref()) {
+            eprintln!("{}", e);
+        }
+    }
+    let bonded = is_bonded(state, adapter_id, &peripheral.address().unwrap_or_default());
+
+    let key = registry::key_for(state, device_id);
+    let changed = {
+        let mut devices = state.devices.lock().unwrap();
+        match devices.iter_mut().find(|d| key.is_some() && registry::device_key(d) == key) {
+            Some(device) if device.paired != paired || device.bonded != bonded => {
+                device.paired = paired;
+                device.bonded = bonded;
+                true
+            }
+            Some(_) => false,
+            None => true,
+        }
+    };
+    if changed {
+        let _ = sink.emit("device-pairing-changed", serde_json::json!({
+            "deviceId": device_id,
+            "paired": paired,
+            "bonded": bonded
+        }));
+    }
+}
+
+// 与设备配对（必要时先连接）
+pub async fn pair_device(state: &AppState, sink: Arc<dyn EventSink>, device_id: String) -> Result<(), String> {
+    eprintln!("尝试与设备配对: {}", device_id);
+    let (adapter_id, peripheral) = registry::resolve_on(state, &device_id, None)?;
+    // 经由 connect_device 建立连接，连接状态机与断线重连照常生效
+    if !peripheral.is_connected().unwrap_or(false) {
+        connect_device(state, sink.clone(), device_id.clone(), Some(adapter_id.clone())).await?;
+    }
+    let result = peripheral.pair().map_err(|e| format!("配对失败: {}", e));
+    sync_pairing(state, &sink, &device_id, &adapter_id, peripheral.as_ref());
+    result
+}
+
+// 解除配对并删除绑定信息
+pub async fn unpair_device(state: &AppState, sink: Arc<dyn EventSink>, device_id: String) -> Result<(), String> {
+    eprintln!("尝试解除设备配对: {}", device_id);
+    let (adapter_id, peripheral) = registry::resolve_on(state, &device_id, None)?;
+    let result = peripheral.unpair().map_err(|e| format!("解除配对失败: {}", e));
+    sync_pairing(state, &sink, &device_id, &adapter_id, peripheral.as_ref());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::{SimDevice, SimPeripheral, SimulatedBackend};
+    use crate::backend::BleBackend;
+    use crate::events::RecordingSink;
+    use crate::model::BleDevice;
+
+    // 两个适配器；设备只在 sim0 上，未配对，经一轮扫描进入扫描结果
+    fn scanned_device() -> (AppState, Arc<RecordingSink>, Arc<SimPeripheral>) {
+        let backend = Arc::new(SimulatedBackend::new());
+        let device = backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(SimDevice::new("LOCK", "AA:BB:CC:DD:EE:07"));
+        backend.add_adapter("sim1", "00:00:00:00:00:02");
+        let central = backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        central.scan_stop().unwrap();
+        let state = AppState::new(backend);
+        state.devices.lock().unwrap().push(BleDevice {
+            identifier: "LOCK".into(),
+            address: "AA:BB:CC:DD:EE:07".into(),
+            ..Default::default()
+        });
+        (state, Arc::new(RecordingSink::default()), device)
+    }
+
+    // (connected, paired, bonded)
+    fn flags(state: &AppState) -> (bool, bool, bool) {
+        let devices = state.devices.lock().unwrap();
+        (devices[0].connected, devices[0].paired, devices[0].bonded)
+    }
+
+    #[tokio::test]
+    async fn pair_connects_through_the_state_machine() {
+        let (state, recorder, device) = scanned_device();
+        let sink: Arc<dyn EventSink> = recorder.clone();
+
+        pair_device(&state, sink, "LOCK".into()).await.unwrap();
+        assert_eq!(connection::get_connection_state(&state, "LOCK".into()).await.unwrap().state, ConnectionState::Connected);
+        let changes = recorder.take("device-connection-changed");
+        assert_eq!(changes.last().unwrap()["state"], "connected");
+        assert_eq!(changes.last().unwrap()["adapterId"], "sim0");
+        assert_eq!(
+            recorder.take("device-pairing-changed"),
+            vec![serde_json::json!({ "deviceId": "LOCK", "paired": true, "bonded": true })]
+        );
+        assert_eq!(flags(&state), (true, true, true));
+
+        // 配对时建立的连接同样受监听，链路断开会被记录
+        device.drop_link();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(connection::get_connection_state(&state, "LOCK".into()).await.unwrap().state, ConnectionState::Disconnected);
+        assert_eq!(recorder.take("device-connection-changed").last().unwrap()["reason"], "link-lost");
+    }
+
+    #[tokio::test]
+    async fn connected_paired_and_bonded_are_tracked_separately() {
+        let (state, recorder, _device) = scanned_device();
+        let sink: Arc<dyn EventSink> = recorder.clone();
+
+        // 连接未配对设备：状态未变化，不推送配对事件
+        connect_device(&state, sink.clone(), "LOCK".into(), None).await.unwrap();
+        assert_eq!(flags(&state), (true, false, false));
+        assert!(recorder.take("device-pairing-changed").is_empty());
+
+        pair_device(&state, sink.clone(), "LOCK".into()).await.unwrap();
+        assert_eq!(flags(&state), (true, true, true));
+        // 绑定信息按适配器区分
+        assert!(is_bonded(&state, "sim0", "aa:bb:cc:dd:ee:07"));
+        assert!(!is_bonded(&state, "sim1", "AA:BB:CC:DD:EE:07"));
+
+        // 断开后配对与绑定保留
+        disconnect_device(&state, sink.clone(), "LOCK".into()).await.unwrap();
+        assert_eq!(flags(&state), (false, true, true));
+
+        unpair_device(&state, sink, "LOCK".into()).await.unwrap();
+        assert_eq!(flags(&state), (false, false, false));
+        let events = recorder.take("device-pairing-changed");
+        assert_eq!(events.last().unwrap(), &serde_json::json!({ "deviceId": "LOCK", "paired": false, "bonded": false }));
+        assert_eq!(events.len(), 2);
+    }
+}
@@ -0,0 +1,46 @@
+// 事件输出抽象：Tauri 转发给前端，CLI 打印到终端
+
+use serde::Serialize;
+
+pub trait EventSink: Send + Sync {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) -> Result<(), String>;
+}
+
+impl dyn EventSink {
+    pub fn emit<T: Serialize>(&self, event: &str, payload: T) -> Result<(), String> {
+        let value = serde_json::to_value(payload).map_err(|e| e.to_string())?;
+        self.emit_value(event, value)
+    }
+}
+
+// 丢弃所有事件，用于不关心推送的调用方
+pub struct NullSink;
+
+impl EventSink for NullSink {
+    fn emit_value(&self, _event: &str, _payload: serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// 记录所有事件，供测试断言推送内容
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct RecordingSink(pub std::sync::Mutex<Vec<(String, serde_json::Value)>>);
+
+#[cfg(test)]
+impl RecordingSink {
+    pub fn take(&self, event: &str) -> Vec<serde_json::Value> {
+        let mut events = self.0.lock().unwrap();
+        let (matched, rest) = events.drain(..).partition(|(name, _)| name == event);
+        *events = rest;
+        matched.into_iter().map(|(_, payload)| payload).collect()
+    }
+}
+
+#[cfg(test)]
+impl EventSink for RecordingSink {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        self.0.lock().unwrap().push((event.to_string(), payload));
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+// 扫描过滤：在扫描循环中先于设备表更新与事件推送求值，不匹配的广播不会进入前端
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::adv::normalize_uuid;
+use crate::model::BleDevice;
+use crate::state::AppState;
+
+// 厂商数据匹配：公司 ID 必须相同；data 给出时按 mask 逐字节比较前缀（mask 缺省为全 FF）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManufacturerFilter {
+    #[serde(alias = "companyId")]
+    pub company_id: u16,
+    #[serde(default)]
+    pub data: Option<String>, // 十六进制
+    #[serde(default)]
+    pub mask: Option<String>, // 十六进制，长度与 data 相同
+}
+
+// 过滤条件，全部满足才算匹配；未设置的条件不参与判断
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanFilter {
+    pub name: Option<String>, // 正则，匹配名称或 identifier
+    #[serde(alias = "addressPrefix")]
+    pub address_prefix: Option<String>,
+    #[serde(alias = "serviceUuids")]
+    pub service_uuids: Vec<String>, // 广播任一服务 UUID 即可
+    pub manufacturer: Option<ManufacturerFilter>,
+    #[serde(alias = "minRssi")]
+    pub min_rssi: Option<i16>,
+    #[serde(alias = "connectableOnly")]
+    pub connectable_only: bool,
+}
+
+// 预编译后的过滤器
+#[derive(Debug, Clone)]
+pub struct CompiledFilter {
+    pub spec: ScanFilter,
+    name: Option<Regex>,
+    address_prefix: Option<String>,
+    service_uuids: Vec<String>,
+    manufacturer: Option<(String, Vec<u8>, Vec<u8>)>, // (公司 ID, data, mask)
+}
+
+fn parse_hex(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    let cleaned: String = value.trim_start_matches("0x").chars().filter(|c| !c.is_whitespace() && *c != ':' && *c != '-').collect();
+    hex::decode(&cleaned).map_err(|_| format!("{} 不是有效的十六进制: {}", field, value))
+}
+
+impl ScanFilter {
+    pub fn compile(&self) -> Result<CompiledFilter, String> {
+        let name = match self.name.as_deref().filter(|n| !n.is_empty()) {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| format!("名称正则无效: {}", e))?),
+            None => None,
+        };
+        let manufacturer = match &self.manufacturer {
+            Some(m) => {
+                let data = match &m.data {
+                    Some(d) => parse_hex("manufacturer.data", d)?,
+                    None => Vec::new(),
+                };
+                let mask = match &m.mask {
+                    Some(mask) => parse_hex("manufacturer.mask", mask)?,
+                    None => vec![0xFF; data.len()],
+                };
+                if mask.len() != data.len() {
+                    return Err("manufacturer.mask 的长度必须与 data 相同".to_string());
+                }
+                Some((format!("{:04X}", m.company_id), data, mask))
+            }
+            None => None,
+        };
+        Ok(CompiledFilter {
+            spec: self.clone(),
+            name,
+            address_prefix: self.address_prefix.as_deref().filter(|p| !p.is_empty()).map(str::to_uppercase),
+            service_uuids: self.service_uuids.iter().map(|u| normalize_uuid(u)).collect(),
+            manufacturer,
+        })
+    }
+}
+
+impl CompiledFilter {
+    pub fn matches(&self, device: &BleDevice) -> bool {
+        if self.spec.connectable_only && !device.connectable {
+            return false;
+        }
+        if let Some(min) = self.spec.min_rssi {
+            if !device.rssi.is_some_and(|r| r >= min) {
+                return false;
+            }
+        }
+        if let Some(prefix) = &self.address_prefix {
+            if !device.address.to_uppercase().starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(re) = &self.name {
+            let name_match = device.name.as_deref().is_some_and(|n| re.is_match(n));
+            if !name_match && !re.is_match(&device.identifier) {
+                return false;
+            }
+        }
+        if !self.service_uuids.is_empty()
+            && !device.services.iter().any(|s| self.service_uuids.contains(&normalize_uuid(s)))
+        {
+            return false;
+        }
+        if let Some((company, data, mask)) = &self.manufacturer {
+            let payload = match device.manufacturer_data.get(company).and_then(|d| hex::decode(d).ok()) {
+                Some(p) => p,
+                None => return false,
+            };
+            if payload.len() < data.len() {
+                return false;
+            }
+            if data.iter().zip(mask).zip(&payload).any(|((d, m), p)| d & m != p & m) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// 当前过滤器是否放行该设备；未设置过滤器时全部放行
+pub(crate) fn allows(state: &AppState, device: &BleDevice) -> bool {
+    state.scan_filter.lock().unwrap().as_ref().map_or(true, |f| f.matches(device))
+}
+
+// 设置或清除扫描过滤器，正在进行的扫描立即生效
+pub async fn set_scan_filter(state: &AppState, filter: Option<ScanFilter>) -> Result<(), String> {
+    let compiled = filter.map(|f| f.compile()).transpose()?;
+    *state.scan_filter.lock().unwrap() = compiled;
+    Ok(())
+}
+
+pub async fn get_scan_filter(state: &AppState) -> Result<Option<ScanFilter>, String> {
+    Ok(state.scan_filter.lock().unwrap().as_ref().map(|f| f.spec.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn device() -> BleDevice {
+        BleDevice {
+            identifier: "Tag".into(),
+            name: Some("Polar H10".into()),
+            address: "aa:bb:cc:dd:ee:01".into(),
+            rssi: Some(-60),
+            connectable: true,
+            services: vec!["0000180D-0000-1000-8000-00805F9B34FB".into()],
+            manufacturer_data: HashMap::from([("004C".to_string(), "0215E2C5".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    fn matches(filter: ScanFilter, device: &BleDevice) -> bool {
+        filter.compile().unwrap().matches(device)
+    }
+
+    fn manufacturer(data: &str, mask: Option<&str>) -> ScanFilter {
+        ScanFilter {
+            manufacturer: Some(ManufacturerFilter { company_id: 0x004C, data: Some(data.into()), mask: mask.map(str::to_string) }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn manufacturer_data_matches_masked_prefix() {
+        let d = device();
+        assert!(matches(manufacturer("0215", None), &d));
+        assert!(matches(manufacturer("0x02:15:E2:C5", None), &d));
+        assert!(!matches(manufacturer("0216", None), &d));
+        assert!(matches(manufacturer("0200", Some("FF00")), &d));
+        assert!(matches(manufacturer("0010", Some("F010")), &d));
+        // 数据比载荷长、或公司 ID 不同都不匹配
+        assert!(!matches(manufacturer("0215E2C5FF", None), &d));
+        let other = ScanFilter { manufacturer: Some(ManufacturerFilter { company_id: 0x0059, ..Default::default() }), ..Default::default() };
+        assert!(!matches(other, &d));
+        let any_data = ScanFilter { manufacturer: Some(ManufacturerFilter { company_id: 0x004C, ..Default::default() }), ..Default::default() };
+        assert!(matches(any_data, &d));
+    }
+
+    #[test]
+    fn address_prefix_name_and_services() {
+        let d = device();
+        assert!(matches(ScanFilter { address_prefix: Some("AA:BB".into()), ..Default::default() }, &d));
+        assert!(!matches(ScanFilter { address_prefix: Some("AA:BC".into()), ..Default::default() }, &d));
+        assert!(matches(ScanFilter { name: Some("^Polar".into()), ..Default::default() }, &d));
+        // 名称不匹配时退回 identifier
+        assert!(matches(ScanFilter { name: Some("^Tag$".into()), ..Default::default() }, &d));
+        assert!(!matches(ScanFilter { name: Some("Garmin".into()), ..Default::default() }, &d));
+        assert!(matches(ScanFilter { service_uuids: vec!["0x180D".into(), "FEAA".into()], ..Default::default() }, &d));
+        assert!(!matches(ScanFilter { service_uuids: vec!["180F".into()], ..Default::default() }, &d));
+        let not_connectable = BleDevice { connectable: false, ..device() };
+        assert!(!matches(ScanFilter { connectable_only: true, ..Default::default() }, &not_connectable));
+    }
+
+    #[test]
+    fn min_rssi_rejects_devices_without_rssi() {
+        let filter = ScanFilter { min_rssi: Some(-70), ..Default::default() };
+        assert!(matches(filter.clone(), &device()));
+        assert!(!matches(filter.clone(), &BleDevice { rssi: Some(-71), ..device() }));
+        assert!(!matches(filter, &BleDevice { rssi: None, ..device() }));
+    }
+
+    #[test]
+    fn compile_reports_invalid_specs() {
+        assert!(ScanFilter { name: Some("(".into()), ..Default::default() }.compile().is_err());
+        assert!(manufacturer("0215", Some("FF")).compile().is_err());
+        assert!(manufacturer("zz", None).compile().is_err());
+        assert!(manufacturer("0215", Some("GG00")).compile().is_err());
+        // 空名称等同未设置
+        assert!(ScanFilter { name: Some(String::new()), ..Default::default() }.compile().unwrap().name.is_none());
+    }
+}
@@ -0,0 +1,442 @@
+use std::sync::Arc;
+use std::time::Duration;
+use futures::StreamExt;
+use tokio::time::sleep;
+
+use crate::adv;
+use crate::assigned_numbers;
+use crate::backend::{BlePeripheral, CharacteristicInfo};
+use crate::events::EventSink;
+use crate::gatt_value::{CharacteristicValue, PresentationFormat};
+use crate::history;
+use crate::model::{GattCharacteristic, GattDescriptor, GattService};
+use crate::notifications::{self, NotificationRecord};
+use crate::payload::{self, PayloadEncoding};
+use crate::registry;
+use crate::state::AppState;
+use crate::subscriptions;
+
+// 枚举时预读的静态元数据描述符：扩展属性、用户描述、呈现格式、聚合格式、有效范围
+const METADATA_DESCRIPTORS: &[u16] = &[0x2900, 0x2901, 0x2904, 0x2905, 0x2906, 0x2911];
+
+// 特征声明属性位与名称，扩展属性位来自 0x2900 描述符
+const PROPERTIES: &[(u8, &str)] = &[
+    (0x01, "Broadcast"),
+    (0x02, "Read"),
+    (0x08, "WriteRequest"),
+    (0x04, "WriteCommand"),
+    (0x10, "Notify"),
+    (0x20, "Indicate"),
+    (0x40, "AuthenticatedSignedWrites"),
+    (0x80, "ExtendedProperties"),
+];
+const EXTENDED_PROPERTIES: &[(u16, &str)] = &[(0x0001, "ReliableWrite"), (0x0002, "WritableAuxiliaries")];
+
+fn property_list(c: &CharacteristicInfo, extended: Option<u16>) -> (u8, Vec<String>) {
+    let flags = [
+        c.can_broadcast,
+        c.can_read,
+        c.can_write_command,
+        c.can_write_request,
+        c.can_notify,
+        c.can_indicate,
+        c.can_write_signed,
+        c.has_extended_properties || extended.is_some(),
+    ];
+    let bits = flags.iter().enumerate().filter(|(_, on)| **on).fold(0u8, |acc, (i, _)| acc | (1 << i));
+    let mut names: Vec<String> = PROPERTIES.iter().filter(|(bit, _)| bits & bit != 0).map(|(_, n)| n.to_string()).collect();
+    let extended = extended.unwrap_or(0);
+    names.extend(EXTENDED_PROPERTIES.iter().filter(|(bit, _)| extended & bit != 0).map(|(_, n)| n.to_string()));
+    (bits, names)
+}
+
+// 获取指定设备的 GATT 服务与特征（含描述符）
+pub async fn get_device_services(state: &AppState, device_id: String) -> Result<Vec<GattService>, String> {
+    // 从注册表定位 peripheral
+    let peripheral = registry::resolve(state, &device_id)?;
+
+    // 读取服务与特征
+    let mut result: Vec<GattService> = Vec::new();
+    match peripheral.services() {
+        Ok(services) => {
+            for s in services {
+                let uuid = s.uuid.clone();
+                let mut chars_vec: Vec<GattCharacteristic> = Vec::new();
+                for c in s.characteristics {
+                    let mut descriptors = Vec::new();
+                    let mut user_description = None;
+                    let mut presentation_format = None;
+                    let mut extended_properties = None;
+                    for d in &c.descriptors {
+                        let short = assigned_numbers::short_uuid(d);
+                        let value = match short {
+                            Some(n) if METADATA_DESCRIPTORS.contains(&n) => peripheral.read_descriptor(&uuid, &c.uuid, d).ok(),
+                            _ => None,
+                        };
+                        match (short, value.as_deref()) {
+                            (Some(0x2901), Some(v)) => {
+                                user_description = Some(String::from_utf8_lossy(v).trim_end_matches('\0').to_string());
+                            }
+                            (Some(0x2904), Some(v)) => presentation_format = PresentationFormat::parse(v),
+                            (Some(0x2900), Some(&[lo, hi, ..])) => extended_properties = Some(u16::from_le_bytes([lo, hi])),
+                            _ => {}
+                        }
+                        descriptors.push(GattDescriptor {
+                            uuid: d.clone(),
+                            name: assigned_numbers::descriptor_name(d).map(String::from),
+                            value: value.map(hex::encode_upper),
+                        });
+                    }
+                    state
+                        .presentation_formats
+                        .lock()
+                        .unwrap()
+                        .insert(format_key(&device_id, &uuid, &c.uuid), presentation_format.clone());
+                    let (property_bits, properties) = property_list(&c, extended_properties);
+                    chars_vec.push(GattCharacteristic {
+                        name: assigned_numbers::characteristic_name(&c.uuid).map(String::from),
+                        uuid: c.uuid,
+                        properties,
+                        property_bits,
+                        extended_properties,
+                        security: c.security,
+                        descriptors,
+                        user_description,
+                        presentation_format,
+                    });
+                }
+                let name = assigned_numbers::service_name(&uuid).map(String::from);
+                result.push(GattService { uuid, name, characteristics: chars_vec });
+            }
+            history::record_gatt(state, &device_id, &result);
+            Ok(result)
+        }
+        Err(e) => Err(format!("获取服务失败: {}", e)),
+    }
+}
+
+// 读取指定特征值，返回十六进制字符串及标准特征的解码结果
+pub async fn read_characteristic(
+    state: &AppState,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+) -> Result<CharacteristicValue, String> {
+    // 从注册表定位 peripheral
+    let peripheral = registry::resolve(state, &device_id)?;
+
+    // 尝试确保已连接
+    match peripheral.is_connected() {
+        Ok(false) => {
+            if let Err(e) = peripheral.connect() { eprintln!("为读取而连接失败: {}", e); }
+            // 给系统一点时间建立链路
+            let _ = sleep(Duration::from_millis(150)).await;
+        }
+        Ok(true) => {}
+        Err(e) => eprintln!("检查连接状态失败: {}", e),
+    }
+
+    // UUID 形式适配：原样、扩展为 128-bit、压缩为 16-bit
+    fn to_full(uuid: &str) -> String {
+        let u = uuid.to_uppercase();
+        if u.len() == 4 { format!("0000{}-0000-1000-8000-00805F9B34FB", u) } else { u }
+    }
+    fn to_short(uuid: &str) -> String {
+        let u = uuid.to_uppercase();
+        if u.len() == 36 {
+            if let Some(caps) = regex::Regex::new(r"^0000([0-9A-F]{4})-0000-1000-8000-00805F9B34FB$").unwrap().captures(&u) {
+                return caps.get(1).unwrap().as_str().to_string();
+            }
+        }
+        u
+    }
+
+    let candidates = vec![
+        (service_uuid.clone(), characteristic_uuid.clone()),
+        (to_full(&service_uuid), to_full(&characteristic_uuid)),
+        (to_short(&service_uuid), to_short(&characteristic_uuid)),
+    ];
+
+    let format = presentation_format(state, peripheral.as_ref(), &device_id, &service_uuid, &characteristic_uuid);
+    for (s, c) in candidates {
+    // 最多尝试 3 次，处理偶发的 GATT 忙或尚未就绪
+        let mut attempt = 0;
+        loop {
+            match peripheral.read(&s, &c) {
+        Ok(bytes) => return Ok(CharacteristicValue::new(&characteristic_uuid, &bytes, format.as_ref())),
+                Err(e) => {
+                    attempt += 1;
+                    if attempt >= 3 {
+                        eprintln!("读取失败(多次重试后放弃): svc='{}', chr='{}', err={}", s, c, e);
+                        break;
+                    }
+                    eprintln!("读取失败(重试 #{}): svc='{}', chr='{}', err={}", attempt, s, c, e);
+                    let _ = sleep(Duration::from_millis(120)).await;
+                }
+            }
+        }
+    }
+
+    Err("读取特征值失败：所有 UUID 形式均未成功".to_string())
+}
+
+pub fn make_key(device_id: &str, service_uuid: &str, characteristic_uuid: &str) -> String {
+    format!("{}|{}|{}", device_id, service_uuid, characteristic_uuid)
+}
+
+pub(crate) fn format_key(device_id: &str, service_uuid: &str, characteristic_uuid: &str) -> String {
+    make_key(device_id, &adv::normalize_uuid(service_uuid), &adv::normalize_uuid(characteristic_uuid))
+}
+
+// 特征的呈现格式：优先取枚举服务时的缓存，未缓存时查找并读取 0x2904 描述符
+fn presentation_format(
+    state: &AppState,
+    peripheral: &dyn BlePeripheral,
+    device_id: &str,
+    service_uuid: &str,
+    characteristic_uuid: &str,
+) -> Option<PresentationFormat> {
+    let key = format_key(device_id, service_uuid, characteristic_uuid);
+    if let Some(format) = state.presentation_formats.lock().unwrap().get(&key) {
+        return format.clone();
+    }
+    let (service, characteristic) = (adv::normalize_uuid(service_uuid), adv::normalize_uuid(characteristic_uuid));
+    let descriptor = peripheral
+        .services()
+        .ok()?
+        .into_iter()
+        .filter(|s| adv::normalize_uuid(&s.uuid) == service)
+        .flat_map(|s| s.characteristics)
+        .find(|c| adv::normalize_uuid(&c.uuid) == characteristic)?
+        .descriptors
+        .into_iter()
+        .find(|d| assigned_numbers::short_uuid(d) == Some(0x2904));
+    let format = match descriptor {
+        Some(d) => PresentationFormat::parse(&peripheral.read_descriptor(service_uuid, characteristic_uuid, &d).ok()?),
+        None => None,
+    };
+    state.presentation_formats.lock().unwrap().insert(key, format.clone());
+    format
+}
+
+// 写入特征值（write_type: "request" | "command"，数据编码见 payload 模块）
+pub async fn write_characteristic(
+    state: &AppState,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+    data: String,
+    write_type: Option<String>,
+    encoding: Option<PayloadEncoding>,
+) -> Result<(), String> {
+    let peripheral = registry::resolve(state, &device_id)?;
+
+    // 尝试连接
+    if matches!(peripheral.is_connected(), Ok(false)) {
+        let _ = peripheral.connect();
+    }
+
+    let bytes = payload::parse_data(&data, encoding.as_ref())?;
+    let wt = write_type.unwrap_or_else(|| "request".to_string());
+    match wt.as_str() {
+        "command" => peripheral
+            .write_command(&service_uuid, &characteristic_uuid, &bytes)
+            .map_err(|e| format!("写入(命令)失败: {}", e))?,
+        _ => peripheral
+            .write_request(&service_uuid, &characteristic_uuid, &bytes)
+            .map_err(|e| format!("写入(请求)失败: {}", e))?,
+    }
+    Ok(())
+}
+
+// 读取描述符，返回大写十六进制
+pub async fn read_descriptor(
+    state: &AppState,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+    descriptor_uuid: String,
+) -> Result<String, String> {
+    let peripheral = registry::resolve(state, &device_id)?;
+    if matches!(peripheral.is_connected(), Ok(false)) {
+        let _ = peripheral.connect();
+    }
+    peripheral
+        .read_descriptor(&service_uuid, &characteristic_uuid, &descriptor_uuid)
+        .map(hex::encode_upper)
+        .map_err(|e| format!("读取描述符失败: {}", e))
+}
+
+// 写入描述符（数据编码同 write_characteristic）
+pub async fn write_descriptor(
+    state: &AppState,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+    descriptor_uuid: String,
+    data: String,
+    encoding: Option<PayloadEncoding>,
+) -> Result<(), String> {
+    let bytes = payload::parse_data(&data, encoding.as_ref())?;
+    let peripheral = registry::resolve(state, &device_id)?;
+    if matches!(peripheral.is_connected(), Ok(false)) {
+        let _ = peripheral.connect();
+    }
+    peripheral
+        .write_descriptor(&service_uuid, &characteristic_uuid, &descriptor_uuid, &bytes)
+        .map_err(|e| format!("写入描述符失败: {}", e))?;
+    // 呈现格式被改写后下次读取时重新获取
+    if assigned_numbers::short_uuid(&descriptor_uuid) == Some(0x2904) {
+        state.presentation_formats.lock().unwrap().remove(&format_key(&device_id, &service_uuid, &characteristic_uuid));
+    }
+    Ok(())
+}
+
+// 订阅通知
+pub async fn notify_characteristic(
+    state: &AppState,
+    sink: Arc<dyn EventSink>,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+) -> Result<(), String> {
+    subscribe(state, sink, device_id, service_uuid, characteristic_uuid, false, false)
+}
+
+// 订阅指示
+pub async fn indicate_characteristic(
+    state: &AppState,
+    sink: Arc<dyn EventSink>,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+) -> Result<(), String> {
+    subscribe(state, sink, device_id, service_uuid, characteristic_uuid, true, false)
+}
+
+// 订阅并在后台转发事件；每条数据带时间戳写入通知缓冲区（录制中时同时写入文件）。
+// 同一特征重复订阅时替换旧任务；restore 为重连后自动恢复，沿用原有订阅记录
+pub(crate) fn subscribe(
+    state: &AppState,
+    sink: Arc<dyn EventSink>,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+    indicate: bool,
+    restore: bool,
+) -> Result<(), String> {
+    let peripheral = registry::resolve(state, &device_id)?;
+
+    let (mut stream, kind) = if indicate {
+        let stream = peripheral
+            .indicate(&service_uuid, &characteristic_uuid)
+            .map_err(|e| format!("订阅指示失败: {}", e))?;
+        (stream, "indicate")
+    } else {
+        let stream = peripheral
+            .notify(&service_uuid, &characteristic_uuid)
+            .map_err(|e| format!("订阅通知失败: {}", e))?;
+        (stream, "notify")
+    };
+
+    let format = presentation_format(state, peripheral.as_ref(), &device_id, &service_uuid, &characteristic_uuid);
+    let key = format_key(&device_id, &service_uuid, &characteristic_uuid);
+    let info = subscriptions::new_info(&device_id, &service_uuid, &characteristic_uuid, kind);
+    let log = state.notifications.clone();
+    let tracker = state.subscriptions.clone();
+    let sink_for_stats = sink.clone();
+    // 持锁期间启动任务并登记，任务的第一条数据会等到登记完成后再计数
+    let mut subs = state.subscriptions.lock().unwrap();
+    let generation = subs.next_generation();
+    let task_key = key.clone();
+    let handle = tokio::spawn(async move {
+        while let Some(data) = stream.next().await {
+            let value = CharacteristicValue::new(&characteristic_uuid, &data, format.as_ref());
+            let record = NotificationRecord::new(&device_id, &service_uuid, &characteristic_uuid, kind, &data);
+            log.lock().unwrap().record(&record, true);
+            tracker.lock().unwrap().on_message(&task_key, generation, &record, &data);
+            let _ = sink.emit("characteristic-value", notifications::event_payload(&record, &value.decoded, false));
+        }
+        tracker.lock().unwrap().on_closed(&task_key, generation);
+    });
+    subs.insert(key, generation, info, handle, restore);
+    drop(subs);
+    subscriptions::ensure_stats_ticker(state, &sink_for_stats);
+    Ok(())
+}
+
+// 退订（适用于通知/指示）
+pub async fn unsubscribe_characteristic(
+    state: &AppState,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+) -> Result<(), String> {
+    state.subscriptions.lock().unwrap().remove(&format_key(&device_id, &service_uuid, &characteristic_uuid));
+
+    // 也尝试调用底层退订
+    if let Ok(peripheral) = registry::resolve(state, &device_id) {
+        let _ = peripheral.unsubscribe(&service_uuid, &characteristic_uuid);
+    }
+    Ok(())
+}
+
+// 获取 MTU
+pub async fn get_mtu(state: &AppState, device_id: String) -> Result<u16, String> {
+    let peripheral = registry::resolve(state, &device_id)?;
+    peripheral.mtu().map_err(|e| format!("获取 MTU 失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::{SimCharacteristic, SimDevice, SimService, SimulatedBackend};
+    use crate::backend::{SecurityLevel, SecurityRequirements};
+    use crate::device;
+    use crate::events::NullSink;
+
+    #[tokio::test]
+    async fn services_report_property_bits_extended_properties_and_security() {
+        let backend = Arc::new(SimulatedBackend::new());
+        backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(
+            SimDevice::new("PROPS", "AA:BB:CC:DD:EE:0A").paired(true).gatt_service(
+                SimService::new("FFF0")
+                    .characteristic(SimCharacteristic::new("FFF1").read().write_without_response())
+                    .characteristic(
+                        SimCharacteristic::new("FFF2")
+                            .write()
+                            .notify()
+                            .reliable_write()
+                            .writable_auxiliaries()
+                            .security(SecurityLevel::Encryption, SecurityLevel::Authentication),
+                    )
+                    .characteristic(SimCharacteristic::new("FFF3").broadcast().indicate().write_signed()),
+            ),
+        );
+        let state = AppState::new(backend);
+        device::connect_device(&state, Arc::new(NullSink), "PROPS".into(), None).await.unwrap();
+
+        let services = get_device_services(&state, "PROPS".into()).await.unwrap();
+        let chars = &services[0].characteristics;
+        let summary: Vec<(u8, Vec<&str>)> =
+            chars.iter().map(|c| (c.property_bits, c.properties.iter().map(String::as_str).collect())).collect();
+        // 写命令为 0x04、写请求为 0x08
+        assert_eq!(
+            summary,
+            vec![
+                (0x06, vec!["Read", "WriteCommand"]),
+                (0x98, vec!["WriteRequest", "Notify", "ExtendedProperties", "ReliableWrite", "WritableAuxiliaries"]),
+                (0x61, vec!["Broadcast", "Indicate", "AuthenticatedSignedWrites"]),
+            ]
+        );
+
+        assert_eq!(chars[0].extended_properties, None);
+        assert_eq!(chars[1].extended_properties, Some(0x0003));
+        assert_eq!(chars[1].descriptors.iter().find(|d| d.uuid.starts_with("00002900")).unwrap().value.as_deref(), Some("0300"));
+        assert_eq!(
+            chars[1].security,
+            Some(SecurityRequirements { read: SecurityLevel::Encryption, write: SecurityLevel::Authentication })
+        );
+        assert!(chars[0].security.is_none() && chars[2].security.is_none());
+    }
+}
@@ -0,0 +1,595 @@
+// 标准特征值解码：按 SIG 特征 UUID 把读取或通知得到的字节解析为结构化数据，
+// 非标准特征带有呈现格式描述符 (0x2904) 时按描述符格式化。
+// 多字节字段均为小端；温度、血压等使用 IEEE-11073 FLOAT / SFLOAT 编码
+use serde::{Deserialize, Serialize};
+
+use crate::assigned_numbers;
+
+// 读取结果与 characteristic-value 事件共用：原始十六进制 + 可选的解码结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CharacteristicValue {
+    pub value: String, // 大写十六进制
+    pub decoded: Option<DecodedValue>,
+}
+
+impl CharacteristicValue {
+    // 标准特征解码优先，其次按呈现格式描述符
+    pub fn new(characteristic_uuid: &str, bytes: &[u8], format: Option<&PresentationFormat>) -> Self {
+        let decoded = decode(characteristic_uuid, bytes).or_else(|| format.and_then(|f| f.format_value(bytes)));
+        Self { value: hex::encode_upper(bytes), decoded }
+    }
+}
+
+// Characteristic Presentation Format (0x2904)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentationFormat {
+    pub format: u8,
+    #[serde(default)]
+    pub format_name: Option<String>,
+    pub exponent: i8, // 实际值 = 原始值 × 10^exponent
+    pub unit: u16,
+    #[serde(default)]
+    pub unit_symbol: Option<String>,
+    pub namespace: u8,
+    pub description: u16,
+}
+
+const FORMAT_NAMES: &[&str] = &[
+    "", "boolean", "uint2", "uint4", "uint8", "uint12", "uint16", "uint24", "uint32", "uint48", "uint64", "uint128",
+    "sint8", "sint12", "sint16", "sint24", "sint32", "sint48", "sint64", "sint128", "float32", "float64", "SFLOAT",
+    "FLOAT", "duint16", "utf8s", "utf16s", "struct",
+];
+
+impl PresentationFormat {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(bytes);
+        let format = r.u8()?;
+        let exponent = r.u8()? as i8;
+        let unit = r.u16()?;
+        Some(Self {
+            format,
+            format_name: FORMAT_NAMES.get(usize::from(format)).filter(|n| !n.is_empty()).map(|n| n.to_string()),
+            exponent,
+            unit,
+            unit_symbol: assigned_numbers::unit_symbol(unit).map(String::from),
+            namespace: r.u8()?,
+            description: r.u16()?,
+        })
+    }
+
+    // 按格式读取首个值；struct、uint128 等不支持的格式返回 None
+    pub fn format_value(&self, bytes: &[u8]) -> Option<DecodedValue> {
+        let int = |len: usize, signed: bool| -> Option<f64> {
+            let b = bytes.get(..len)?;
+            let mut raw = [0u8; 8];
+            raw[..len].copy_from_slice(b);
+            let unsigned = u64::from_le_bytes(raw);
+            let bits = (len * 8) as u32;
+            let value = if signed { ((unsigned << (64 - bits)) as i64 >> (64 - bits)) as f64 } else { unsigned as f64 };
+            Some(value)
+        };
+        let raw = match self.format {
+            0x01..=0x04 => int(1, false).map(|v| if self.format == 0x01 { (v != 0.0) as u8 as f64 } else { v }),
+            0x05 => int(2, false).map(|v| f64::from(v as u16 & 0x0FFF)),
+            0x06 => int(2, false),
+            0x07 => int(3, false),
+            0x08 => int(4, false),
+            0x09 => int(6, false),
+            0x0A => int(8, false),
+            0x0C => int(1, true),
+            0x0D => int(2, true).map(|v| f64::from((((v as i16) << 4) >> 4) as i32)),
+            0x0E => int(2, true),
+            0x0F => int(3, true),
+            0x10 => int(4, true),
+            0x11 => int(6, true),
+            0x12 => int(8, true),
+            0x14 => Some(f64::from(f32::from_le_bytes(bytes.get(..4)?.try_into().ok()?))),
+            0x15 => Some(f64::from_le_bytes(bytes.get(..8)?.try_into().ok()?)),
+            0x16 => Reader::new(bytes).sfloat()?,
+            0x17 => Reader::new(bytes).float()?,
+            0x19 => {
+                let text = String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string();
+                return Some(DecodedValue::Text(Text { value: text }));
+            }
+            0x1A => {
+                let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+                let text = String::from_utf16_lossy(&units).trim_end_matches('\0').to_string();
+                return Some(DecodedValue::Text(Text { value: text }));
+            }
+            _ => return None,
+        }?;
+        // IEEE-11073 浮点已自带指数
+        let value = if matches!(self.format, 0x14..=0x17) {
+            raw
+        } else {
+            round(raw * 10f64.powi(i32::from(self.exponent)), (-i32::from(self.exponent)).max(0))
+        };
+        Some(scalar(value, self.unit_symbol.as_deref().unwrap_or("")))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DecodedValue {
+    Text(Text),
+    Scalar(Scalar),
+    Enumerated(Enumerated),
+    HeartRate(HeartRateMeasurement),
+    Temperature(TemperatureMeasurement),
+    BloodPressure(BloodPressureMeasurement),
+    Csc(CscMeasurement),
+    Rsc(RscMeasurement),
+    Appearance(Appearance),
+    PnpId(PnpId),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Text {
+    pub value: String,
+}
+
+// 带单位的单个数值（电量、发射功率、环境温湿度等）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Scalar {
+    pub value: f64,
+    pub unit: String,
+}
+
+// 枚举值及其名称（传感器位置、体温测量部位等）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Enumerated {
+    pub value: u8,
+    pub name: Option<String>,
+}
+
+// Heart Rate Measurement (0x2A37)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeartRateMeasurement {
+    pub bpm: u16,
+    pub sensor_contact: Option<bool>, // None 表示不支持接触检测
+    pub energy_expended: Option<u16>, // 千焦
+    pub rr_intervals: Vec<f64>,       // 秒
+}
+
+// Temperature Measurement (0x2A1C) / Intermediate Temperature (0x2A1E)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemperatureMeasurement {
+    pub value: Option<f64>, // NaN / NRes 等特殊值为 None
+    pub unit: String,       // °C / °F
+    pub timestamp: Option<String>,
+    pub temperature_type: Option<Enumerated>,
+}
+
+// Blood Pressure Measurement (0x2A35)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BloodPressureMeasurement {
+    pub systolic: Option<f64>,
+    pub diastolic: Option<f64>,
+    pub mean_arterial_pressure: Option<f64>,
+    pub unit: String, // mmHg / kPa
+    pub timestamp: Option<String>,
+    pub pulse_rate: Option<f64>, // 次/分
+    pub user_id: Option<u8>,
+    pub measurement_status: Option<u16>,
+}
+
+// CSC Measurement (0x2A5B)，事件时间单位为 1/1024 秒
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CscMeasurement {
+    pub wheel_revolutions: Option<u32>,
+    pub last_wheel_event_time: Option<u16>,
+    pub crank_revolutions: Option<u16>,
+    pub last_crank_event_time: Option<u16>,
+}
+
+// RSC Measurement (0x2A53)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RscMeasurement {
+    pub speed: f64,                  // 米/秒
+    pub cadence: u8,                 // 步/分
+    pub stride_length: Option<f64>,  // 米
+    pub total_distance: Option<f64>, // 米
+    pub running: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Appearance {
+    pub value: u16,
+    pub name: Option<String>,
+}
+
+// PnP ID (0x2A50)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpId {
+    pub vendor_id_source: u8, // 1 = 蓝牙 SIG，2 = USB-IF
+    pub vendor_id: u16,
+    pub vendor_name: Option<String>, // 仅 SIG 来源可解析
+    pub product_id: u16,
+    pub product_version: u16,
+}
+
+// 顺序读取小端字段，越界返回 None
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn sfloat(&mut self) -> Option<Option<f64>> {
+        self.u16().map(sfloat)
+    }
+
+    fn float(&mut self) -> Option<Option<f64>> {
+        self.u32().map(float)
+    }
+
+    // Date Time：年(u16) 月 日 时 分 秒
+    fn date_time(&mut self) -> Option<String> {
+        let year = self.u16()?;
+        let b = self.take(5)?;
+        Some(format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", year, b[0], b[1], b[2], b[3], b[4]))
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.pos.min(self.bytes.len())..]
+    }
+}
+
+// IEEE-11073 16 位 SFLOAT：4 位有符号指数 + 12 位有符号尾数；NaN、NRes、±INF 与保留值返回 None
+pub fn sfloat(raw: u16) -> Option<f64> {
+    if matches!(raw, 0x07FE..=0x0802) {
+        return None;
+    }
+    let mantissa = i32::from(((raw << 4) as i16) >> 4);
+    let exponent = i32::from((raw as i16) >> 12);
+    Some(f64::from(mantissa) * 10f64.powi(exponent))
+}
+
+// IEEE-11073 32 位 FLOAT：8 位有符号指数 + 24 位有符号尾数
+pub fn float(raw: u32) -> Option<f64> {
+    if matches!(raw & 0x00FF_FFFF, 0x007F_FFFE..=0x0080_0002) {
+        return None;
+    }
+    let mantissa = ((raw << 8) as i32) >> 8;
+    let exponent = i32::from((raw >> 24) as i8);
+    Some(f64::from(mantissa) * 10f64.powi(exponent))
+}
+
+// 去掉浮点误差，保留到指数对应的位数
+fn round(value: f64, digits: i32) -> f64 {
+    let factor = 10f64.powi(digits);
+    (value * factor).round() / factor
+}
+
+fn enumerated(value: u8, names: &[&str]) -> Enumerated {
+    Enumerated { value, name: names.get(usize::from(value)).filter(|n| !n.is_empty()).map(|n| n.to_string()) }
+}
+
+const BODY_SENSOR_LOCATIONS: &[&str] = &["Other", "Chest", "Wrist", "Finger", "Hand", "Ear Lobe", "Foot"];
+const TEMPERATURE_TYPES: &[&str] =
+    &["", "Armpit", "Body (general)", "Ear", "Finger", "Gastro-intestinal Tract", "Mouth", "Rectum", "Toe", "Tympanum"];
+
+fn scalar(value: f64, unit: &str) -> DecodedValue {
+    DecodedValue::Scalar(Scalar { value, unit: unit.to_string() })
+}
+
+fn heart_rate(bytes: &[u8]) -> Option<DecodedValue> {
+    let mut r = Reader::new(bytes);
+    let flags = r.u8()?;
+    let bpm = if flags & 0x01 != 0 { r.u16()? } else { u16::from(r.u8()?) };
+    let sensor_contact = (flags & 0x04 != 0).then_some(flags & 0x02 != 0);
+    let energy_expended = if flags & 0x08 != 0 { Some(r.u16()?) } else { None };
+    let mut rr_intervals = Vec::new();
+    if flags & 0x10 != 0 {
+        while let Some(rr) = r.u16() {
+            rr_intervals.push(round(f64::from(rr) / 1024.0, 4));
+        }
+    }
+    Some(DecodedValue::HeartRate(HeartRateMeasurement { bpm, sensor_contact, energy_expended, rr_intervals }))
+}
+
+fn temperature(bytes: &[u8]) -> Option<DecodedValue> {
+    let mut r = Reader::new(bytes);
+    let flags = r.u8()?;
+    let exponent = bytes.get(4).map(|e| -i32::from(*e as i8)).unwrap_or(0).max(0);
+    let value = r.float()?.map(|v| round(v, exponent));
+    let timestamp = if flags & 0x02 != 0 { Some(r.date_time()?) } else { None };
+    let temperature_type = if flags & 0x04 != 0 { Some(enumerated(r.u8()?, TEMPERATURE_TYPES)) } else { None };
+    Some(DecodedValue::Temperature(TemperatureMeasurement {
+        value,
+        unit: if flags & 0x01 != 0 { "°F" } else { "°C" }.to_string(),
+        timestamp,
+        temperature_type,
+    }))
+}
+
+fn blood_pressure(bytes: &[u8]) -> Option<DecodedValue> {
+    let mut r = Reader::new(bytes);
+    let flags = r.u8()?;
+    let systolic = r.sfloat()?;
+    let diastolic = r.sfloat()?;
+    let mean_arterial_pressure = r.sfloat()?;
+    let timestamp = if flags & 0x02 != 0 { Some(r.date_time()?) } else { None };
+    let pulse_rate = if flags & 0x04 != 0 { r.sfloat()? } else { None };
+    let user_id = if flags & 0x08 != 0 { Some(r.u8()?) } else { None };
+    let measurement_status = if flags & 0x10 != 0 { Some(r.u16()?) } else { None };
+    Some(DecodedValue::BloodPressure(BloodPressureMeasurement {
+        systolic,
+        diastolic,
+        mean_arterial_pressure,
+        unit: if flags & 0x01 != 0 { "kPa" } else { "mmHg" }.to_string(),
+        timestamp,
+        pulse_rate,
+        user_id,
+        measurement_status,
+    }))
+}
+
+fn csc(bytes: &[u8]) -> Option<DecodedValue> {
+    let mut r = Reader::new(bytes);
+    let flags = r.u8()?;
+    let (wheel_revolutions, last_wheel_event_time) =
+        if flags & 0x01 != 0 { (Some(r.u32()?), Some(r.u16()?)) } else { (None, None) };
+    let (crank_revolutions, last_crank_event_time) =
+        if flags & 0x02 != 0 { (Some(r.u16()?), Some(r.u16()?)) } else { (None, None) };
+    Some(DecodedValue::Csc(CscMeasurement { wheel_revolutions, last_wheel_event_time, crank_revolutions, last_crank_event_time }))
+}
+
+fn rsc(bytes: &[u8]) -> Option<DecodedValue> {
+    let mut r = Reader::new(bytes);
+    let flags = r.u8()?;
+    let speed = round(f64::from(r.u16()?) / 256.0, 3);
+    let cadence = r.u8()?;
+    let stride_length = if flags & 0x01 != 0 { Some(f64::from(r.u16()?) / 100.0) } else { None };
+    let total_distance = if flags & 0x02 != 0 { Some(f64::from(r.u32()?) / 10.0) } else { None };
+    Some(DecodedValue::Rsc(RscMeasurement { speed, cadence, stride_length, total_distance, running: flags & 0x04 != 0 }))
+}
+
+fn pnp_id(bytes: &[u8]) -> Option<DecodedValue> {
+    let mut r = Reader::new(bytes);
+    let vendor_id_source = r.u8()?;
+    let vendor_id = r.u16()?;
+    let vendor_name = (vendor_id_source == 1).then(|| assigned_numbers::company_name(vendor_id)).flatten();
+    Some(DecodedValue::PnpId(PnpId {
+        vendor_id_source,
+        vendor_id,
+        vendor_name: vendor_name.map(String::from),
+        product_id: r.u16()?,
+        product_version: r.u16()?,
+    }))
+}
+
+// 按特征 UUID 解码；非标准特征或数据长度不足时返回 None
+pub fn decode(characteristic_uuid: &str, bytes: &[u8]) -> Option<DecodedValue> {
+    let mut r = Reader::new(bytes);
+    match assigned_numbers::short_uuid(characteristic_uuid)? {
+        // Device Name 与 Device Information 中的字符串特征
+        0x2A00 | 0x2A24 | 0x2A25 | 0x2A26 | 0x2A27 | 0x2A28 | 0x2A29 => {
+            let text = String::from_utf8_lossy(r.rest()).trim_end_matches('\0').to_string();
+            Some(DecodedValue::Text(Text { value: text }))
+        }
+        0x2A01 => {
+            let value = r.u16()?;
+            Some(DecodedValue::Appearance(Appearance {
+                value,
+                name: assigned_numbers::appearance_name(value).map(String::from),
+            }))
+        }
+        0x2A07 => Some(scalar(f64::from(r.u8()? as i8), "dBm")),
+        0x2A19 => Some(scalar(f64::from(r.u8()?), "%")),
+        0x2A1C | 0x2A1E => temperature(bytes),
+        0x2A1D => Some(DecodedValue::Enumerated(enumerated(r.u8()?, TEMPERATURE_TYPES))),
+        0x2A35 | 0x2A36 => blood_pressure(bytes),
+        0x2A37 => heart_rate(bytes),
+        0x2A38 => Some(DecodedValue::Enumerated(enumerated(r.u8()?, BODY_SENSOR_LOCATIONS))),
+        0x2A50 => pnp_id(bytes),
+        0x2A53 => rsc(bytes),
+        0x2A5B => csc(bytes),
+        // Environmental Sensing：温度 0.01 °C，湿度 0.01 %
+        0x2A6E => Some(scalar(f64::from(r.u16()? as i16) / 100.0, "°C")),
+        0x2A6F => Some(scalar(f64::from(r.u16()?) / 100.0, "%")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        hex::decode(s.replace(' ', "")).unwrap()
+    }
+
+    #[test]
+    fn battery_level() {
+        assert_eq!(decode("2A19", &[0x57]), Some(scalar(87.0, "%")));
+        assert_eq!(decode("2A19", &[]), None);
+    }
+
+    #[test]
+    fn heart_rate_uint8_without_contact_support() {
+        let Some(DecodedValue::HeartRate(hr)) = decode("00002A37-0000-1000-8000-00805F9B34FB", &[0x00, 0x48]) else { panic!() };
+        assert_eq!(hr.bpm, 72);
+        assert_eq!(hr.sensor_contact, None);
+        assert_eq!(hr.energy_expended, None);
+        assert!(hr.rr_intervals.is_empty());
+    }
+
+    #[test]
+    fn heart_rate_uint16_with_energy_and_rr() {
+        // flags：UINT16 格式、检测到接触、含能量消耗与 RR 间期
+        let Some(DecodedValue::HeartRate(hr)) = decode("2A37", &hex("1F 2C01 0A00 0004 0002")) else { panic!() };
+        assert_eq!(hr.bpm, 300);
+        assert_eq!(hr.sensor_contact, Some(true));
+        assert_eq!(hr.energy_expended, Some(10));
+        assert_eq!(hr.rr_intervals, vec![1.0, 0.5]);
+    }
+
+    #[test]
+    fn heart_rate_contact_not_detected() {
+        let Some(DecodedValue::HeartRate(hr)) = decode("2A37", &[0x04, 0x3C]) else { panic!() };
+        assert_eq!(hr.sensor_contact, Some(false));
+    }
+
+    #[test]
+    fn ieee_11073_float() {
+        // 36.4 = 364 × 10^-1
+        assert_eq!(float(0xFF00016C).map(|v| round(v, 1)), Some(36.4));
+        // 负尾数：-1 × 10^2
+        assert_eq!(float(0x02FFFFFF), Some(-100.0));
+        assert_eq!(float(0x007FFFFF), None); // NaN
+        assert_eq!(float(0x00800000), None); // NRes
+        assert_eq!(float(0x007FFFFE), None); // +INF
+        assert_eq!(float(0x00800002), None); // -INF
+    }
+
+    #[test]
+    fn ieee_11073_sfloat() {
+        // 120 mmHg = 120 × 10^0
+        assert_eq!(sfloat(0x0078), Some(120.0));
+        // 7.2 = 72 × 10^-1
+        assert_eq!(sfloat(0xF048).map(|v| round(v, 1)), Some(7.2));
+        // 负尾数：-1 × 10^0
+        assert_eq!(sfloat(0x0FFF), Some(-1.0));
+        assert_eq!(sfloat(0x07FF), None); // NaN
+        assert_eq!(sfloat(0x0800), None); // NRes
+    }
+
+    #[test]
+    fn temperature_measurement_with_timestamp_and_type() {
+        // 摄氏、含时间戳与测量部位：36.4 °C，2024-03-15 08:30:00，耳温
+        let bytes = hex("06 6C0100FF E807 03 0F 08 1E 00 03");
+        let Some(DecodedValue::Temperature(t)) = decode("2A1C", &bytes) else { panic!() };
+        assert_eq!(t.value, Some(36.4));
+        assert_eq!(t.unit, "°C");
+        assert_eq!(t.timestamp.as_deref(), Some("2024-03-15T08:30:00"));
+        assert_eq!(t.temperature_type, Some(Enumerated { value: 3, name: Some("Ear".into()) }));
+    }
+
+    #[test]
+    fn temperature_measurement_fahrenheit_nan() {
+        let Some(DecodedValue::Temperature(t)) = decode("2A1E", &hex("01 FFFF7F00")) else { panic!() };
+        assert_eq!(t.value, None);
+        assert_eq!(t.unit, "°F");
+    }
+
+    #[test]
+    fn blood_pressure_with_pulse() {
+        // mmHg：120/80，平均动脉压 93，脉率 72
+        let bytes = hex("04 7800 5000 5D00 4800");
+        let Some(DecodedValue::BloodPressure(bp)) = decode("2A35", &bytes) else { panic!() };
+        assert_eq!((bp.systolic, bp.diastolic, bp.mean_arterial_pressure), (Some(120.0), Some(80.0), Some(93.0)));
+        assert_eq!(bp.unit, "mmHg");
+        assert_eq!(bp.pulse_rate, Some(72.0));
+        assert_eq!(bp.user_id, None);
+    }
+
+    #[test]
+    fn device_information_strings() {
+        assert_eq!(decode("2A29", b"Nordic\0"), Some(DecodedValue::Text(Text { value: "Nordic".into() })));
+        assert_eq!(decode("2A26", b"1.2.3"), Some(DecodedValue::Text(Text { value: "1.2.3".into() })));
+    }
+
+    #[test]
+    fn csc_measurement() {
+        let bytes = hex("03 10270000 0004 3200 0008");
+        let Some(DecodedValue::Csc(c)) = decode("2A5B", &bytes) else { panic!() };
+        assert_eq!(c.wheel_revolutions, Some(10000));
+        assert_eq!(c.last_wheel_event_time, Some(1024));
+        assert_eq!(c.crank_revolutions, Some(50));
+        assert_eq!(c.last_crank_event_time, Some(2048));
+
+        let Some(DecodedValue::Csc(c)) = decode("2A5B", &hex("02 3200 0008")) else { panic!() };
+        assert_eq!(c.wheel_revolutions, None);
+        assert_eq!(c.crank_revolutions, Some(50));
+    }
+
+    #[test]
+    fn rsc_measurement() {
+        // 跑步，3.5 m/s，步频 170，步幅 1.25 m，总距离 1234.5 m
+        let bytes = hex("07 8003 AA 7D00 39300000");
+        let Some(DecodedValue::Rsc(r)) = decode("2A53", &bytes) else { panic!() };
+        assert_eq!(r.speed, 3.5);
+        assert_eq!(r.cadence, 170);
+        assert_eq!(r.stride_length, Some(1.25));
+        assert_eq!(r.total_distance, Some(1234.5));
+        assert!(r.running);
+    }
+
+    #[test]
+    fn appearance_and_pnp_id() {
+        assert_eq!(
+            decode("2A01", &hex("C203")),
+            Some(DecodedValue::Appearance(Appearance { value: 0x03C2, name: Some("Mouse".into()) }))
+        );
+        let Some(DecodedValue::PnpId(p)) = decode("2A50", &hex("01 4C00 0D02 0100")) else { panic!() };
+        assert_eq!((p.vendor_id_source, p.vendor_id, p.product_id, p.product_version), (1, 0x004C, 0x020D, 1));
+        assert_eq!(p.vendor_name.as_deref(), Some("Apple, Inc."));
+    }
+
+    #[test]
+    fn truncated_or_unknown_values() {
+        assert_eq!(decode("2A37", &[0x01, 0x48]), None);
+        assert_eq!(decode("2A5B", &[0x01, 0x10]), None);
+        assert_eq!(decode("6E400003-B5A3-F393-E0A9-E50E24DCCA9E", &[0x01]), None);
+    }
+
+    #[test]
+    fn serializes_with_type_tag() {
+        let json = serde_json::to_value(CharacteristicValue::new("2A38", &[0x01], None)).unwrap();
+        assert_eq!(json, serde_json::json!({"value": "01", "decoded": {"type": "enumerated", "value": 1, "name": "Chest"}}));
+    }
+
+    #[test]
+    fn presentation_format_scales_signed_values() {
+        // sint16，指数 -2，摄氏度
+        let format = PresentationFormat::parse(&hex("0E FE 2F27 01 0000")).unwrap();
+        assert_eq!(format.format_name.as_deref(), Some("sint16"));
+        assert_eq!(format.unit_symbol.as_deref(), Some("°C"));
+        assert_eq!(format.format_value(&hex("3C0A")), Some(scalar(26.2, "°C")));
+        assert_eq!(format.format_value(&hex("60F0")), Some(scalar(-40.0, "°C")));
+        assert_eq!(format.format_value(&[0x01]), None);
+    }
+
+    #[test]
+    fn presentation_format_applies_to_custom_characteristics_only() {
+        // uint8 百分比
+        let format = PresentationFormat::parse(&hex("04 00 AD27 01 0000")).unwrap();
+        let custom = CharacteristicValue::new("6E400004-B5A3-F393-E0A9-E50E24DCCA9E", &[0x2A], Some(&format));
+        assert_eq!(custom.decoded, Some(scalar(42.0, "%")));
+        let utf8 = PresentationFormat::parse(&hex("19 00 0027 01 0000")).unwrap();
+        assert_eq!(utf8.format_value(b"on"), Some(DecodedValue::Text(Text { value: "on".into() })));
+        // 标准特征仍按规范解码
+        let battery = CharacteristicValue::new("2A19", &[0x57], Some(&utf8));
+        assert_eq!(battery.decoded, Some(scalar(87.0, "%")));
+    }
+}
@@ -0,0 +1,663 @@
+// 设备历史库：用 SQLite 持久化所有扫描到的设备，跨会话保留首次/最近出现时间、RSSI、名称、厂商数据与服务数据修订及 GATT 表
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{self, BleDevice, GattService};
+use crate::registry;
+use crate::state::AppState;
+
+// 同一设备两次 RSSI 采样的最小间隔；名称、厂商数据或服务数据变化时不受限制
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const SCHEMA_VERSION: i32 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS devices (
+    address     TEXT PRIMARY KEY,
+    identifier  TEXT NOT NULL,
+    first_seen  INTEGER NOT NULL,
+    last_seen   INTEGER NOT NULL,
+    sightings   INTEGER NOT NULL DEFAULT 0,
+    last_rssi   INTEGER,
+    connectable INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS rssi_samples (
+    address    TEXT NOT NULL,
+    ts         INTEGER NOT NULL,
+    rssi       INTEGER NOT NULL,
+    adapter_id TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS rssi_samples_address_ts ON rssi_samples(address, ts);
+CREATE TABLE IF NOT EXISTS names (
+    address    TEXT NOT NULL,
+    name       TEXT NOT NULL,
+    first_seen INTEGER NOT NULL,
+    last_seen  INTEGER NOT NULL,
+    PRIMARY KEY (address, name)
+);
+CREATE TABLE IF NOT EXISTS manufacturer_data (
+    address    TEXT NOT NULL,
+    company_id INTEGER NOT NULL,
+    data       TEXT NOT NULL,
+    first_seen INTEGER NOT NULL,
+    last_seen  INTEGER NOT NULL,
+    PRIMARY KEY (address, company_id, data)
+);
+CREATE INDEX IF NOT EXISTS manufacturer_data_company ON manufacturer_data(company_id);
+CREATE TABLE IF NOT EXISTS service_data (
+    address    TEXT NOT NULL,
+    uuid       TEXT NOT NULL,
+    data       TEXT NOT NULL,
+    first_seen INTEGER NOT NULL,
+    last_seen  INTEGER NOT NULL,
+    PRIMARY KEY (address, uuid, data)
+);
+CREATE TABLE IF NOT EXISTS gatt_tables (
+    address  TEXT NOT NULL,
+    ts       INTEGER NOT NULL,
+    services TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS gatt_tables_address_ts ON gatt_tables(address, ts);
+";
+
+pub struct HistoryDb {
+    conn: Connection,
+    // 地址 -> (最近写入时间, 名称、厂商数据与服务数据指纹)，用于限制写入频率
+    recent: HashMap<String, (Instant, u64)>,
+}
+
+// 历史查询条件，均为可选；地址按前缀匹配，名称按子串匹配（不区分大小写）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default, alias = "companyId")]
+    pub company_id: Option<u16>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub since: Option<i64>, // 最近出现时间下限，Unix 毫秒
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameRecord {
+    pub name: String,
+    pub first_seen: i64,
+    pub last_seen: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManufacturerRevision {
+    pub company_id: u16,
+    pub data: String, // 十六进制
+    pub first_seen: i64,
+    pub last_seen: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceDataRevision {
+    pub uuid: String,
+    pub data: String, // 十六进制
+    pub first_seen: i64,
+    pub last_seen: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryDevice {
+    pub address: String,
+    pub identifier: String,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub sightings: i64,
+    pub last_rssi: Option<i16>,
+    pub connectable: bool,
+    pub names: Vec<NameRecord>,
+    pub manufacturer_data: Vec<ManufacturerRevision>,
+    pub service_data: Vec<ServiceDataRevision>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssiSample {
+    pub timestamp: i64,
+    pub rssi: i16,
+    pub adapter_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GattRevision {
+    pub timestamp: i64,
+    pub services: Vec<GattService>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceHistory {
+    pub device: HistoryDevice,
+    pub rssi: Vec<RssiSample>,
+    pub gatt: Vec<GattRevision>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneSummary {
+    pub devices: usize,
+    pub rssi_samples: usize,
+    pub names: usize,
+    pub manufacturer_data: usize,
+    pub service_data: usize,
+    pub gatt_tables: usize,
+}
+
+fn now_ms() -> i64 {
+    model::now_ms() as i64
+}
+
+fn db_err(e: rusqlite::Error) -> String {
+    format!("历史数据库错误: {}", e)
+}
+
+fn fingerprint(device: &BleDevice) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    device.name.hash(&mut hasher);
+    let mut data: Vec<_> = device.manufacturer_data.iter().collect();
+    data.sort();
+    data.hash(&mut hasher);
+    let mut service_data: Vec<_> = device.service_data.iter().collect();
+    service_data.sort();
+    service_data.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl HistoryDb {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("无法创建历史数据库目录 {}: {}", dir.display(), e))?;
+        }
+        let conn = Connection::open(path).map_err(|e| format!("无法打开历史数据库 {}: {}", path.display(), e))?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(db_err)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        // WAL 模式下扫描写入不会阻塞查询
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(db_err)?;
+        conn.pragma_update(None, "synchronous", "NORMAL").map_err(db_err)?;
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |r| r.get(0)).map_err(db_err)?;
+        if version > SCHEMA_VERSION {
+            return Err(format!("历史数据库版本 {} 高于当前支持的版本 {}", version, SCHEMA_VERSION));
+        }
+        conn.execute_batch(SCHEMA).map_err(db_err)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(db_err)?;
+        Ok(Self { conn, recent: HashMap::new() })
+    }
+
+    // 记录一次广播；同一设备在 SAMPLE_INTERVAL 内且名称/厂商数据/服务数据未变化时跳过
+    pub fn record_sighting(&mut self, device: &BleDevice) -> Result<(), String> {
+        // 历史库与注册表同键：以地址为主键，取不到地址的平台退回 identifier
+        let key = match registry::device_key(device) {
+            Some(k) => k,
+            None => return Ok(()),
+        };
+        let print = fingerprint(device);
+        if let Some((at, last)) = self.recent.get(&key) {
+            if *last == print && at.elapsed() < SAMPLE_INTERVAL {
+                return Ok(());
+            }
+        }
+        self.recent.insert(key.clone(), (Instant::now(), print));
+
+        let ts = now_ms();
+        let tx = self.conn.transaction().map_err(db_err)?;
+        tx.execute(
+            "INSERT INTO devices (address, identifier, first_seen, last_seen, sightings, last_rssi, connectable)
+             VALUES (?1, ?2, ?3, ?3, 1, ?4, ?5)
+             ON CONFLICT(address) DO UPDATE SET
+                identifier = excluded.identifier,
+                last_seen = excluded.last_seen,
+                sightings = sightings + 1,
+                last_rssi = COALESCE(excluded.last_rssi, last_rssi),
+                connectable = excluded.connectable",
+            params![key, device.identifier, ts, device.rssi, device.connectable],
+        )
+        .map_err(db_err)?;
+        if let Some(rssi) = device.rssi {
+            tx.execute(
+                "INSERT INTO rssi_samples (address, ts, rssi, adapter_id) VALUES (?1, ?2, ?3, ?4)",
+                params![key, ts, rssi, device.adapter_id],
+            )
+            .map_err(db_err)?;
+        }
+        if let Some(name) = device.name.as_deref().filter(|n| !n.is_empty()) {
+            tx.execute(
+                "INSERT INTO names (address, name, first_seen, last_seen) VALUES (?1, ?2, ?3, ?3)
+                 ON CONFLICT(address, name) DO UPDATE SET last_seen = excluded.last_seen",
+                params![key, name, ts],
+            )
+            .map_err(db_err)?;
+        }
+        for (company, data) in &device.manufacturer_data {
+            let company_id = match u16::from_str_radix(company, 16) {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            tx.execute(
+                "INSERT INTO manufacturer_data (address, company_id, data, first_seen, last_seen) VALUES (?1, ?2, ?3, ?4, ?4)
+                 ON CONFLICT(address, company_id, data) DO UPDATE SET last_seen = excluded.last_seen",
+                params![key, company_id, data, ts],
+            )
+            .map_err(db_err)?;
+        }
+        for (uuid, data) in &device.service_data {
+            tx.execute(
+                "INSERT INTO service_data (address, uuid, data, first_seen, last_seen) VALUES (?1, ?2, ?3, ?4, ?4)
+                 ON CONFLICT(address, uuid, data) DO UPDATE SET last_seen = excluded.last_seen",
+                params![key, uuid, data, ts],
+            )
+            .map_err(db_err)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    // 记录 GATT 表；与该设备最近一次记录相同时不重复保存
+    pub fn record_gatt(&mut self, address: &str, services: &[GattService]) -> Result<(), String> {
+        let json = serde_json::to_string(services).map_err(|e| e.to_string())?;
+        let latest: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT services FROM gatt_tables WHERE address = ?1 ORDER BY ts DESC LIMIT 1",
+                params![address],
+                |r| r.get(0),
+            )
+            .optional()
+            .map_err(db_err)?;
+        if latest.as_deref() == Some(json.as_str()) {
+            return Ok(());
+        }
+        self.conn
+            .execute("INSERT INTO gatt_tables (address, ts, services) VALUES (?1, ?2, ?3)", params![address, now_ms(), json])
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryDevice>, String> {
+        let mut sql = String::from(
+            "SELECT d.address, d.identifier, d.first_seen, d.last_seen, d.sightings, d.last_rssi, d.connectable FROM devices d WHERE 1 = 1",
+        );
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(address) = query.address.as_deref().filter(|a| !a.is_empty()) {
+            // 地址前缀匹配，转义 LIKE 通配符
+            let escaped = address.to_uppercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            values.push(format!("{}%", escaped).into());
+            sql.push_str(&format!(" AND d.address LIKE ?{} ESCAPE '\\'", values.len()));
+        }
+        if let Some(company_id) = query.company_id {
+            values.push(i64::from(company_id).into());
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM manufacturer_data m WHERE m.address = d.address AND m.company_id = ?{})",
+                values.len()
+            ));
+        }
+        if let Some(name) = query.name.as_deref().filter(|n| !n.is_empty()) {
+            values.push(format!("%{}%", name.to_lowercase()).into());
+            sql.push_str(&format!(
+                " AND (lower(d.identifier) LIKE ?{0} OR EXISTS (SELECT 1 FROM names n WHERE n.address = d.address AND lower(n.name) LIKE ?{0}))",
+                values.len()
+            ));
+        }
+        if let Some(since) = query.since {
+            values.push(since.into());
+            sql.push_str(&format!(" AND d.last_seen >= ?{}", values.len()));
+        }
+        sql.push_str(" ORDER BY d.last_seen DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut stmt = self.conn.prepare(&sql).map_err(db_err)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(values), |r| {
+                Ok(HistoryDevice {
+                    address: r.get(0)?,
+                    identifier: r.get(1)?,
+                    first_seen: r.get(2)?,
+                    last_seen: r.get(3)?,
+                    sightings: r.get(4)?,
+                    last_rssi: r.get(5)?,
+                    connectable: r.get(6)?,
+                    names: Vec::new(),
+                    manufacturer_data: Vec::new(),
+                    service_data: Vec::new(),
+                })
+            })
+            .map_err(db_err)?;
+        let mut devices = rows.collect::<Result<Vec<_>, _>>().map_err(db_err)?;
+        for device in &mut devices {
+            self.fill_revisions(device)?;
+        }
+        Ok(devices)
+    }
+
+    fn fill_revisions(&self, device: &mut HistoryDevice) -> Result<(), String> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT name, first_seen, last_seen FROM names WHERE address = ?1 ORDER BY first_seen")
+            .map_err(db_err)?;
+        device.names = stmt
+            .query_map(params![device.address], |r| Ok(NameRecord { name: r.get(0)?, first_seen: r.get(1)?, last_seen: r.get(2)? }))
+            .map_err(db_err)?
+            .collect::<Result<_, _>>()
+            .map_err(db_err)?;
+
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT company_id, data, first_seen, last_seen FROM manufacturer_data WHERE address = ?1 ORDER BY company_id, first_seen",
+            )
+            .map_err(db_err)?;
+        device.manufacturer_data = stmt
+            .query_map(params![device.address], |r| {
+                Ok(ManufacturerRevision { company_id: r.get(0)?, data: r.get(1)?, first_seen: r.get(2)?, last_seen: r.get(3)? })
+            })
+            .map_err(db_err)?
+            .collect::<Result<_, _>>()
+            .map_err(db_err)?;
+
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT uuid, data, first_seen, last_seen FROM service_data WHERE address = ?1 ORDER BY uuid, first_seen")
+            .map_err(db_err)?;
+        device.service_data = stmt
+            .query_map(params![device.address], |r| {
+                Ok(ServiceDataRevision { uuid: r.get(0)?, data: r.get(1)?, first_seen: r.get(2)?, last_seen: r.get(3)? })
+            })
+            .map_err(db_err)?
+            .collect::<Result<_, _>>()
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    // 单个设备的完整历史：RSSI 采样（可按时间与条数截取，返回最近的 limit 条）与所有 GATT 表修订
+    pub fn device(&self, address: &str, since: Option<i64>, limit: Option<u32>) -> Result<DeviceHistory, String> {
+        let key = address.to_uppercase();
+        let mut device = self
+            .conn
+            .query_row(
+                "SELECT address, identifier, first_seen, last_seen, sightings, last_rssi, connectable FROM devices
+                 WHERE address = ?1 OR address = ?2 OR identifier = ?3 ORDER BY last_seen DESC LIMIT 1",
+                params![key, format!("id:{}", address), address],
+                |r| {
+                    Ok(HistoryDevice {
+                        address: r.get(0)?,
+                        identifier: r.get(1)?,
+                        first_seen: r.get(2)?,
+                        last_seen: r.get(3)?,
+                        sightings: r.get(4)?,
+                        last_rssi: r.get(5)?,
+                        connectable: r.get(6)?,
+                        names: Vec::new(),
+                        manufacturer_data: Vec::new(),
+                        service_data: Vec::new(),
+                    })
+                },
+            )
+            .optional()
+            .map_err(db_err)?
+            .ok_or_else(|| format!("历史记录中没有设备: {}", address))?;
+        self.fill_revisions(&mut device)?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT ts, rssi, adapter_id FROM (
+                    SELECT ts, rssi, adapter_id FROM rssi_samples WHERE address = ?1 AND ts >= ?2 ORDER BY ts DESC LIMIT ?3
+                 ) ORDER BY ts",
+            )
+            .map_err(db_err)?;
+        let limit = limit.map(i64::from).unwrap_or(-1);
+        let rssi = stmt
+            .query_map(params![device.address, since.unwrap_or(0), limit], |r| {
+                Ok(RssiSample { timestamp: r.get(0)?, rssi: r.get(1)?, adapter_id: r.get(2)? })
+            })
+            .map_err(db_err)?
+            .collect::<Result<_, _>>()
+            .map_err(db_err)?;
+
+        let mut stmt = self.conn.prepare("SELECT ts, services FROM gatt_tables WHERE address = ?1 ORDER BY ts").map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![device.address], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))
+            .map_err(db_err)?;
+        let mut gatt = Vec::new();
+        for row in rows {
+            let (timestamp, json) = row.map_err(db_err)?;
+            match serde_json::from_str(&json) {
+                Ok(services) => gatt.push(GattRevision { timestamp, services }),
+                Err(e) => eprintln!("忽略无法解析的 GATT 记录({}): {}", device.address, e),
+            }
+        }
+
+        Ok(DeviceHistory { device, rssi, gatt })
+    }
+
+    // 删除早于 cutoff（Unix 毫秒）的记录；最近出现时间早于 cutoff 的设备整体删除
+    pub fn prune(&mut self, cutoff: i64) -> Result<PruneSummary, String> {
+        let tx = self.conn.transaction().map_err(db_err)?;
+        let stale = "SELECT address FROM devices WHERE last_seen < ?1";
+        let summary = PruneSummary {
+            rssi_samples: tx
+                .execute(&format!("DELETE FROM rssi_samples WHERE ts < ?1 OR address IN ({})", stale), params![cutoff])
+                .map_err(db_err)?,
+            names: tx
+                .execute(&format!("DELETE FROM names WHERE last_seen < ?1 OR address IN ({})", stale), params![cutoff])
+                .map_err(db_err)?,
+            manufacturer_data: tx
+                .execute(&format!("DELETE FROM manufacturer_data WHERE last_seen < ?1 OR address IN ({})", stale), params![cutoff])
+                .map_err(db_err)?,
+            service_data: tx
+                .execute(&format!("DELETE FROM service_data WHERE last_seen < ?1 OR address IN ({})", stale), params![cutoff])
+                .map_err(db_err)?,
+            gatt_tables: tx
+                .execute(&format!("DELETE FROM gatt_tables WHERE ts < ?1 OR address IN ({})", stale), params![cutoff])
+                .map_err(db_err)?,
+            devices: tx.execute("DELETE FROM devices WHERE last_seen < ?1", params![cutoff]).map_err(db_err)?,
+        };
+        tx.commit().map_err(db_err)?;
+        self.recent.clear();
+        Ok(summary)
+    }
+}
+
+// 默认数据库路径：环境变量 BLESCANNER_HISTORY 优先
+pub fn path_from_env() -> Option<PathBuf> {
+    std::env::var_os("BLESCANNER_HISTORY").filter(|p| !p.is_empty()).map(PathBuf::from)
+}
+
+// 打开历史库并挂到应用状态上；未打开时扫描照常进行，只是不做持久化
+pub fn open(state: &AppState, path: &Path) -> Result<(), String> {
+    let db = HistoryDb::open(path)?;
+    eprintln!("设备历史数据库: {}", path.display());
+    *state.history.lock().unwrap() = Some(db);
+    Ok(())
+}
+
+pub(crate) fn record_sighting(state: &AppState, device: &BleDevice) {
+    if let Some(db) = state.history.lock().unwrap().as_mut() {
+        if let Err(e) = db.record_sighting(device) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+pub(crate) fn record_gatt(state: &AppState, device_id: &str, services: &[GattService]) {
+    let key = registry::key_for(state, device_id).unwrap_or_else(|| device_id.to_uppercase());
+    let mut history = state.history.lock().unwrap();
+    let db = match history.as_mut() {
+        Some(db) => db,
+        None => return,
+    };
+    if let Err(e) = db.record_gatt(&key, services) {
+        eprintln!("{}", e);
+    }
+}
+
+fn with_db<T>(state: &AppState, f: impl FnOnce(&mut HistoryDb) -> Result<T, String>) -> Result<T, String> {
+    match state.history.lock().unwrap().as_mut() {
+        Some(db) => f(db),
+        None => Err("设备历史数据库未启用".to_string()),
+    }
+}
+
+// 按地址前缀、厂商 ID、名称或时间查询历史设备
+pub async fn query_history(state: &AppState, query: HistoryQuery) -> Result<Vec<HistoryDevice>, String> {
+    with_db(state, |db| db.query(&query))
+}
+
+// 查询单个设备的完整历史（address 也可以是 identifier）
+pub async fn get_device_history(
+    state: &AppState,
+    address: String,
+    since: Option<i64>,
+    limit: Option<u32>,
+) -> Result<DeviceHistory, String> {
+    with_db(state, |db| db.device(&address, since, limit))
+}
+
+// 删除超过 max_age_secs 未更新的历史记录
+pub async fn prune_history(state: &AppState, max_age_secs: u64) -> Result<PruneSummary, String> {
+    // 超出 i64 范围的保留时长视为永久保留
+    let max_age_ms = i64::try_from(max_age_secs).unwrap_or(i64::MAX).saturating_mul(1000);
+    let cutoff = now_ms().saturating_sub(max_age_ms);
+    with_db(state, |db| db.prune(cutoff))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sighting(identifier: &str, address: &str, name: Option<&str>, company: Option<(&str, &str)>) -> BleDevice {
+        BleDevice {
+            identifier: identifier.into(),
+            address: address.into(),
+            name: name.map(str::to_string),
+            rssi: Some(-60),
+            adapter_id: "hci0".into(),
+            manufacturer_data: company.map(|(id, data)| HashMap::from([(id.to_string(), data.to_string())])).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    fn populated() -> HistoryDb {
+        let mut db = HistoryDb::open_in_memory().unwrap();
+        db.record_sighting(&sighting("Tag", "aa:bb:cc:dd:ee:01", Some("Tag"), Some(("004C", "0215")))).unwrap();
+        db.record_sighting(&sighting("Band", "AA:BB:CC:DD:FF:02", Some("Mi Band"), Some(("0157", "01")))).unwrap();
+        db.record_sighting(&sighting("NoAddr", "Unknown", None, None)).unwrap();
+        db
+    }
+
+    #[test]
+    fn sightings_are_throttled_unless_advertisement_changes() {
+        let mut db = populated();
+        db.record_sighting(&sighting("Tag", "AA:BB:CC:DD:EE:01", Some("Tag"), Some(("004C", "0215")))).unwrap();
+        let tag = db.device("AA:BB:CC:DD:EE:01", None, None).unwrap();
+        assert_eq!(tag.device.sightings, 1);
+        assert_eq!(tag.rssi.len(), 1);
+
+        // 厂商数据变化时立即记录新修订
+        db.record_sighting(&sighting("Tag", "AA:BB:CC:DD:EE:01", Some("Tag"), Some(("004C", "0216")))).unwrap();
+        let tag = db.device("AA:BB:CC:DD:EE:01", None, None).unwrap();
+        assert_eq!(tag.device.sightings, 2);
+        assert_eq!(tag.device.manufacturer_data.len(), 2);
+        assert_eq!(tag.rssi[0].adapter_id, "hci0");
+    }
+
+    #[test]
+    fn service_data_revisions_are_stored_per_sighting() {
+        let mut db = HistoryDb::open_in_memory().unwrap();
+        let uuid = "0000FEAA-0000-1000-8000-00805F9B34FB";
+        let mut tag = sighting("Tag", "AA:BB:CC:DD:EE:01", Some("Tag"), None);
+        tag.service_data = HashMap::from([(uuid.to_string(), "10EE03".to_string())]);
+        db.record_sighting(&tag).unwrap();
+        // 只有服务数据变化时同样不受写入频率限制
+        tag.service_data.insert(uuid.to_string(), "20000BB8".to_string());
+        db.record_sighting(&tag).unwrap();
+        db.record_sighting(&tag).unwrap();
+
+        let history = db.device("Tag", None, None).unwrap();
+        assert_eq!(history.device.sightings, 2);
+        let revisions: Vec<(&str, &str)> =
+            history.device.service_data.iter().map(|r| (r.uuid.as_str(), r.data.as_str())).collect();
+        assert_eq!(revisions, vec![(uuid, "10EE03"), (uuid, "20000BB8")]);
+        assert!(history.device.service_data.iter().all(|r| r.first_seen <= r.last_seen));
+        assert_eq!(db.query(&HistoryQuery::default()).unwrap()[0].service_data.len(), 2);
+
+        db.conn.execute("UPDATE service_data SET last_seen = 0 WHERE data = '10EE03'", []).unwrap();
+        assert_eq!(db.prune(1000).unwrap().service_data, 1);
+    }
+
+    #[test]
+    fn query_by_address_company_and_name() {
+        let db = populated();
+        let addresses = |q: HistoryQuery| db.query(&q).unwrap().into_iter().map(|d| d.address).collect::<Vec<_>>();
+
+        assert_eq!(addresses(HistoryQuery { address: Some("aa:bb:cc:dd:e".into()), ..Default::default() }), vec!["AA:BB:CC:DD:EE:01"]);
+        assert_eq!(addresses(HistoryQuery { company_id: Some(0x0157), ..Default::default() }), vec!["AA:BB:CC:DD:FF:02"]);
+        assert_eq!(addresses(HistoryQuery { name: Some("BAND".into()), ..Default::default() }), vec!["AA:BB:CC:DD:FF:02"]);
+        // LIKE 通配符按字面匹配
+        assert!(addresses(HistoryQuery { address: Some("%".into()), ..Default::default() }).is_empty());
+        assert_eq!(addresses(HistoryQuery { limit: Some(2), ..Default::default() }).len(), 2);
+    }
+
+    #[test]
+    fn device_lookup_by_address_identifier_and_fallback_key() {
+        let mut db = populated();
+        assert_eq!(db.device("aa:bb:cc:dd:ee:01", None, None).unwrap().device.names[0].name, "Tag");
+        assert_eq!(db.device("Band", None, None).unwrap().device.address, "AA:BB:CC:DD:FF:02");
+        assert_eq!(db.device("NoAddr", None, None).unwrap().device.address, "id:NoAddr");
+        assert!(db.device("00:00:00:00:00:00", None, None).is_err());
+
+        db.record_gatt("AA:BB:CC:DD:EE:01", &[]).unwrap();
+        db.record_gatt("AA:BB:CC:DD:EE:01", &[]).unwrap();
+        assert_eq!(db.device("AA:BB:CC:DD:EE:01", None, None).unwrap().gatt.len(), 1);
+    }
+
+    #[test]
+    fn prune_removes_old_records_and_stale_devices() {
+        let mut db = populated();
+        db.conn.execute("UPDATE devices SET last_seen = 0 WHERE address = 'AA:BB:CC:DD:FF:02'", []).unwrap();
+        db.conn.execute("UPDATE names SET last_seen = 0 WHERE address = 'AA:BB:CC:DD:EE:01'", []).unwrap();
+
+        let summary = db.prune(1000).unwrap();
+        assert_eq!(summary.devices, 1);
+        assert_eq!(summary.names, 2); // Tag 的旧名称 + Band 的全部名称
+        assert_eq!(summary.rssi_samples, 1);
+        assert!(db.device("Band", None, None).is_err());
+        let tag = db.device("Tag", None, None).unwrap();
+        assert!(tag.device.names.is_empty());
+        assert_eq!(tag.rssi.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn prune_history_saturates_huge_ages() {
+        let state = AppState::new(std::sync::Arc::new(crate::backend::sim::SimulatedBackend::new()));
+        *state.history.lock().unwrap() = Some(populated());
+        let summary = prune_history(&state, u64::MAX).await.unwrap();
+        assert_eq!(summary.devices, 0);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(prune_history(&state, 0).await.unwrap().devices, 3);
+    }
+}
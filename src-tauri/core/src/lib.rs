@@ -0,0 +1,30 @@
+// BLE Scanner 核心：扫描循环、设备表与 GATT 操作，供 Tauri 应用与 CLI 共用
+
+pub mod adv;
+pub mod aging;
+pub mod assigned_numbers;
+pub mod backend;
+pub mod beacon;
+pub mod coalesce;
+pub mod connection;
+pub mod decoder;
+pub mod device;
+pub mod events;
+pub mod filter;
+pub mod gatt;
+pub mod gatt_value;
+pub mod history;
+pub mod long_write;
+pub mod model;
+pub mod notifications;
+pub mod payload;
+pub mod registry;
+pub mod rssi;
+pub mod scan;
+pub mod snapshot;
+pub mod state;
+pub mod subscriptions;
+
+pub use events::EventSink;
+pub use model::{BleAdapter, BleDevice, GattCharacteristic, GattService};
+pub use state::AppState;
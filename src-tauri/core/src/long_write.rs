@@ -0,0 +1,288 @@
+// 长值写入：按协商的 MTU 分块写入，或通过 Prepare/Execute Write 原子写入
+//
+// chunked  逐块写请求/写命令，每块 MTU - 3 字节；写命令之间按间隔节流，避免外设缓冲区溢出
+// prepared 逐块 Prepare Write（每块 MTU - 5 字节）后 Execute Write，总长不超过 512 字节
+// reliable 同 prepared，并逐块比对外设回显，要求特征声明了 Reliable Write 扩展属性
+// 每块写完推送 write-progress 事件；verify 时在最后一块写完后回读比对：
+// prepared / reliable 要求回读值等于完整数据，chunked 时外设可能只保留最后一块，与完整数据或最后一块一致均视为通过
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::adv;
+use crate::assigned_numbers;
+use crate::backend::BlePeripheral;
+use crate::events::EventSink;
+use crate::payload::{self, PayloadEncoding};
+use crate::registry;
+use crate::state::AppState;
+
+// ATT 属性值最大长度，也是 Prepared Write 的上限
+pub const MAX_ATTRIBUTE_LEN: usize = 512;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LongWriteMode {
+    #[default]
+    Chunked,
+    Prepared,
+    Reliable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LongWriteOptions {
+    pub mode: LongWriteMode,
+    // 仅 chunked：使用写命令（无响应）
+    #[serde(alias = "withoutResponse")]
+    pub without_response: bool,
+    // 写命令之间的间隔，写请求本身有响应，不额外等待
+    #[serde(alias = "chunkIntervalMs")]
+    pub chunk_interval_ms: u64,
+    pub verify: bool,
+    // 数据编码，None 时按 UTF-8（见 payload 模块）
+    pub encoding: Option<PayloadEncoding>,
+}
+
+impl Default for LongWriteOptions {
+    fn default() -> Self {
+        Self { mode: LongWriteMode::Chunked, without_response: false, chunk_interval_ms: 20, verify: false, encoding: None }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongWriteResult {
+    pub mode: LongWriteMode,
+    pub total_bytes: usize,
+    pub chunk_size: usize,
+    pub chunks: usize,
+    pub elapsed_ms: u64,
+    // 未要求校验时为 None
+    pub verified: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteProgress<'a> {
+    device_id: &'a str,
+    service_uuid: &'a str,
+    characteristic_uuid: &'a str,
+    chunk: usize,
+    chunks: usize,
+    bytes_written: usize,
+    total_bytes: usize,
+}
+
+// 从 0x2900 描述符判断特征是否声明了 Reliable Write
+fn supports_reliable_write(peripheral: &dyn BlePeripheral, service_uuid: &str, characteristic_uuid: &str) -> bool {
+    let (service, characteristic) = (adv::normalize_uuid(service_uuid), adv::normalize_uuid(characteristic_uuid));
+    let descriptor = peripheral
+        .services()
+        .ok()
+        .into_iter()
+        .flatten()
+        .filter(|s| adv::normalize_uuid(&s.uuid) == service)
+        .flat_map(|s| s.characteristics)
+        .find(|c| adv::normalize_uuid(&c.uuid) == characteristic)
+        .and_then(|c| c.descriptors.into_iter().find(|d| assigned_numbers::short_uuid(d) == Some(0x2900)));
+    match descriptor.and_then(|d| peripheral.read_descriptor(service_uuid, characteristic_uuid, &d).ok()) {
+        Some(v) if !v.is_empty() => v[0] & 0x01 != 0,
+        _ => false,
+    }
+}
+
+// 分块写入长值（数据编码同 write_characteristic）
+pub async fn write_long(
+    state: &AppState,
+    sink: Arc<dyn EventSink>,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+    data: String,
+    options: LongWriteOptions,
+) -> Result<LongWriteResult, String> {
+    let peripheral = registry::resolve(state, &device_id)?;
+    if matches!(peripheral.is_connected(), Ok(false)) {
+        let _ = peripheral.connect();
+    }
+
+    let bytes = payload::parse_data(&data, options.encoding.as_ref())?;
+    if bytes.is_empty() {
+        return Err("写入数据为空".to_string());
+    }
+    let mtu = peripheral.mtu().map_err(|e| format!("获取 MTU 失败: {}", e))? as usize;
+    let prepared = options.mode != LongWriteMode::Chunked;
+    // ATT 头：写请求/命令 3 字节，Prepare Write 另含 2 字节偏移
+    let chunk_size = mtu.saturating_sub(if prepared { 5 } else { 3 }).max(1);
+    let chunks = (bytes.len() + chunk_size - 1) / chunk_size;
+
+    if prepared {
+        if options.without_response {
+            return Err("Prepared Write 只能使用写请求".to_string());
+        }
+        if bytes.len() > MAX_ATTRIBUTE_LEN {
+            return Err(format!("Prepared Write 总长度 {} 字节超过 {} 字节上限，请使用分块写入", bytes.len(), MAX_ATTRIBUTE_LEN));
+        }
+        if options.mode == LongWriteMode::Reliable && !supports_reliable_write(peripheral.as_ref(), &service_uuid, &characteristic_uuid) {
+            return Err(format!("特征未声明 Reliable Write: {}", characteristic_uuid));
+        }
+    }
+
+    let started = Instant::now();
+    let mut written = 0;
+    for (i, chunk) in bytes.chunks(chunk_size).enumerate() {
+        let result = if prepared {
+            peripheral.prepare_write(&service_uuid, &characteristic_uuid, written as u16, chunk).and_then(|echo| {
+                if options.mode == LongWriteMode::Reliable && echo != chunk {
+                    Err(format!("第 {} 块回显与写入数据不一致", i + 1))
+                } else {
+                    Ok(())
+                }
+            })
+        } else if options.without_response {
+            peripheral.write_command(&service_uuid, &characteristic_uuid, chunk)
+        } else {
+            peripheral.write_request(&service_uuid, &characteristic_uuid, chunk)
+        };
+        if let Err(e) = result {
+            if prepared {
+                let _ = peripheral.execute_write(false);
+            }
+            return Err(format!("写入第 {}/{} 块失败: {}", i + 1, chunks, e));
+        }
+        written += chunk.len();
+        let _ = sink.emit(
+            "write-progress",
+            WriteProgress {
+                device_id: &device_id,
+                service_uuid: &service_uuid,
+                characteristic_uuid: &characteristic_uuid,
+                chunk: i + 1,
+                chunks,
+                bytes_written: written,
+                total_bytes: bytes.len(),
+            },
+        );
+        if options.without_response && i + 1 < chunks && options.chunk_interval_ms > 0 {
+            sleep(Duration::from_millis(options.chunk_interval_ms)).await;
+        }
+    }
+    if prepared {
+        peripheral.execute_write(true).map_err(|e| format!("Execute Write 失败: {}", e))?;
+    }
+
+    let verified = if options.verify {
+        let value = peripheral
+            .read(&service_uuid, &characteristic_uuid)
+            .map_err(|e| format!("回读校验失败: {}", e))?;
+        let last = bytes.chunks(chunk_size).last().unwrap_or_default();
+        Some(value == bytes || (!prepared && value == last))
+    } else {
+        None
+    };
+    Ok(LongWriteResult {
+        mode: options.mode,
+        total_bytes: bytes.len(),
+        chunk_size,
+        chunks,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        verified,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::{SimCharacteristic, SimDevice, SimPeripheral, SimService, SimulatedBackend};
+    use crate::events::RecordingSink;
+
+    fn long_device(mtu: u16) -> (AppState, Arc<RecordingSink>, Arc<SimPeripheral>) {
+        let backend = Arc::new(SimulatedBackend::new());
+        let device = backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(
+            SimDevice::new("LONG", "AA:BB:CC:DD:EE:05").paired(true).mtu(mtu).gatt_service(
+                SimService::new("FFF0").characteristic(
+                    SimCharacteristic::new("FFF1").read().write().write_without_response().reliable_write(),
+                ),
+            ),
+        );
+        (AppState::new(backend), Arc::new(RecordingSink::default()), device)
+    }
+
+    async fn write(state: &AppState, sink: &Arc<RecordingSink>, len: usize, options: LongWriteOptions) -> Result<LongWriteResult, String> {
+        let data = (0..len).map(|i| format!("{:02X}", i as u8)).collect::<String>();
+        let options = LongWriteOptions { encoding: Some(PayloadEncoding::Hex), chunk_interval_ms: 0, ..options };
+        write_long(state, sink.clone(), "LONG".into(), "FFF0".into(), "FFF1".into(), data, options).await
+    }
+
+    fn mode(mode: LongWriteMode) -> LongWriteOptions {
+        LongWriteOptions { mode, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn chunk_size_follows_mtu_and_mode() {
+        let (state, sink, device) = long_device(23);
+        let result = write(&state, &sink, 50, mode(LongWriteMode::Chunked)).await.unwrap();
+        assert_eq!((result.chunk_size, result.chunks), (20, 3));
+        let sizes: Vec<usize> = device.written_values().iter().map(|(_, _, v)| v.len()).collect();
+        assert_eq!(sizes, vec![20, 20, 10]);
+        let progress = sink.take("write-progress");
+        assert_eq!(progress.len(), 3);
+        assert_eq!(progress[2]["bytesWritten"], 50);
+
+        let result = write(&state, &sink, 50, mode(LongWriteMode::Prepared)).await.unwrap();
+        assert_eq!((result.chunk_size, result.chunks), (18, 3));
+        assert_eq!(device.written_values().last().unwrap().2.len(), 50);
+
+        let (state, sink, _) = long_device(185);
+        let result = write(&state, &sink, 400, LongWriteOptions { without_response: true, ..Default::default() }).await.unwrap();
+        assert_eq!((result.chunk_size, result.chunks), (182, 3));
+    }
+
+    #[tokio::test]
+    async fn prepared_writes_are_limited_to_512_bytes() {
+        let (state, sink, device) = long_device(247);
+        let result = write(&state, &sink, MAX_ATTRIBUTE_LEN, LongWriteOptions { verify: true, ..mode(LongWriteMode::Prepared) }).await.unwrap();
+        assert_eq!(result.verified, Some(true));
+        assert!(write(&state, &sink, MAX_ATTRIBUTE_LEN + 1, mode(LongWriteMode::Prepared)).await.is_err());
+        assert_eq!(device.written_values().len(), 1);
+        // 分块写入不受限制，回读校验在最后一块之后进行
+        assert!(write(&state, &sink, 600, mode(LongWriteMode::Chunked)).await.is_ok());
+        let result = write(&state, &sink, 600, LongWriteOptions { verify: true, ..Default::default() }).await.unwrap();
+        assert_eq!((result.chunks, result.verified), (3, Some(true)));
+        assert!(write(&state, &sink, 10, LongWriteOptions { without_response: true, ..mode(LongWriteMode::Prepared) }).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn reliable_write_aborts_on_echo_mismatch() {
+        let (state, sink, device) = long_device(23);
+        device.corrupt_prepare_echo(true);
+        let err = write(&state, &sink, 40, mode(LongWriteMode::Reliable)).await.unwrap_err();
+        assert!(err.contains("1/3"), "{}", err);
+        // execute_write(false) 丢弃了已排队的块：没有写入，下一次写入可以从偏移 0 开始
+        assert!(device.written_values().is_empty());
+        device.corrupt_prepare_echo(false);
+        let result = write(&state, &sink, 40, LongWriteOptions { verify: true, ..mode(LongWriteMode::Reliable) }).await.unwrap();
+        assert_eq!(result.verified, Some(true));
+
+        // prepared 模式不比对回显
+        device.corrupt_prepare_echo(true);
+        assert!(write(&state, &sink, 40, mode(LongWriteMode::Prepared)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reliable_write_requires_extended_property() {
+        let backend = Arc::new(SimulatedBackend::new());
+        backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(
+            SimDevice::new("LONG", "AA:BB:CC:DD:EE:05")
+                .paired(true)
+                .gatt_service(SimService::new("FFF0").characteristic(SimCharacteristic::new("FFF1").read().write())),
+        );
+        let state = AppState::new(backend);
+        let sink = Arc::new(RecordingSink::default());
+        assert!(write(&state, &sink, 10, mode(LongWriteMode::Reliable)).await.unwrap_err().contains("Reliable Write"));
+        assert!(write(&state, &sink, 10, mode(LongWriteMode::Prepared)).await.is_ok());
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::adv;
+use crate::backend::SecurityRequirements;
+use crate::beacon::Beacon;
+use crate::decoder::DecodedData;
+use crate::gatt_value::PresentationFormat;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BleDevice {
+    pub identifier: String,
+    pub name: Option<String>,
+    pub address: String,
+    pub rssi: Option<i16>, // 主适配器（RSSI 序列所用）上报的值
+    #[serde(default)]
+    pub rssi_by_adapter: HashMap<String, i16>, // 各适配器最近一次上报的 RSSI
+    pub tx_power: Option<i16>,
+    pub connectable: bool,
+    #[serde(default)]
+    pub connected: bool, // 当前是否已建立连接
+    pub paired: bool, // 协议栈报告的配对状态
+    #[serde(default)]
+    pub bonded: bool, // 系统中是否保存了绑定信息
+    pub manufacturer_data: HashMap<String, String>,
+    #[serde(default)]
+    pub manufacturer_names: HashMap<String, String>, // 公司 ID -> SIG 登记的公司名称
+    pub services: Vec<String>,
+    #[serde(default)]
+    pub service_names: HashMap<String, String>, // 服务 UUID -> SIG 服务名称（仅已知的 16 位 UUID）
+    #[serde(default)]
+    pub service_data: HashMap<String, String>, // 服务 UUID（128 位大写）-> 十六进制数据（AD 0x16/0x20/0x21）
+    pub adv_data: Option<HashMap<String, String>>, // 广播数据
+    pub raw_adv_data: Option<String>, // 由观测字段重建的广播数据十六进制字符串
+    #[serde(default)]
+    pub adv_fields: Vec<adv::AdvField>, // 各广播字段的来源（observed / unavailable）
+    #[serde(default)]
+    pub adapter_id: String, // 最近一次上报该设备的适配器
+    #[serde(default)]
+    pub seen_by: Vec<String>, // 本次会话中扫描到该设备的所有适配器
+    #[serde(default)]
+    pub last_seen: u64, // 最近一次收到广播的时间（Unix 毫秒）
+    #[serde(default)]
+    pub stale: bool, // 超过 stale 超时未再出现
+    #[serde(default)]
+    pub beacon: Option<Beacon>, // 识别出的信标（iBeacon / AltBeacon / Eddystone）
+    #[serde(default)]
+    pub decoded: Vec<DecodedData>, // 厂商数据解码结果
+}
+
+// 当前 Unix 时间（毫秒）
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GattCharacteristic {
+    pub uuid: String,
+    #[serde(default)]
+    pub name: Option<String>, // SIG 特征名称
+    pub properties: Vec<String>,
+    // 特征声明中的属性位：0x01 广播、0x02 读、0x04 写命令、0x08 写请求、0x10 通知、0x20 指示、0x40 签名写、0x80 扩展属性
+    #[serde(default)]
+    pub property_bits: u8,
+    // 扩展属性描述符 (0x2900) 的值：bit0 可靠写、bit1 可写辅助描述符
+    #[serde(default)]
+    pub extended_properties: Option<u16>,
+    // 后端提供时的读写安全要求
+    #[serde(default)]
+    pub security: Option<SecurityRequirements>,
+    #[serde(default)]
+    pub descriptors: Vec<GattDescriptor>,
+    #[serde(default)]
+    pub user_description: Option<String>, // 0x2901
+    #[serde(default)]
+    pub presentation_format: Option<PresentationFormat>, // 0x2904
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GattDescriptor {
+    pub uuid: String,
+    #[serde(default)]
+    pub name: Option<String>, // SIG 描述符名称
+    // 枚举时读取的静态元数据描述符（用户描述、呈现格式、有效范围等）的值，十六进制；CCCD 等不预读
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GattService {
+    pub uuid: String,
+    #[serde(default)]
+    pub name: Option<String>, // SIG 服务名称
+    pub characteristics: Vec<GattCharacteristic>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BleAdapter {
+    pub identifier: String,
+    pub address: String,
+    pub powered: bool,
+    #[serde(default)]
+    pub selected: bool, // 是否为默认使用的适配器
+}
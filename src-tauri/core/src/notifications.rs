@@ -0,0 +1,468 @@
+// 通知记录：每个订阅一个有界环形缓冲区，可选录制到文件（CSV / JSONL / 二进制），
+// 并可按原速或倍速回放录制的会话，便于在没有硬件时调试前端与解码
+//
+// 二进制格式：文件头 b"BLEREC\x01\n"，之后每条记录依次为
+//   u64 LE 时间戳（Unix 毫秒）、u8 类型（0 通知 / 1 指示）、
+//   设备ID / 服务UUID / 特征UUID 各为 u8 长度 + UTF-8、u16 LE 数据长度 + 数据
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::adv;
+use crate::events::EventSink;
+use crate::gatt_value::{CharacteristicValue, DecodedValue};
+use crate::model::now_ms;
+use crate::state::AppState;
+
+pub const DEFAULT_CAPACITY: usize = 1000;
+const BINARY_MAGIC: &[u8] = b"BLEREC\x01\n";
+const CSV_HEADER: &str = "timestamp,deviceId,serviceUuid,characteristicUuid,kind,value";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    pub timestamp: u64, // Unix 毫秒
+    pub device_id: String,
+    pub service_uuid: String,
+    pub characteristic_uuid: String,
+    pub kind: String, // notify / indicate
+    pub value: String, // 大写十六进制
+}
+
+impl NotificationRecord {
+    pub fn new(device_id: &str, service_uuid: &str, characteristic_uuid: &str, kind: &str, data: &[u8]) -> Self {
+        Self {
+            timestamp: now_ms(),
+            device_id: device_id.to_string(),
+            service_uuid: service_uuid.to_string(),
+            characteristic_uuid: characteristic_uuid.to_string(),
+            kind: kind.to_string(),
+            value: hex::encode_upper(data),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingFormat {
+    Csv,
+    Jsonl,
+    Binary,
+}
+
+impl RecordingFormat {
+    // 按扩展名选择格式：.csv / .bin，其余为 JSONL
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("csv") => RecordingFormat::Csv,
+            Some("bin") => RecordingFormat::Binary,
+            _ => RecordingFormat::Jsonl,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStatus {
+    pub path: String,
+    pub format: RecordingFormat,
+    pub device_id: Option<String>, // 只录制该设备，None 表示全部订阅
+    pub started_at: u64,
+    pub records: u64,
+}
+
+struct Recorder {
+    status: RecordingStatus,
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    fn write(&mut self, record: &NotificationRecord) -> std::io::Result<()> {
+        match self.status.format {
+            RecordingFormat::Csv => writeln!(
+                self.writer,
+                "{},{},{},{},{},{}",
+                record.timestamp,
+                csv_field(&record.device_id),
+                csv_field(&record.service_uuid),
+                csv_field(&record.characteristic_uuid),
+                record.kind,
+                record.value
+            ),
+            RecordingFormat::Jsonl => writeln!(self.writer, "{}", serde_json::to_string(record).unwrap_or_default()),
+            RecordingFormat::Binary => {
+                let data = hex::decode(&record.value).unwrap_or_default();
+                self.writer.write_all(&record.timestamp.to_le_bytes())?;
+                self.writer.write_all(&[u8::from(record.kind == "indicate")])?;
+                for s in [&record.device_id, &record.service_uuid, &record.characteristic_uuid] {
+                    let bytes = &s.as_bytes()[..s.len().min(u8::MAX as usize)];
+                    self.writer.write_all(&[bytes.len() as u8])?;
+                    self.writer.write_all(bytes)?;
+                }
+                self.writer.write_all(&(data.len() as u16).to_le_bytes())?;
+                self.writer.write_all(&data)
+            }
+        }
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// 拆分一行 CSV，支持双引号转义
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+fn buffer_key(device_id: &str, service_uuid: &str, characteristic_uuid: &str) -> String {
+    format!("{}|{}|{}", device_id, adv::normalize_uuid(service_uuid), adv::normalize_uuid(characteristic_uuid))
+}
+
+pub struct NotificationLog {
+    capacity: usize,
+    buffers: HashMap<String, VecDeque<NotificationRecord>>,
+    recorder: Option<Recorder>,
+    replay: Option<JoinHandle<()>>,
+}
+
+impl Default for NotificationLog {
+    fn default() -> Self {
+        Self { capacity: DEFAULT_CAPACITY, buffers: HashMap::new(), recorder: None, replay: None }
+    }
+}
+
+impl NotificationLog {
+    // 写入环形缓冲区；persist 时同时写入录制文件（回放的数据不再录制）
+    pub fn record(&mut self, record: &NotificationRecord, persist: bool) {
+        let key = buffer_key(&record.device_id, &record.service_uuid, &record.characteristic_uuid);
+        let buffer = self.buffers.entry(key).or_default();
+        if buffer.len() >= self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(record.clone());
+
+        let Some(recorder) = self.recorder.as_mut().filter(|_| persist) else { return };
+        if recorder.status.device_id.as_deref().map(|id| id != record.device_id).unwrap_or(false) {
+            return;
+        }
+        match recorder.write(record) {
+            Ok(()) => recorder.status.records += 1,
+            Err(e) => {
+                eprintln!("写入通知录制文件失败，停止录制: {}: {}", recorder.status.path, e);
+                self.recorder = None;
+            }
+        }
+    }
+
+    // 最近的 limit 条记录（按时间顺序）
+    pub fn fetch(&self, device_id: &str, service_uuid: &str, characteristic_uuid: &str, limit: Option<usize>) -> Vec<NotificationRecord> {
+        let Some(buffer) = self.buffers.get(&buffer_key(device_id, service_uuid, characteristic_uuid)) else { return Vec::new() };
+        let skip = limit.map(|n| buffer.len().saturating_sub(n)).unwrap_or(0);
+        buffer.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        for buffer in self.buffers.values_mut() {
+            while buffer.len() > capacity {
+                buffer.pop_front();
+            }
+        }
+    }
+}
+
+// characteristic-value 事件内容，实时通知与回放共用
+pub fn event_payload(record: &NotificationRecord, decoded: &Option<DecodedValue>, replay: bool) -> serde_json::Value {
+    serde_json::json!({
+        "deviceId": record.device_id,
+        "serviceUuid": record.service_uuid,
+        "characteristicUuid": record.characteristic_uuid,
+        "value": record.value,
+        "decoded": decoded,
+        "kind": record.kind,
+        "timestamp": record.timestamp,
+        "replay": replay
+    })
+}
+
+// 读取录制文件，格式由文件头判断（二进制魔数或 CSV 表头），无表头时按扩展名识别 CSV，其余按 JSONL 解析
+pub fn load_recording(path: &Path) -> Result<Vec<NotificationRecord>, String> {
+    let mut file = File::open(path).map_err(|e| format!("打开录制文件失败: {}: {}", path.display(), e))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| format!("读取录制文件失败: {}", e))?;
+    if let Some(body) = bytes.strip_prefix(BINARY_MAGIC) {
+        return parse_binary(body);
+    }
+    let text = String::from_utf8(bytes).map_err(|_| "录制文件不是有效的 UTF-8 文本".to_string())?;
+    let csv = text.lines().next() == Some(CSV_HEADER) || RecordingFormat::from_path(path) == RecordingFormat::Csv;
+    let mut records = Vec::new();
+    for (i, line) in BufReader::new(text.as_bytes()).lines().map_while(Result::ok).enumerate() {
+        if line.trim().is_empty() || (csv && line == CSV_HEADER) {
+            continue;
+        }
+        let record = if csv {
+            let f = split_csv(&line);
+            if f.len() != 6 {
+                return Err(format!("第 {} 行字段数应为 6，实际 {}", i + 1, f.len()));
+            }
+            NotificationRecord {
+                timestamp: f[0].parse().map_err(|_| format!("第 {} 行时间戳无效: {}", i + 1, f[0]))?,
+                device_id: f[1].clone(),
+                service_uuid: f[2].clone(),
+                characteristic_uuid: f[3].clone(),
+                kind: f[4].clone(),
+                value: f[5].clone(),
+            }
+        } else {
+            serde_json::from_str(&line).map_err(|e| format!("第 {} 行解析失败: {}", i + 1, e))?
+        };
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn parse_binary(mut body: &[u8]) -> Result<Vec<NotificationRecord>, String> {
+    fn take<'a>(body: &mut &'a [u8], n: usize) -> Result<&'a [u8], String> {
+        if body.len() < n {
+            return Err("录制文件被截断".to_string());
+        }
+        let (head, rest) = body.split_at(n);
+        *body = rest;
+        Ok(head)
+    }
+    fn take_str(body: &mut &[u8]) -> Result<String, String> {
+        let len = take(body, 1)?[0] as usize;
+        String::from_utf8(take(body, len)?.to_vec()).map_err(|_| "录制文件包含无效的 UTF-8".to_string())
+    }
+
+    let mut records = Vec::new();
+    while !body.is_empty() {
+        let timestamp = u64::from_le_bytes(take(&mut body, 8)?.try_into().unwrap());
+        let kind = if take(&mut body, 1)?[0] == 1 { "indicate" } else { "notify" };
+        let device_id = take_str(&mut body)?;
+        let service_uuid = take_str(&mut body)?;
+        let characteristic_uuid = take_str(&mut body)?;
+        let len = u16::from_le_bytes(take(&mut body, 2)?.try_into().unwrap()) as usize;
+        let value = hex::encode_upper(take(&mut body, len)?);
+        records.push(NotificationRecord { timestamp, device_id, service_uuid, characteristic_uuid, kind: kind.to_string(), value });
+    }
+    Ok(records)
+}
+
+// 获取订阅的最近通知
+pub async fn get_notifications(
+    state: &AppState,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+    limit: Option<usize>,
+) -> Result<Vec<NotificationRecord>, String> {
+    Ok(state.notifications.lock().unwrap().fetch(&device_id, &service_uuid, &characteristic_uuid, limit))
+}
+
+pub async fn clear_notifications(state: &AppState, device_id: String, service_uuid: String, characteristic_uuid: String) -> Result<(), String> {
+    state.notifications.lock().unwrap().buffers.remove(&buffer_key(&device_id, &service_uuid, &characteristic_uuid));
+    Ok(())
+}
+
+// 每个订阅保留的通知条数
+pub async fn set_notification_buffer_size(state: &AppState, capacity: usize) -> Result<(), String> {
+    if capacity == 0 {
+        return Err("capacity 必须大于 0".to_string());
+    }
+    state.notifications.lock().unwrap().set_capacity(capacity);
+    Ok(())
+}
+
+// 开始录制通知到文件；已在录制时先结束上一段录制
+pub async fn start_recording(
+    state: &AppState,
+    path: String,
+    format: Option<RecordingFormat>,
+    device_id: Option<String>,
+) -> Result<RecordingStatus, String> {
+    let format = format.unwrap_or_else(|| RecordingFormat::from_path(Path::new(&path)));
+    let file = File::create(&path).map_err(|e| format!("创建录制文件失败: {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    let header = match format {
+        RecordingFormat::Csv => writeln!(writer, "{}", CSV_HEADER),
+        RecordingFormat::Binary => writer.write_all(BINARY_MAGIC),
+        RecordingFormat::Jsonl => Ok(()),
+    };
+    header.map_err(|e| format!("写入录制文件失败: {}", e))?;
+    let status = RecordingStatus { path, format, device_id: device_id.filter(|id| !id.is_empty()), started_at: now_ms(), records: 0 };
+    let previous = state.notifications.lock().unwrap().recorder.replace(Recorder { status: status.clone(), writer });
+    if let Some(mut previous) = previous {
+        let _ = previous.writer.flush();
+    }
+    Ok(status)
+}
+
+// 结束录制并返回统计，未在录制时返回 None
+pub async fn stop_recording(state: &AppState) -> Result<Option<RecordingStatus>, String> {
+    let recorder = state.notifications.lock().unwrap().recorder.take();
+    match recorder {
+        Some(mut r) => {
+            r.writer.flush().map_err(|e| format!("写入录制文件失败: {}", e))?;
+            Ok(Some(r.status))
+        }
+        None => Ok(None),
+    }
+}
+
+pub async fn get_recording_status(state: &AppState) -> Result<Option<RecordingStatus>, String> {
+    Ok(state.notifications.lock().unwrap().recorder.as_ref().map(|r| r.status.clone()))
+}
+
+// 回放录制文件：按记录间隔（除以 speed）重新推送 characteristic-value 事件（replay = true），
+// 结束后推送 replay-completed；speed 为 0 时不等待。返回记录条数
+pub async fn replay_recording(state: &AppState, sink: Arc<dyn EventSink>, path: String, speed: f64) -> Result<usize, String> {
+    if !(speed >= 0.0 && speed.is_finite()) {
+        return Err("speed 必须为非负数".to_string());
+    }
+    let records = load_recording(Path::new(&path))?;
+    let count = records.len();
+    let log = state.notifications.clone();
+    let handle = tokio::spawn(async move {
+        let mut previous: Option<u64> = None;
+        for record in records {
+            if let Some(prev) = previous.filter(|_| speed > 0.0) {
+                let gap = record.timestamp.saturating_sub(prev) as f64 / speed;
+                tokio::time::sleep(Duration::from_millis(gap as u64)).await;
+            }
+            previous = Some(record.timestamp);
+            let bytes = hex::decode(&record.value).unwrap_or_default();
+            let value = CharacteristicValue::new(&record.characteristic_uuid, &bytes, None);
+            log.lock().unwrap().record(&record, false);
+            let _ = sink.emit("characteristic-value", event_payload(&record, &value.decoded, true));
+        }
+        let _ = sink.emit("replay-completed", serde_json::json!({ "path": path, "records": count }));
+    });
+    if let Some(previous) = state.notifications.lock().unwrap().replay.replace(handle) {
+        previous.abort();
+    }
+    Ok(count)
+}
+
+// 停止回放，返回是否有正在进行的回放
+pub async fn stop_replay(state: &AppState) -> Result<bool, String> {
+    match state.notifications.lock().unwrap().replay.take() {
+        Some(handle) => {
+            let running = !handle.is_finished();
+            handle.abort();
+            Ok(running)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ts: u64, device_id: &str, data: &[u8]) -> NotificationRecord {
+        NotificationRecord { timestamp: ts, ..NotificationRecord::new(device_id, "180D", "2A37", "notify", data) }
+    }
+
+    #[test]
+    fn ring_buffer_keeps_latest_records() {
+        let mut log = NotificationLog::default();
+        log.set_capacity(3);
+        for i in 0..5u8 {
+            log.record(&record(i as u64, "dev", &[i]), false);
+        }
+        let values: Vec<String> = log.fetch("dev", "0000180D-0000-1000-8000-00805F9B34FB", "2a37", None).into_iter().map(|r| r.value).collect();
+        assert_eq!(values, vec!["02", "03", "04"]);
+        assert_eq!(log.fetch("dev", "180D", "2A37", Some(1))[0].value, "04");
+        assert!(log.fetch("other", "180D", "2A37", None).is_empty());
+    }
+
+    #[test]
+    fn recordings_round_trip_in_every_format() {
+        let records = vec![record(1000, "C0:FF:EE:00:00:01", &[0x00, 72]), record(1250, "id,with\"quote", &[0x16, 0x01, 0x02])];
+        let dir = std::env::temp_dir();
+        for (name, format) in [("rec.csv", RecordingFormat::Csv), ("rec.jsonl", RecordingFormat::Jsonl), ("rec.bin", RecordingFormat::Binary)] {
+            let path = dir.join(format!("blescanner-test-{}-{}", std::process::id(), name));
+            assert_eq!(RecordingFormat::from_path(&path), format);
+            let state = AppState::new(Arc::new(crate::backend::sim::SimulatedBackend::new()));
+            let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+            let status = rt.block_on(start_recording(&state, path.to_string_lossy().to_string(), None, None)).unwrap();
+            assert_eq!(status.format, format);
+            for r in &records {
+                state.notifications.lock().unwrap().record(r, true);
+            }
+            // 回放数据不写入录制文件
+            state.notifications.lock().unwrap().record(&records[0], false);
+            assert_eq!(rt.block_on(stop_recording(&state)).unwrap().unwrap().records, 2);
+            assert_eq!(load_recording(&path).unwrap(), records);
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_csv_without_extension_at_scaled_speed() {
+        // 显式指定 CSV 格式录制到 .log 文件，回放按表头识别
+        let path = std::env::temp_dir().join(format!("blescanner-test-{}-notify.log", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        let state = AppState::new(Arc::new(crate::backend::sim::SimulatedBackend::new()));
+        start_recording(&state, path_str.clone(), Some(RecordingFormat::Csv), None).await.unwrap();
+        let records = vec![record(1000, "HRM", &[0x00, 60]), record(1400, "HRM", &[0x00, 61])];
+        for r in &records {
+            state.notifications.lock().unwrap().record(r, true);
+        }
+        stop_recording(&state).await.unwrap();
+        assert_eq!(load_recording(&path).unwrap(), records);
+        state.notifications.lock().unwrap().buffers.clear();
+
+        let recorder = Arc::new(crate::events::RecordingSink::default());
+        let started = std::time::Instant::now();
+        assert_eq!(replay_recording(&state, recorder.clone(), path_str.clone(), 4.0).await.unwrap(), 2);
+        let mut completed = Vec::new();
+        for _ in 0..100 {
+            completed = recorder.take("replay-completed");
+            if !completed.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // 400ms 的间隔按 4 倍速缩短为 100ms
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(100) && elapsed < Duration::from_millis(400), "{:?}", elapsed);
+        assert_eq!(completed, vec![serde_json::json!({ "path": path_str, "records": 2 })]);
+
+        let values = recorder.take("characteristic-value");
+        assert_eq!(values.iter().map(|v| v["value"].as_str().unwrap()).collect::<Vec<_>>(), vec!["003C", "003D"]);
+        assert!(values.iter().all(|v| v["replay"] == true && v["deviceId"] == "HRM"));
+        // 回放的通知进入缓冲区
+        assert_eq!(state.notifications.lock().unwrap().fetch("HRM", "180D", "2A37", None).len(), 2);
+        assert!(replay_recording(&state, recorder, path_str, -1.0).await.is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
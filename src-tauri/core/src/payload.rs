@@ -0,0 +1,360 @@
+// 写入数据编码：把用户输入按显式编码转换为字节，格式错误时报错而不是猜测或截断
+//
+// 编码名称（大小写不敏感）：
+//   hex / base64 / utf8
+//   auto：旧的猜测规则，全为十六进制字符（可带 0x 前缀）按十六进制，否则按 UTF-8
+//   u8 i8 u16le u16be i16le i16be u32le ... i64be f32le f32be f64le f64be：
+//     一个或多个数值，以空格或逗号分隔；整数支持 0x 前缀
+//   struct:<字段>,<字段>,...：按字段依次编码，字段为上述数值类型或 hex，
+//     输入值以空格或逗号分隔，个数必须与字段数一致
+// 未指定编码时按 UTF-8 发送原文；奇数位十六进制报错而不是丢弃末尾半字节
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberKind {
+    Unsigned,
+    Signed,
+    Float,
+}
+
+// 定长数值类型：种类、字节数与字节序（单字节时字节序无意义）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberType {
+    pub kind: NumberKind,
+    pub size: usize,
+    pub endian: Endian,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructField {
+    Number(NumberType),
+    Hex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PayloadEncoding {
+    Hex,
+    Base64,
+    Utf8,
+    Auto,
+    Number(NumberType),
+    Struct(Vec<StructField>),
+}
+
+impl FromStr for NumberType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let name = s.trim().to_ascii_lowercase();
+        let (body, endian) = if let Some(b) = name.strip_suffix("le") {
+            (b, Some(Endian::Little))
+        } else if let Some(b) = name.strip_suffix("be") {
+            (b, Some(Endian::Big))
+        } else {
+            (name.as_str(), None)
+        };
+        let kind = match body.chars().next() {
+            Some('u') => NumberKind::Unsigned,
+            Some('i') => NumberKind::Signed,
+            Some('f') => NumberKind::Float,
+            _ => return Err(format!("未知的编码: {}", s)),
+        };
+        // 首字符已确认为 ASCII
+        let size = match (kind, &body[1..]) {
+            (NumberKind::Float, "32") => 4,
+            (NumberKind::Float, "64") => 8,
+            (NumberKind::Float, _) => return Err(format!("未知的编码: {}", s)),
+            (_, "8") => 1,
+            (_, "16") => 2,
+            (_, "32") => 4,
+            (_, "64") => 8,
+            _ => return Err(format!("未知的编码: {}", s)),
+        };
+        // 多字节类型必须写明字节序，避免默认值引起误解
+        let endian = match (size, endian) {
+            (1, None) => Endian::Little,
+            (1, Some(_)) => return Err(format!("单字节类型不需要字节序: {}", s)),
+            (_, Some(e)) => e,
+            (_, None) => return Err(format!("多字节类型需要指定字节序（le/be）: {}", s)),
+        };
+        Ok(NumberType { kind, size, endian })
+    }
+}
+
+impl fmt::Display for NumberType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.kind {
+            NumberKind::Unsigned => 'u',
+            NumberKind::Signed => 'i',
+            NumberKind::Float => 'f',
+        };
+        let suffix = match (self.size, self.endian) {
+            (1, _) => "",
+            (_, Endian::Little) => "le",
+            (_, Endian::Big) => "be",
+        };
+        write!(f, "{}{}{}", prefix, self.size * 8, suffix)
+    }
+}
+
+impl FromStr for PayloadEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let name = s.trim();
+        if let Some(fields) = name.strip_prefix("struct:").or_else(|| name.strip_prefix("STRUCT:")) {
+            let fields = fields
+                .split(',')
+                .map(|f| match f.trim().to_ascii_lowercase().as_str() {
+                    "hex" => Ok(StructField::Hex),
+                    other => other.parse().map(StructField::Number),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(PayloadEncoding::Struct(fields));
+        }
+        match name.to_ascii_lowercase().as_str() {
+            "hex" => Ok(PayloadEncoding::Hex),
+            "base64" => Ok(PayloadEncoding::Base64),
+            "utf8" | "utf-8" | "text" => Ok(PayloadEncoding::Utf8),
+            "auto" => Ok(PayloadEncoding::Auto),
+            "struct" => Err("struct 编码需要字段模板，如 struct:u8,u16le,f32le".to_string()),
+            other => other.parse().map(PayloadEncoding::Number),
+        }
+    }
+}
+
+impl fmt::Display for PayloadEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadEncoding::Hex => write!(f, "hex"),
+            PayloadEncoding::Base64 => write!(f, "base64"),
+            PayloadEncoding::Utf8 => write!(f, "utf8"),
+            PayloadEncoding::Auto => write!(f, "auto"),
+            PayloadEncoding::Number(t) => write!(f, "{}", t),
+            PayloadEncoding::Struct(fields) => {
+                let names: Vec<String> = fields
+                    .iter()
+                    .map(|field| match field {
+                        StructField::Hex => "hex".to_string(),
+                        StructField::Number(t) => t.to_string(),
+                    })
+                    .collect();
+                write!(f, "struct:{}", names.join(","))
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for PayloadEncoding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, String> {
+        s.parse()
+    }
+}
+
+impl From<PayloadEncoding> for String {
+    fn from(e: PayloadEncoding) -> String {
+        e.to_string()
+    }
+}
+
+fn split_values(input: &str) -> impl Iterator<Item = &str> {
+    input.split(|c: char| c.is_whitespace() || c == ',').filter(|v| !v.is_empty())
+}
+
+pub fn decode_hex(input: &str) -> Result<Vec<u8>, String> {
+    let s = input.trim();
+    let s = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    let digits: String = s.chars().filter(|c| !c.is_whitespace() && *c != ':' && *c != '-').collect();
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("无效的十六进制字符: '{}'", c));
+    }
+    if digits.len() % 2 != 0 {
+        return Err(format!("十六进制长度必须为偶数，当前 {} 位", digits.len()));
+    }
+    hex::decode(&digits).map_err(|e| format!("无效的十六进制: {}", e))
+}
+
+fn parse_int(value: &str) -> Result<i128, String> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (radix, digits) = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(h) => (16, h),
+        None => (10, digits),
+    };
+    // 标准库解析会接受数字前的符号，这里只允许最前面出现一个符号（拒绝 "--5"、"0x-5"）
+    if digits.starts_with(['+', '-']) {
+        return Err(format!("无效的整数: {}", value));
+    }
+    let n = i128::from_str_radix(digits, radix).map_err(|_| format!("无效的整数: {}", value))?;
+    Ok(if negative { -n } else { n })
+}
+
+fn encode_number(t: NumberType, value: &str) -> Result<Vec<u8>, String> {
+    let le = match t.kind {
+        NumberKind::Float => {
+            let v = value.parse::<f64>().map_err(|_| format!("无效的浮点数: {}", value))?;
+            if t.size == 4 {
+                if v.is_finite() && v.abs() > f32::MAX as f64 {
+                    return Err(format!("{} 超出 {} 范围", value, t));
+                }
+                (v as f32).to_le_bytes().to_vec()
+            } else {
+                v.to_le_bytes().to_vec()
+            }
+        }
+        kind => {
+            let v = parse_int(value)?;
+            let bits = t.size as u32 * 8;
+            let (min, max) = match kind {
+                NumberKind::Unsigned => (0, (1i128 << bits) - 1),
+                _ => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+            };
+            if v < min || v > max {
+                return Err(format!("{} 超出 {} 范围 [{}, {}]", value, t, min, max));
+            }
+            v.to_le_bytes()[..t.size].to_vec()
+        }
+    };
+    Ok(match t.endian {
+        Endian::Little => le,
+        Endian::Big => le.into_iter().rev().collect(),
+    })
+}
+
+// 按编码把输入转换为字节
+pub fn encode(input: &str, encoding: &PayloadEncoding) -> Result<Vec<u8>, String> {
+    match encoding {
+        PayloadEncoding::Hex => decode_hex(input),
+        PayloadEncoding::Base64 => base64::engine::general_purpose::STANDARD
+            .decode(input.trim())
+            .map_err(|e| format!("无效的 Base64: {}", e)),
+        PayloadEncoding::Utf8 => Ok(input.as_bytes().to_vec()),
+        PayloadEncoding::Auto => {
+            let s = input.trim();
+            let body = s.strip_prefix("0x").unwrap_or(s);
+            if body.chars().all(|c| c.is_ascii_hexdigit()) {
+                return decode_hex(s).map_err(|e| format!("{}（如需按文本发送请指定 utf8 编码）", e));
+            }
+            Ok(s.as_bytes().to_vec())
+        }
+        PayloadEncoding::Number(t) => {
+            let mut out = Vec::new();
+            for v in split_values(input) {
+                out.extend(encode_number(*t, v)?);
+            }
+            if out.is_empty() {
+                return Err(format!("{} 编码需要至少一个数值", t));
+            }
+            Ok(out)
+        }
+        PayloadEncoding::Struct(fields) => {
+            let values: Vec<&str> = split_values(input).collect();
+            if values.len() != fields.len() {
+                return Err(format!("结构模板有 {} 个字段，但提供了 {} 个值", fields.len(), values.len()));
+            }
+            let mut out = Vec::new();
+            for (i, (field, value)) in fields.iter().zip(values).enumerate() {
+                let bytes = match field {
+                    StructField::Hex => decode_hex(value),
+                    StructField::Number(t) => encode_number(*t, value),
+                };
+                out.extend(bytes.map_err(|e| format!("字段 #{}: {}", i + 1, e))?);
+            }
+            Ok(out)
+        }
+    }
+}
+
+// 解析写入数据；encoding 为 None 时按 UTF-8
+pub fn parse_data(input: &str, encoding: Option<&PayloadEncoding>) -> Result<Vec<u8>, String> {
+    encode(input, encoding.unwrap_or(&PayloadEncoding::Utf8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enc(name: &str, input: &str) -> Result<Vec<u8>, String> {
+        encode(input, &name.parse()?)
+    }
+
+    #[test]
+    fn text_and_binary_encodings() {
+        assert_eq!(enc("utf8", "cafe").unwrap(), b"cafe".to_vec());
+        assert_eq!(enc("hex", "CA FE").unwrap(), vec![0xCA, 0xFE]);
+        assert_eq!(enc("hex", "0x0102").unwrap(), vec![0x01, 0x02]);
+        assert!(enc("hex", "abc").unwrap_err().contains("偶数"));
+        assert!(enc("hex", "zz").is_err());
+        assert_eq!(enc("base64", "AQID").unwrap(), vec![1, 2, 3]);
+        assert!(enc("base64", "***").is_err());
+    }
+
+    #[test]
+    fn numbers_respect_width_sign_and_endianness() {
+        assert_eq!(enc("u8", "1, 2 0xff").unwrap(), vec![1, 2, 255]);
+        assert!(enc("u8", "256").unwrap_err().contains("超出"));
+        assert_eq!(enc("i8", "-1").unwrap(), vec![0xFF]);
+        assert_eq!(enc("u16le", "0x1234").unwrap(), vec![0x34, 0x12]);
+        assert_eq!(enc("u16be", "0x1234").unwrap(), vec![0x12, 0x34]);
+        assert_eq!(enc("i32be", "-2").unwrap(), vec![0xFF, 0xFF, 0xFF, 0xFE]);
+        assert_eq!(enc("u64le", "18446744073709551615").unwrap(), vec![0xFF; 8]);
+        assert_eq!(enc("f32le", "1.5").unwrap(), 1.5f32.to_le_bytes().to_vec());
+        assert_eq!(enc("f64be", "-2").unwrap(), (-2f64).to_be_bytes().to_vec());
+        assert!(enc("u16", "1").is_err());
+        assert!(enc("u8le", "1").is_err());
+        assert!(enc("f16le", "1").is_err());
+        assert!(enc("u16le", "").is_err());
+        // 只允许一个前导符号
+        assert_eq!(enc("i8", "+5").unwrap(), vec![5]);
+        for bad in ["--5", "+-5", "-+5", "0x-5", "-0x+5", "++5"] {
+            assert!(enc("i16le", bad).unwrap_err().contains("无效的整数"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn struct_template_encodes_fields_in_order() {
+        let e: PayloadEncoding = "struct:u8,u16le,hex,f32be".parse().unwrap();
+        assert_eq!(e.to_string(), "struct:u8,u16le,hex,f32be");
+        let mut expected = vec![0x01, 0x2C, 0x01, 0xAA, 0xBB];
+        expected.extend(2.5f32.to_be_bytes());
+        assert_eq!(encode("1 300 AABB 2.5", &e).unwrap(), expected);
+        assert!(encode("1 300", &e).unwrap_err().contains("4 个字段"));
+        assert!(encode("1 70000 AA 1", &e).unwrap_err().starts_with("字段 #2"));
+        assert!("struct".parse::<PayloadEncoding>().is_err());
+    }
+
+    #[test]
+    fn unspecified_encoding_is_utf8_and_auto_guesses() {
+        assert_eq!(parse_data("cafe", None).unwrap(), b"cafe".to_vec());
+        assert_eq!(parse_data("0x0A0b", None).unwrap(), b"0x0A0b".to_vec());
+
+        let auto: PayloadEncoding = "AUTO".parse().unwrap();
+        assert_eq!(auto.to_string(), "auto");
+        assert_eq!(parse_data("0x0A0b", Some(&auto)).unwrap(), vec![0x0A, 0x0B]);
+        assert_eq!(parse_data("cafe", Some(&auto)).unwrap(), vec![0xCA, 0xFE]);
+        assert_eq!(parse_data("hello", Some(&auto)).unwrap(), b"hello".to_vec());
+        assert!(parse_data("abc", Some(&auto)).is_err());
+    }
+
+    #[test]
+    fn serializes_as_encoding_name() {
+        let e: PayloadEncoding = serde_json::from_str("\"I16BE\"").unwrap();
+        assert_eq!(serde_json::to_string(&e).unwrap(), "\"i16be\"");
+        assert!(serde_json::from_str::<PayloadEncoding>("\"bogus\"").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+// 外设注册表：统一持有 Peripheral 句柄，按 identifier 或地址 O(1) 查找，并跟踪生命周期
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::backend::BlePeripheral;
+use crate::model::BleDevice;
+use crate::state::{adapter_id, resolve_adapter, AppState};
+
+// 外设生命周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lifecycle {
+    Discovered,
+    Connecting,
+    Connected,
+    Lost,
+}
+
+pub struct PeripheralEntry {
+    pub identifier: String,
+    pub address: String,
+    pub adapter_id: String, // 首选适配器：连接所用或最近上报该设备的适配器
+    pub lifecycle: Lifecycle,
+    pub last_seen: Instant,
+    handles: HashMap<String, Arc<dyn BlePeripheral>>, // adapterId -> 句柄
+}
+
+impl PeripheralEntry {
+    // 首选适配器上的句柄，没有时取任意一个
+    pub fn peripheral(&self) -> Option<Arc<dyn BlePeripheral>> {
+        self.handles.get(&self.adapter_id).or_else(|| self.handles.values().next()).cloned()
+    }
+
+    pub fn peripheral_on(&self, adapter_id: &str) -> Option<Arc<dyn BlePeripheral>> {
+        self.handles.get(adapter_id).cloned()
+    }
+}
+
+#[derive(Default)]
+pub struct PeripheralRegistry {
+    entries: HashMap<String, PeripheralEntry>, // key = 规范化地址（无地址时为 identifier）
+    by_identifier: HashMap<String, String>,    // identifier -> key
+}
+
+// 地址不区分大小写；取不到地址时退回 identifier
+pub(crate) fn entry_key(identifier: &str, address: &str) -> Option<String> {
+    if !address.is_empty() {
+        Some(address.to_uppercase())
+    } else if !identifier.is_empty() {
+        Some(format!("id:{}", identifier))
+    } else {
+        None
+    }
+}
+
+// 设备表记录对应的注册表键；取不到地址的平台上报 "Unknown"，同样退回 identifier
+pub fn device_key(device: &BleDevice) -> Option<String> {
+    let address = if device.address == "Unknown" { "" } else { device.address.as_str() };
+    entry_key(&device.identifier, address)
+}
+
+impl PeripheralRegistry {
+    // 扫描或枚举到外设时登记/刷新句柄
+    pub fn observe(&mut self, adapter_id: &str, peripheral: Arc<dyn BlePeripheral>) {
+        let identifier = peripheral.identifier().unwrap_or_default();
+        let address = peripheral.address().unwrap_or_default();
+        let key = match entry_key(&identifier, &address) {
+            Some(k) => k,
+            None => return,
+        };
+
+        let entry = self.entries.entry(key.clone()).or_insert_with(|| PeripheralEntry {
+            identifier: identifier.clone(),
+            address: address.clone(),
+            adapter_id: adapter_id.to_string(),
+            lifecycle: Lifecycle::Discovered,
+            last_seen: Instant::now(),
+            handles: HashMap::new(),
+        });
+        entry.handles.insert(adapter_id.to_string(), peripheral);
+        entry.last_seen = Instant::now();
+        match entry.lifecycle {
+            // 连接中/已连接时保持连接所用的适配器
+            Lifecycle::Connecting | Lifecycle::Connected => {}
+            Lifecycle::Discovered | Lifecycle::Lost => {
+                entry.lifecycle = Lifecycle::Discovered;
+                entry.adapter_id = adapter_id.to_string();
+            }
+        }
+
+        // identifier 可能随广播名称变化，同步索引
+        if entry.identifier != identifier {
+            let old = std::mem::replace(&mut entry.identifier, identifier.clone());
+            if self.by_identifier.get(&old) == Some(&key) {
+                self.by_identifier.remove(&old);
+            }
+        }
+        if !identifier.is_empty() {
+            self.by_identifier.insert(identifier, key);
+        }
+    }
+
+    // device_id 可以是 identifier、地址或注册表键本身
+    fn key_of(&self, device_id: &str) -> Option<String> {
+        if self.entries.contains_key(device_id) {
+            return Some(device_id.to_string());
+        }
+        if let Some(key) = self.by_identifier.get(device_id) {
+            return Some(key.clone());
+        }
+        let key = device_id.to_uppercase();
+        if self.entries.contains_key(&key) {
+            return Some(key);
+        }
+        let key = format!("id:{}", device_id);
+        self.entries.contains_key(&key).then_some(key)
+    }
+
+    // 按 identifier 或地址查找
+    pub fn get(&self, device_id: &str) -> Option<&PeripheralEntry> {
+        self.key_of(device_id).and_then(|k| self.entries.get(&k))
+    }
+
+    pub fn get_mut(&mut self, device_id: &str) -> Option<&mut PeripheralEntry> {
+        match self.key_of(device_id) {
+            Some(k) => self.entries.get_mut(&k),
+            None => None,
+        }
+    }
+
+    pub fn lifecycle(&self, device_id: &str) -> Option<Lifecycle> {
+        self.get(device_id).map(|e| e.lifecycle)
+    }
+
+    pub fn set_lifecycle(&mut self, device_id: &str, lifecycle: Lifecycle) {
+        if let Some(entry) = self.get_mut(device_id) {
+            entry.lifecycle = lifecycle;
+        }
+    }
+
+    // 连接成功：记录连接所用的适配器，后续操作沿用
+    pub fn mark_connected(&mut self, device_id: &str, adapter_id: &str) {
+        if let Some(entry) = self.get_mut(device_id) {
+            entry.lifecycle = Lifecycle::Connected;
+            if entry.handles.contains_key(adapter_id) {
+                entry.adapter_id = adapter_id.to_string();
+            }
+        }
+    }
+
+    // 扫描结束时，将该适配器上自 since 起未再上报且未连接的外设标记为 lost
+    pub fn mark_lost(&mut self, adapter_id: &str, since: Instant) {
+        for entry in self.entries.values_mut() {
+            if entry.lifecycle == Lifecycle::Discovered && entry.last_seen < since && entry.handles.contains_key(adapter_id) {
+                entry.lifecycle = Lifecycle::Lost;
+            }
+        }
+    }
+}
+
+// 命令传入的 deviceId 对应的注册表键；尚未登记时按设备表中的记录推断
+pub fn key_for(state: &AppState, device_id: &str) -> Option<String> {
+    if let Some(key) = state.registry.lock().unwrap().key_of(device_id) {
+        return Some(key);
+    }
+    let devices = state.devices.lock().unwrap();
+    devices.iter().find(|d| d.identifier == device_id || d.address.eq_ignore_ascii_case(device_id)).and_then(device_key)
+}
+
+// 解析设备句柄：先查注册表，未命中时从适配器的已配对设备与扫描结果中补登记
+pub fn resolve_on(
+    state: &AppState,
+    device_id: &str,
+    adapter_hint: Option<&str>,
+) -> Result<(String, Arc<dyn BlePeripheral>), String> {
+    let hint = adapter_hint.filter(|id| !id.is_empty());
+    let lookup = |registry: &PeripheralRegistry| {
+        registry.get(device_id).and_then(|entry| match hint {
+            Some(id) => entry.peripheral_on(id).map(|p| (id.to_string(), p)),
+            None => entry.peripheral().map(|p| (entry.adapter_id.clone(), p)),
+        })
+    };
+    if let Some(found) = lookup(&state.registry.lock().unwrap()) {
+        return Ok(found);
+    }
+
+    let adapter = resolve_adapter(state, device_id, hint)?;
+    let id = adapter_id(adapter.as_ref());
+    let mut peripherals = adapter.paired_peripherals().unwrap_or_default();
+    peripherals.extend(adapter.scan_results().unwrap_or_default());
+    let mut registry = state.registry.lock().unwrap();
+    for p in peripherals {
+        registry.observe(&id, p);
+    }
+    lookup(&registry).ok_or_else(|| format!("未找到设备: {}，请确认已扫描或已连接", device_id))
+}
+
+pub fn resolve(state: &AppState, device_id: &str) -> Result<Arc<dyn BlePeripheral>, String> {
+    resolve_on(state, device_id, None).map(|(_, p)| p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::{SimDevice, SimulatedBackend};
+    use crate::backend::BleBackend;
+    use crate::state::enumerate_adapters;
+
+    fn paired(backend: &SimulatedBackend, adapter: &str) -> Arc<dyn BlePeripheral> {
+        let central = backend.adapters().unwrap().into_iter().find(|a| a.identifier().unwrap() == adapter).unwrap();
+        central.paired_peripherals().unwrap().remove(0)
+    }
+
+    #[test]
+    fn lookup_by_identifier_address_and_key() {
+        let backend = SimulatedBackend::new();
+        backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(SimDevice::new("HRM", "aa:bb:cc:dd:ee:01").paired(true));
+        let mut reg = PeripheralRegistry::default();
+        reg.observe("sim0", paired(&backend, "sim0"));
+
+        for id in ["HRM", "aa:bb:cc:dd:ee:01", "AA:BB:CC:DD:EE:01"] {
+            assert_eq!(reg.key_of(id).as_deref(), Some("AA:BB:CC:DD:EE:01"), "{}", id);
+        }
+        assert!(reg.get("OTHER").is_none());
+    }
+
+    #[test]
+    fn identifier_index_follows_renames_across_adapters() {
+        let backend = SimulatedBackend::new();
+        backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(SimDevice::new("Tag", "AA:BB:CC:DD:EE:02").paired(true));
+        backend.add_adapter("sim1", "00:00:00:00:00:02").add_device(SimDevice::new("Tag v2", "AA:BB:CC:DD:EE:02").paired(true));
+        let mut reg = PeripheralRegistry::default();
+        reg.observe("sim0", paired(&backend, "sim0"));
+        reg.observe("sim1", paired(&backend, "sim1"));
+
+        assert!(reg.key_of("Tag").is_none());
+        let entry = reg.get("Tag v2").unwrap();
+        assert_eq!(entry.identifier, "Tag v2");
+        assert_eq!(entry.adapter_id, "sim1");
+        assert!(entry.peripheral_on("sim0").is_some() && entry.peripheral_on("sim1").is_some());
+        assert_eq!(reg.entries.len(), 1);
+    }
+
+    #[test]
+    fn lifecycle_transitions_keep_connection_adapter() {
+        let backend = SimulatedBackend::new();
+        backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(SimDevice::new("HRM", "AA:BB:CC:DD:EE:01").paired(true));
+        backend.add_adapter("sim1", "00:00:00:00:00:02").add_device(SimDevice::new("HRM", "AA:BB:CC:DD:EE:01").paired(true));
+        let mut reg = PeripheralRegistry::default();
+        reg.observe("sim0", paired(&backend, "sim0"));
+        assert_eq!(reg.lifecycle("HRM"), Some(Lifecycle::Discovered));
+
+        reg.set_lifecycle("HRM", Lifecycle::Connecting);
+        reg.observe("sim1", paired(&backend, "sim1"));
+        assert_eq!(reg.lifecycle("HRM"), Some(Lifecycle::Connecting));
+        reg.mark_connected("HRM", "sim0");
+        assert_eq!(reg.get("HRM").unwrap().adapter_id, "sim0");
+
+        // 已连接的外设不会因扫描结束被标记为 lost
+        reg.mark_lost("sim0", Instant::now());
+        assert_eq!(reg.lifecycle("HRM"), Some(Lifecycle::Connected));
+
+        reg.set_lifecycle("HRM", Lifecycle::Discovered);
+        reg.mark_lost("sim0", Instant::now());
+        assert_eq!(reg.lifecycle("HRM"), Some(Lifecycle::Lost));
+        reg.observe("sim1", paired(&backend, "sim1"));
+        assert_eq!(reg.lifecycle("HRM"), Some(Lifecycle::Discovered));
+        assert_eq!(reg.get("HRM").unwrap().adapter_id, "sim1");
+    }
+
+    #[test]
+    fn device_key_falls_back_to_identifier() {
+        let mut device = BleDevice { identifier: "dev".into(), address: "Unknown".into(), ..Default::default() };
+        assert_eq!(device_key(&device).as_deref(), Some("id:dev"));
+        device.address = "aa:bb:cc:dd:ee:01".into();
+        assert_eq!(device_key(&device).as_deref(), Some("AA:BB:CC:DD:EE:01"));
+    }
+
+    #[test]
+    fn resolve_on_registers_paired_and_scanned_peripherals() {
+        let backend = Arc::new(SimulatedBackend::new());
+        let adapter = backend.add_adapter("sim0", "00:00:00:00:00:01");
+        adapter.add_device(SimDevice::new("Bonded", "AA:BB:CC:DD:EE:01").paired(true));
+        adapter.add_device(SimDevice::new("Beacon", "AA:BB:CC:DD:EE:02"));
+        let state = AppState::new(backend.clone());
+
+        // 未登记的已配对外设从适配器补登记
+        let (adapter_id, p) = resolve_on(&state, "aa:bb:cc:dd:ee:01", None).unwrap();
+        assert_eq!(adapter_id, "sim0");
+        assert_eq!(p.identifier().unwrap(), "Bonded");
+        assert!(resolve(&state, "Beacon").is_err());
+
+        // 扫描结果同样可用
+        let central = enumerate_adapters(&state).unwrap().remove(0);
+        central.scan_start().unwrap();
+        adapter.advertise_once();
+        central.scan_stop().unwrap();
+        assert_eq!(resolve(&state, "Beacon").unwrap().address().unwrap(), "AA:BB:CC:DD:EE:02");
+        assert_eq!(state.registry.lock().unwrap().lifecycle("AA:BB:CC:DD:EE:02"), Some(Lifecycle::Discovered));
+
+        // 指定的适配器上没有句柄时报错
+        assert!(resolve_on(&state, "Bonded", Some("sim1")).is_err());
+    }
+}
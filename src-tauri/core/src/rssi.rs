@@ -0,0 +1,426 @@
+// RSSI 时间序列：每个设备一个有界环形缓冲区，提供平滑值、统计量与路径损耗距离估计
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::EventSink;
+use crate::model::BleDevice;
+use crate::registry;
+use crate::state::AppState;
+
+// 主适配器超过该时长（毫秒）未上报时，由其他适配器接替 RSSI 序列
+const HANDOVER_MS: u64 = 5000;
+
+// 平滑算法
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Smoothing {
+    Kalman,
+    Ema,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RssiSettings {
+    pub capacity: usize, // 每个设备保留的采样数
+    pub smoothing: Smoothing,
+    #[serde(alias = "emaAlpha")]
+    pub ema_alpha: f64,
+    #[serde(alias = "kalmanQ")]
+    pub kalman_q: f64, // 过程噪声
+    #[serde(alias = "kalmanR")]
+    pub kalman_r: f64, // 测量噪声
+    // 路径损耗指数（环境因子）：自由空间约 2，室内 2.5~4
+    #[serde(alias = "pathLossExponent")]
+    pub path_loss_exponent: f64,
+    // 1 米处的参考 RSSI；为空时使用信标广播的参考功率，再退回由 tx_power 推算（tx_power - 41）
+    #[serde(alias = "measuredPower")]
+    pub measured_power: Option<i16>,
+    // rssi-summary 事件间隔（毫秒），0 表示不推送
+    #[serde(alias = "emitIntervalMs")]
+    pub emit_interval_ms: u64,
+}
+
+impl Default for RssiSettings {
+    fn default() -> Self {
+        Self {
+            capacity: 120,
+            smoothing: Smoothing::Kalman,
+            ema_alpha: 0.3,
+            kalman_q: 0.5,
+            kalman_r: 8.0,
+            path_loss_exponent: 2.0,
+            measured_power: None,
+            emit_interval_ms: 1000,
+        }
+    }
+}
+
+impl RssiSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.capacity == 0 {
+            return Err("capacity 必须大于 0".to_string());
+        }
+        // NaN 能通过下面的范围比较，先统一排除非有限值
+        for (name, value) in [
+            ("ema_alpha", self.ema_alpha),
+            ("kalman_q", self.kalman_q),
+            ("kalman_r", self.kalman_r),
+            ("path_loss_exponent", self.path_loss_exponent),
+        ] {
+            if !value.is_finite() {
+                return Err(format!("{} 必须是有限数值", name));
+            }
+        }
+        if !(self.ema_alpha > 0.0 && self.ema_alpha <= 1.0) {
+            return Err("ema_alpha 必须在 (0, 1] 范围内".to_string());
+        }
+        if self.kalman_q <= 0.0 || self.kalman_r <= 0.0 {
+            return Err("kalman_q 与 kalman_r 必须大于 0".to_string());
+        }
+        if self.path_loss_exponent <= 0.0 {
+            return Err("path_loss_exponent 必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssiPoint {
+    pub timestamp: u64,
+    pub rssi: i16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssiStats {
+    pub count: usize,
+    pub min: i16,
+    pub max: i16,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+// 单个设备的聚合结果（rssi-summary 事件的元素）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssiSummary {
+    pub device_id: String,
+    pub adapter_id: String, // 序列所用的适配器
+    pub rssi: i16,
+    pub smoothed: f64,
+    pub stats: RssiStats,
+    pub distance: Option<f64>, // 米
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssiHistory {
+    #[serde(flatten)]
+    pub summary: RssiSummary,
+    pub samples: Vec<RssiPoint>,
+}
+
+#[derive(Debug, Default)]
+struct Track {
+    device_id: String, // 最近一次上报的 identifier，用于事件中的 deviceId
+    adapter_id: String, // 主适配器：不同适配器的 RSSI 不可比，只取一个适配器的采样
+    samples: VecDeque<RssiPoint>,
+    tx_power: Option<i16>,
+    beacon_power: Option<i16>, // 信标帧中的 1 米参考功率
+    estimate: f64,
+    error: f64, // 卡尔曼估计误差协方差
+    dirty: bool, // 上次推送后是否有新采样
+}
+
+impl Track {
+    fn push(&mut self, settings: &RssiSettings, point: RssiPoint) {
+        let z = f64::from(point.rssi);
+        if self.samples.is_empty() {
+            self.estimate = z;
+            self.error = settings.kalman_r;
+        } else {
+            match settings.smoothing {
+                Smoothing::Kalman => {
+                    self.error += settings.kalman_q;
+                    let gain = self.error / (self.error + settings.kalman_r);
+                    self.estimate += gain * (z - self.estimate);
+                    self.error *= 1.0 - gain;
+                }
+                Smoothing::Ema => self.estimate += settings.ema_alpha * (z - self.estimate),
+            }
+        }
+        while self.samples.len() >= settings.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(point);
+        self.dirty = true;
+    }
+
+    fn stats(&self) -> RssiStats {
+        let count = self.samples.len();
+        let values = self.samples.iter().map(|s| s.rssi);
+        let mean = values.clone().map(f64::from).sum::<f64>() / count.max(1) as f64;
+        let variance = values.clone().map(|v| (f64::from(v) - mean).powi(2)).sum::<f64>() / count.max(1) as f64;
+        RssiStats { count, min: values.clone().min().unwrap_or(0), max: values.max().unwrap_or(0), mean, variance }
+    }
+
+    fn summary(&self, settings: &RssiSettings) -> Option<RssiSummary> {
+        let last = self.samples.back()?;
+        let reference = reference_power(settings, self.beacon_power, self.tx_power);
+        Some(RssiSummary {
+            device_id: self.device_id.clone(),
+            adapter_id: self.adapter_id.clone(),
+            rssi: last.rssi,
+            smoothed: self.estimate,
+            stats: self.stats(),
+            distance: reference.map(|r| distance(r, self.estimate, settings.path_loss_exponent)),
+        })
+    }
+}
+
+// 1 米处参考功率：优先使用设置值，其次信标参考功率，否则由 tx_power 推算；
+// 未广播 tx_power 时后端会返回哨兵值（如 -32768），超出合理范围的一律忽略
+fn reference_power(settings: &RssiSettings, beacon_power: Option<i16>, tx_power: Option<i16>) -> Option<i16> {
+    settings
+        .measured_power
+        .or(beacon_power)
+        .or_else(|| tx_power.filter(|tx| (-100..=20).contains(tx)).map(|tx| tx - 41))
+}
+
+// 对数距离路径损耗模型：d = 10 ^ ((P1m - RSSI) / (10 * n))
+pub fn distance(measured_power: i16, rssi: f64, exponent: f64) -> f64 {
+    10f64.powf((f64::from(measured_power) - rssi) / (10.0 * exponent))
+}
+
+#[derive(Debug, Default)]
+pub struct RssiTracker {
+    pub settings: RssiSettings,
+    tracks: HashMap<String, Track>, // key = 注册表键
+}
+
+impl RssiTracker {
+    // 记录一次广播的 RSSI；返回该适配器是否为主适配器（采样是否进入序列）
+    pub fn record(&mut self, device: &BleDevice) -> bool {
+        let rssi = match device.rssi {
+            Some(r) => r,
+            None => return false,
+        };
+        let Some(key) = registry::device_key(device) else { return false };
+        let track = self.tracks.entry(key).or_default();
+        let quiet = track.samples.back().map_or(true, |s| device.last_seen.saturating_sub(s.timestamp) >= HANDOVER_MS);
+        if track.adapter_id != device.adapter_id && !quiet {
+            return false;
+        }
+        track.adapter_id = device.adapter_id.clone();
+        track.device_id = device.identifier.clone();
+        if device.tx_power.is_some() {
+            track.tx_power = device.tx_power;
+        }
+        if let Some(power) = device.beacon.as_ref().and_then(|b| b.measured_power()) {
+            track.beacon_power = Some(power);
+        }
+        track.push(&self.settings, RssiPoint { timestamp: device.last_seen, rssi });
+        true
+    }
+
+    pub fn forget(&mut self, key: &str) {
+        self.tracks.remove(key);
+    }
+
+    pub fn history(&self, key: &str) -> Option<RssiHistory> {
+        let track = self.tracks.get(key)?;
+        Some(RssiHistory {
+            summary: track.summary(&self.settings)?,
+            samples: track.samples.iter().cloned().collect(),
+        })
+    }
+
+    // 取出上次推送以来有新采样的设备
+    fn take_updated(&mut self) -> Vec<RssiSummary> {
+        let settings = &self.settings;
+        self.tracks
+            .iter_mut()
+            .filter(|(_, t)| t.dirty)
+            .filter_map(|(_, t)| {
+                t.dirty = false;
+                t.summary(settings)
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn record(state: &AppState, device: &BleDevice) -> bool {
+    state.rssi.lock().unwrap().record(device)
+}
+
+// 推送一次 rssi-summary 聚合事件
+pub(crate) fn emit_summary(state: &AppState, sink: &Arc<dyn EventSink>) {
+    let updated = state.rssi.lock().unwrap().take_updated();
+    if !updated.is_empty() {
+        let _ = sink.emit("rssi-summary", &updated);
+    }
+}
+
+// 获取设备的 RSSI 序列与统计
+pub async fn get_rssi_history(state: &AppState, device_id: String) -> Result<RssiHistory, String> {
+    let key = registry::key_for(state, &device_id).unwrap_or_else(|| device_id.clone());
+    state
+        .rssi
+        .lock()
+        .unwrap()
+        .history(&key)
+        .ok_or_else(|| format!("没有设备 {} 的 RSSI 数据", device_id))
+}
+
+pub async fn get_rssi_settings(state: &AppState) -> Result<RssiSettings, String> {
+    Ok(state.rssi.lock().unwrap().settings.clone())
+}
+
+// 修改设置；缩小容量时立即截断已有缓冲
+pub async fn set_rssi_settings(state: &AppState, settings: RssiSettings) -> Result<(), String> {
+    settings.validate()?;
+    let mut tracker = state.rssi.lock().unwrap();
+    for track in tracker.tracks.values_mut() {
+        while track.samples.len() > settings.capacity {
+            track.samples.pop_front();
+        }
+    }
+    tracker.settings = settings;
+    Ok(())
+}
+
+// 距离校准：把设备放在已知距离处，按当前平滑值反推参数。
+// 1 米处校准参考功率，其他距离校准路径损耗指数
+pub async fn calibrate_distance(state: &AppState, device_id: String, meters: f64) -> Result<RssiSettings, String> {
+    if !(meters > 0.0 && meters.is_finite()) {
+        return Err("距离必须大于 0".to_string());
+    }
+    let key = registry::key_for(state, &device_id).unwrap_or_else(|| device_id.clone());
+    let mut tracker = state.rssi.lock().unwrap();
+    let track = tracker.tracks.get(&key).filter(|t| !t.samples.is_empty());
+    let (smoothed, beacon_power, tx_power) = match track {
+        Some(t) => (t.estimate, t.beacon_power, t.tx_power),
+        None => return Err(format!("没有设备 {} 的 RSSI 数据，请先扫描", device_id)),
+    };
+    let ratio = meters.log10();
+    if ratio.abs() < 0.05 {
+        tracker.settings.measured_power = Some(smoothed.round() as i16);
+    } else {
+        let reference =
+            reference_power(&tracker.settings, beacon_power, tx_power).ok_or("设备未广播 tx_power，请先在 1 米处校准参考功率")?;
+        let exponent = (f64::from(reference) - smoothed) / (10.0 * ratio);
+        if !(exponent > 0.0 && exponent.is_finite()) {
+            return Err(format!("校准结果无效（路径损耗指数 {:.2}），请检查距离或参考功率", exponent));
+        }
+        tracker.settings.path_loss_exponent = exponent;
+    }
+    Ok(tracker.settings.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(adapter_id: &str, last_seen: u64, rssi: i16) -> BleDevice {
+        BleDevice {
+            identifier: "Tag".into(),
+            address: "AA:BB:CC:DD:EE:01".into(),
+            rssi: Some(rssi),
+            adapter_id: adapter_id.into(),
+            last_seen,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn series_follows_one_adapter_until_it_goes_quiet() {
+        let mut tracker = RssiTracker::default();
+        assert!(tracker.record(&sample("hci0", 1000, -60)));
+        assert!(!tracker.record(&sample("hci1", 1100, -80)));
+        assert!(tracker.record(&sample("hci0", 1200, -62)));
+
+        let history = tracker.history("AA:BB:CC:DD:EE:01").unwrap();
+        assert_eq!(history.summary.adapter_id, "hci0");
+        assert_eq!(history.samples.iter().map(|p| p.rssi).collect::<Vec<_>>(), vec![-60, -62]);
+
+        // 主适配器停止上报后由其他适配器接替
+        assert!(tracker.record(&sample("hci1", 1200 + HANDOVER_MS, -80)));
+        assert!(!tracker.record(&sample("hci0", 1300 + HANDOVER_MS, -61)));
+        assert_eq!(tracker.history("AA:BB:CC:DD:EE:01").unwrap().summary.adapter_id, "hci1");
+    }
+
+    fn feed(settings: RssiSettings, values: &[i16]) -> Track {
+        let mut track = Track::default();
+        for (i, &rssi) in values.iter().enumerate() {
+            track.push(&settings, RssiPoint { timestamp: i as u64, rssi });
+        }
+        track
+    }
+
+    #[test]
+    fn kalman_and_ema_converge_to_a_step() {
+        let mut values = vec![-80; 5];
+        values.extend([-60; 60]);
+        for smoothing in [Smoothing::Kalman, Smoothing::Ema] {
+            let track = feed(RssiSettings { smoothing, ..Default::default() }, &values);
+            assert!((track.estimate - -60.0).abs() < 1.0, "{:?}: {}", smoothing, track.estimate);
+        }
+        // 跳变后的第一个采样只部分跟随
+        let ema = feed(RssiSettings { smoothing: Smoothing::Ema, ema_alpha: 0.5, ..Default::default() }, &[-80, -60]);
+        assert_eq!(ema.estimate, -70.0);
+        let kalman = feed(RssiSettings::default(), &[-80, -60]);
+        // 增益 = (R + Q) / (2R + Q)
+        assert!((kalman.estimate - (-80.0 + 20.0 * 8.5 / 16.5)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stats_cover_the_bounded_window() {
+        let track = feed(RssiSettings { capacity: 4, ..Default::default() }, &[-90, -50, -60, -70, -60]);
+        let stats = track.stats();
+        assert_eq!((stats.count, stats.min, stats.max), (4, -70, -50));
+        assert_eq!(stats.mean, -60.0);
+        assert_eq!(stats.variance, 50.0);
+        assert_eq!(Track::default().stats().count, 0);
+    }
+
+    #[tokio::test]
+    async fn calibration_rejects_non_finite_distance() {
+        let state = AppState::new(std::sync::Arc::new(crate::backend::sim::SimulatedBackend::new()));
+        for meters in [f64::NAN, f64::INFINITY, 0.0] {
+            assert!(calibrate_distance(&state, "Tag".into(), meters).await.unwrap_err().contains("距离"));
+        }
+    }
+
+    #[test]
+    fn distance_uses_reference_power_priority() {
+        assert!((distance(-59, -59.0, 2.0) - 1.0).abs() < 1e-9);
+        assert!((distance(-59, -79.0, 2.0) - 10.0).abs() < 1e-9);
+        assert!((distance(-59, -79.0, 4.0) - 10f64.sqrt()).abs() < 1e-9);
+
+        let settings = RssiSettings::default();
+        assert_eq!(reference_power(&settings, Some(-65), Some(4)), Some(-65));
+        assert_eq!(reference_power(&settings, None, Some(4)), Some(-37));
+        // 后端的哨兵值不参与推算
+        assert_eq!(reference_power(&settings, None, Some(-32768)), None);
+        let fixed = RssiSettings { measured_power: Some(-50), ..Default::default() };
+        assert_eq!(reference_power(&fixed, Some(-65), Some(4)), Some(-50));
+    }
+
+    #[test]
+    fn settings_validation() {
+        assert!(RssiSettings::default().validate().is_ok());
+        assert!(RssiSettings { capacity: 0, ..Default::default() }.validate().is_err());
+        assert!(RssiSettings { ema_alpha: 1.5, ..Default::default() }.validate().is_err());
+        assert!(RssiSettings { kalman_r: 0.0, ..Default::default() }.validate().is_err());
+        assert!(RssiSettings { path_loss_exponent: -1.0, ..Default::default() }.validate().is_err());
+        for bad in [f64::NAN, f64::INFINITY] {
+            assert!(RssiSettings { ema_alpha: bad, ..Default::default() }.validate().is_err());
+            assert!(RssiSettings { kalman_q: bad, ..Default::default() }.validate().is_err());
+            assert!(RssiSettings { kalman_r: bad, ..Default::default() }.validate().is_err());
+            assert!(RssiSettings { path_loss_exponent: bad, ..Default::default() }.validate().unwrap_err().contains("有限"));
+        }
+    }
+}
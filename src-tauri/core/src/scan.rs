@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use futures::StreamExt;
+use serde::Deserialize;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::adv;
+use crate::aging;
+use crate::assigned_numbers;
+use crate::backend::{BleCentral, BlePeripheral, ScanEvent};
+use crate::beacon;
+use crate::coalesce;
+use crate::decoder;
+use crate::connection;
+use crate::events::EventSink;
+use crate::filter::{self, ScanFilter};
+use crate::history;
+use crate::registry;
+use crate::rssi;
+use crate::model::{now_ms, BleDevice};
+use crate::state::{adapter_id, current_adapter, enumerate_adapters, find_adapter, is_bonded, refresh_bonds, AppState};
+
+// 扫描参数（兼容多种参数命名）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StartScanArgs {
+    #[serde(default)]
+    #[serde(alias = "durationSecs", alias = "duration", alias = "seconds")]
+    pub duration_secs: Option<u64>,
+    // 参与扫描的适配器；为空时使用选中的适配器，["all"] 表示全部适配器
+    #[serde(default)]
+    #[serde(alias = "adapterIds", alias = "adapters")]
+    pub adapter_ids: Option<Vec<String>>,
+    // 扫描过滤器；为空时沿用当前过滤器，扫描中可用 set_scan_filter 修改
+    #[serde(default)]
+    pub filter: Option<ScanFilter>,
+}
+
+// 开始扫描 BLE 设备（实时更新），返回后台扫描任务句柄
+pub async fn start_scan(
+    state: &AppState,
+    sink: Arc<dyn EventSink>,
+    args: StartScanArgs,
+) -> Result<JoinHandle<()>, String> {
+    eprintln!("开始扫描 BLE 设备(实时)...");
+    let merged_duration = args.duration_secs;
+    eprintln!("start_scan 参数 duration_secs = {:?}", merged_duration);
+
+    // 确定参与扫描的适配器
+    let adapters: Vec<Arc<dyn BleCentral>> = match args.adapter_ids.as_deref() {
+        None | Some([]) => vec![current_adapter(state)?],
+        Some([all]) if all == "all" => {
+            let all = enumerate_adapters(state)?;
+            if all.is_empty() {
+                return Err("未找到可用的蓝牙适配器".to_string());
+            }
+            all
+        }
+        Some(ids) => ids.iter().map(|id| find_adapter(state, id)).collect::<Result<_, _>>()?,
+    };
+
+    if let Some(spec) = args.filter {
+        filter::set_scan_filter(state, Some(spec)).await?;
+    }
+
+    state.coalescer.lock().unwrap().reset();
+
+    // 标记为扫描中（不清空设备列表，保留之前扫描的数据）
+    {
+        let mut scanning = state.scanning.lock().unwrap();
+        *scanning = true;
+    }
+
+    let state_clone = state.clone();
+    let sink_clone = sink.clone();
+
+    let handle = tokio::spawn(async move {
+        // 每个适配器独立扫描，结果合并到同一设备列表并标注来源适配器
+        let scans = adapters
+            .into_iter()
+            .map(|adapter| scan_adapter(state_clone.clone(), sink_clone.clone(), adapter, merged_duration));
+        let scans = futures::future::join_all(scans);
+        tokio::pin!(scans);
+
+        // 扫描期间定期老化设备列表，并按设置的间隔推送 RSSI 聚合
+        let mut sweep = tokio::time::interval(Duration::from_millis(aging::SWEEP_INTERVAL_MS));
+        let emit_interval = state_clone.rssi.lock().unwrap().settings.emit_interval_ms;
+        let mut summary = tokio::time::interval(Duration::from_millis(emit_interval.max(100)));
+        // 合并窗口为 0 时仍定期检查，以便扫描中途开启合并
+        let window = state_clone.coalescer.lock().unwrap().settings.window_ms;
+        let mut flush = tokio::time::interval(Duration::from_millis(if window == 0 { 250 } else { window.max(20) }));
+        loop {
+            tokio::select! {
+                _ = &mut scans => break,
+                _ = flush.tick() => coalesce::flush(&state_clone, &sink_clone),
+                _ = sweep.tick() => aging::sweep(&state_clone, &sink_clone),
+                _ = summary.tick(), if emit_interval > 0 => rssi::emit_summary(&state_clone, &sink_clone),
+            }
+        }
+        coalesce::flush(&state_clone, &sink_clone);
+
+        {
+            let mut scanning = state_clone.scanning.lock().unwrap();
+            *scanning = false;
+        }
+        if let Err(e) = sink_clone.emit("scan-completed", ()) {
+            eprintln!("Failed to emit scan-completed event: {}", e);
+        }
+    });
+
+    Ok(handle)
+}
+
+// 单个适配器的扫描循环，含健康检查与自动恢复
+async fn scan_adapter(state_clone: AppState, sink_clone: Arc<dyn EventSink>, adapter: Arc<dyn BleCentral>, merged_duration: Option<u64>) {
+    let adapter_id = adapter_id(adapter.as_ref());
+    // 启动连续扫描
+    let mut events = adapter.scan_events();
+    if let Err(e) = adapter.scan_start() {
+        eprintln!("启动连续扫描失败({}): {}", adapter_id, e);
+        let _ = sink_clone.emit("scan-error", format!("启动连续扫描失败({}): {}", adapter_id, e));
+        return;
+    }
+    state_clone.active_scans.lock().unwrap().insert(adapter_id.clone(), adapter.clone());
+    // 绑定列表只在扫描开始时读取一次，配对/解除配对时再刷新
+    if let Err(e) = refresh_bonds(&state_clone, adapter.as_ref()) {
+        eprintln!("{}", e);
+    }
+
+    let scan_start_time = std::time::Instant::now();
+    let raw_secs = merged_duration.unwrap_or(10);
+    let infinite = raw_secs == 0;
+    let bounded_secs = raw_secs.clamp(1, 180);
+    let scan_duration = Duration::from_secs(bounded_secs); // 用户可调，1~180 秒；0 表示无限
+
+    // 健康检查参数
+    let mut last_event_at = std::time::Instant::now();
+    let stall_threshold = Duration::from_secs(45); // 超过该时长无事件则认为异常
+    let mut last_restart_at = std::time::Instant::now() - Duration::from_secs(3600);
+    let restart_backoff = Duration::from_secs(30); // 最小重启间隔
+    let mut restart_count: u32 = 0;
+    let max_restarts: u32 = 5;
+    let mut health_tick = tokio::time::interval(Duration::from_secs(5));
+
+    loop {
+        // 若外部请求停止，跳出循环
+        if !*state_clone.scanning.lock().unwrap() {
+            break;
+        }
+
+        // 有界扫描时长限制
+        if !infinite && scan_start_time.elapsed() >= scan_duration {
+            eprintln!("扫描时间到达限制({}s)，自动停止扫描", scan_duration.as_secs());
+            break;
+        }
+
+        tokio::select! {
+            _ = health_tick.tick() => {
+                // 周期性健康检查（仅对无限扫描模式启用）
+                if infinite {
+                    // 蓝牙开关可用性快速检查
+                    if let Ok(enabled) = state_clone.backend.bluetooth_enabled() {
+                        if !enabled {
+                            let _ = sink_clone.emit("scan-error", "蓝牙适配器已关闭，停止扫描");
+                            break;
+                        }
+                    }
+
+                    // 长时间无事件，尝试重启扫描
+                    if last_event_at.elapsed() > stall_threshold
+                        && last_restart_at.elapsed() > restart_backoff
+                        && restart_count < max_restarts
+                    {
+                        eprintln!(
+                            "扫描健康检查：{} 秒无事件，尝试重启扫描(第 {} 次)",
+                            last_event_at.elapsed().as_secs(),
+                            restart_count + 1
+                        );
+                        let _ = adapter.scan_stop();
+                        sleep(Duration::from_millis(200)).await;
+                        events = adapter.scan_events();
+                        match adapter.scan_start() {
+                            Ok(_) => {
+                                last_restart_at = std::time::Instant::now();
+                                restart_count += 1;
+                                last_event_at = std::time::Instant::now();
+                                let _ = sink_clone.emit("scan-info", "扫描已自动重启");
+                            }
+                            Err(e) => {
+                                eprintln!("自动重启扫描失败: {}", e);
+                                let _ = sink_clone.emit("scan-error", format!("自动重启扫描失败: {}", e));
+                                // 无法恢复，结束扫描
+                                break;
+                            }
+                        }
+                    }
+                }
+            },
+            res = events.next() => {
+                match res {
+                    Some(Ok(event)) => match event {
+                        ScanEvent::Found(p) | ScanEvent::Updated(p) => {
+                            last_event_at = std::time::Instant::now();
+                            let mut ble_device = device_from_peripheral(p.as_ref(), &adapter_id);
+                            ble_device.bonded = is_bonded(&state_clone, &adapter_id, &ble_device.address);
+                            state_clone.registry.lock().unwrap().observe(&adapter_id, p);
+                            connection::on_advertisement(&state_clone, &ble_device);
+                            // 过滤器不放行的设备只登记句柄，不进入设备表与前端
+                            if !filter::allows(&state_clone, &ble_device) {
+                                coalesce::count_filtered(&state_clone);
+                                continue;
+                            }
+                            decoder::apply(&state_clone, &mut ble_device);
+                            // 多个适配器同时扫描时只有主适配器的 RSSI 进入序列与设备记录
+                            let primary = rssi::record(&state_clone, &ble_device);
+                            upsert_device(&state_clone, &ble_device, primary);
+                            history::record_sighting(&state_clone, &ble_device);
+
+                            // 推送到前端：合并窗口内批量推送 devices-updated，关闭合并时沿用 device-discovered
+                            coalesce::publish(&state_clone, &sink_clone, &ble_device);
+                        }
+                        ScanEvent::Start => {
+                            // 可选：记录日志
+                            last_event_at = std::time::Instant::now();
+                            eprintln!("扫描回调：Start");
+                        }
+                        ScanEvent::Stop => {
+                            eprintln!("扫描回调：Stop");
+                            break;
+                        }
+                    },
+                    None => {
+                        // 事件流结束
+                        if infinite {
+                            // 无限模式下尝试恢复
+                            eprintln!("扫描事件流结束，尝试恢复...");
+                            let _ = adapter.scan_stop();
+                            sleep(Duration::from_millis(200)).await;
+                            events = adapter.scan_events();
+                            if adapter.scan_start().is_ok() {
+                                last_event_at = std::time::Instant::now();
+                                last_restart_at = std::time::Instant::now();
+                                restart_count += 1;
+                                continue;
+                            }
+                        }
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        eprintln!("扫描事件流错误: {}", e);
+                        let _ = sink_clone.emit("scan-error", format!("扫描事件流错误: {}", e));
+                        if infinite {
+                            // 尝试恢复
+                            let _ = adapter.scan_stop();
+                            sleep(Duration::from_millis(200)).await;
+                            events = adapter.scan_events();
+                            if adapter.scan_start().is_ok() {
+                                last_event_at = std::time::Instant::now();
+                                last_restart_at = std::time::Instant::now();
+                                restart_count += 1;
+                                continue;
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // 停止扫描
+    let _ = adapter.scan_stop();
+    state_clone.active_scans.lock().unwrap().remove(&adapter_id);
+    // 本次扫描未再上报的外设标记为 lost
+    state_clone.registry.lock().unwrap().mark_lost(&adapter_id, scan_start_time);
+}
+
+// 停止扫描
+pub async fn stop_scan(state: &AppState) -> Result<(), String> {
+    eprintln!("停止扫描 BLE 设备...");
+
+    // 置位标志，后台循环会退出
+    {
+        let mut scanning = state.scanning.lock().unwrap();
+        *scanning = false;
+    }
+
+    // 停止所有正在扫描的适配器
+    let active: Vec<(String, Arc<dyn BleCentral>)> = state.active_scans.lock().unwrap().drain().collect();
+    for (id, adapter) in active {
+        if let Err(e) = adapter.scan_stop() {
+            eprintln!("调用适配器 {} scan_stop 失败: {}", id, e);
+        }
+    }
+
+    Ok(())
+}
+
+// 获取扫描到的设备
+pub async fn get_devices(state: &AppState) -> Result<Vec<BleDevice>, String> {
+    let devices = state.devices.lock().unwrap();
+    Ok(devices.clone())
+}
+
+// 检查是否正在扫描
+pub async fn is_scanning(state: &AppState) -> Result<bool, String> {
+    let scanning = state.scanning.lock().unwrap();
+    Ok(*scanning)
+}
+
+// 由 Peripheral 上报的字段构建设备记录，adapter_id 为上报该设备的适配器
+pub fn device_from_peripheral(p: &dyn BlePeripheral, adapter_id: &str) -> BleDevice {
+    // 构建 BleDevice 结构
+    let identifier = p.identifier().unwrap_or_else(|_| "unknown".to_string());
+    let address = p.address().unwrap_or_else(|_| "Unknown".to_string());
+    let rssi = p.rssi().ok();
+    let tx_power = p.tx_power().ok();
+    let connectable = p.is_connectable().unwrap_or(false);
+    let connected = p.is_connected().unwrap_or(false);
+    let paired = p.is_paired().unwrap_or(false);
+
+    // 收集 Peripheral 实际上报的广播字段
+    let mut observed = adv::ObservedAdvertisement {
+        local_name: Some(identifier.clone()),
+        tx_power,
+        ..Default::default()
+    };
+
+    let mut manufacturer_data = HashMap::new();
+    let mut manufacturer_names = HashMap::new();
+    if let Ok(man_data) = p.manufacturer_data() {
+        for (company_id, data) in man_data {
+            manufacturer_data.insert(format!("{:04X}", company_id), hex::encode(&data));
+            if let Some(name) = assigned_numbers::company_name(company_id) {
+                manufacturer_names.insert(format!("{:04X}", company_id), name.to_string());
+            }
+            observed.manufacturer_data.push((company_id, data));
+        }
+    }
+    observed.manufacturer_data.sort_by_key(|(id, _)| *id);
+
+    let mut services = Vec::new();
+    let mut service_data = HashMap::new();
+    let mut service_names = HashMap::new();
+    if let Ok(service_list) = p.services() {
+        for service in service_list {
+            if let Some(name) = assigned_numbers::service_name(&service.uuid) {
+                service_names.insert(service.uuid.clone(), name.to_string());
+            }
+            if !service.data.is_empty() {
+                service_data.insert(adv::normalize_uuid(&service.uuid), hex::encode(&service.data));
+            }
+            observed.service_data.push((service.uuid.clone(), service.data));
+            services.push(service.uuid);
+        }
+    }
+    observed.service_uuids = services.clone();
+
+    // 尝试获取广播数据 (Advertisement Data)
+    let mut adv_data = HashMap::new();
+
+    // SimpleBLE 的 identifier 即广播中的本地名称，为空表示设备未广播名称
+    let device_name = observed.local_name.clone().filter(|n| !n.is_empty());
+
+    // 添加 RSSI 到广播数据
+    if let Some(rssi_value) = rssi {
+        adv_data.insert("rssi".to_string(), rssi_value.to_string());
+    }
+
+    // 添加 TX Power 到广播数据
+    if let Some(tx_power_value) = tx_power {
+        adv_data.insert("tx_power".to_string(), tx_power_value.to_string());
+    }
+
+    // 添加可连接性信息
+    adv_data.insert("connectable".to_string(), connectable.to_string());
+
+    // 添加服务列表信息
+    if !services.is_empty() {
+        adv_data.insert("services_count".to_string(), services.len().to_string());
+        adv_data.insert("primary_service".to_string(), services.first().unwrap_or(&"Unknown".to_string()).clone());
+    }
+
+    // 添加制造商数据信息
+    if !manufacturer_data.is_empty() {
+        adv_data.insert("manufacturer_data_entries".to_string(), manufacturer_data.len().to_string());
+        if let Some((company_id, data)) = manufacturer_data.iter().next() {
+            adv_data.insert("manufacturer_company_id".to_string(), company_id.clone());
+            adv_data.insert("manufacturer_data_length".to_string(), (data.len() / 2).to_string()); // 十六进制字符串长度 / 2 = 字节长度
+        }
+    }
+
+    // 添加设备地址信息
+    adv_data.insert("mac_address".to_string(), address.clone());
+    adv_data.insert("identifier".to_string(), identifier.clone());
+
+    let beacon = beacon::decode(&observed.manufacturer_data, &observed.service_data);
+
+    // 由实际观测字段重建原始广播数据（AD Structure 序列），不可用字段不做填充
+    let (advertisement, adv_fields) = observed.reconstruct();
+    let raw_adv_data = {
+        let raw_data = advertisement.encode();
+        if raw_data.is_empty() { None } else { Some(hex::encode_upper(&raw_data)) }
+    };
+
+    BleDevice {
+        identifier: identifier.clone(),
+        name: device_name,
+        address,
+        rssi,
+        rssi_by_adapter: rssi.map(|r| HashMap::from([(adapter_id.to_string(), r)])).unwrap_or_default(),
+        tx_power,
+        connectable,
+        connected,
+        paired,
+        bonded: false,
+        manufacturer_data,
+        manufacturer_names,
+        services,
+        service_names,
+        service_data,
+        adv_data: if adv_data.is_empty() { None } else { Some(adv_data) },
+        raw_adv_data,
+        adv_fields,
+        adapter_id: adapter_id.to_string(),
+        seen_by: vec![adapter_id.to_string()],
+        last_seen: now_ms(),
+        stale: false,
+        beacon,
+        decoded: Vec::new(),
+    }
+}
+
+// 更新到状态（upsert），与注册表同键：未广播名称的设备按地址区分；
+// primary 为 false 时该适配器的 RSSI 只记入 rssi_by_adapter
+pub fn upsert_device(state: &AppState, ble_device: &BleDevice, primary: bool) {
+    let key = registry::device_key(ble_device);
+    let mut devices = state.devices.lock().unwrap();
+    if let Some(existing) = devices.iter_mut().find(|d| registry::device_key(d) == key) {
+        // 仅更新易变字段以减少抖动
+        if primary {
+            existing.rssi = ble_device.rssi;
+        }
+        if let Some(rssi) = ble_device.rssi {
+            existing.rssi_by_adapter.insert(ble_device.adapter_id.clone(), rssi);
+        }
+        existing.tx_power = ble_device.tx_power;
+        existing.connectable = ble_device.connectable;
+        existing.connected = ble_device.connected;
+        existing.paired = ble_device.paired;
+        existing.bonded = ble_device.bonded;
+        existing.manufacturer_data = ble_device.manufacturer_data.clone();
+        existing.manufacturer_names = ble_device.manufacturer_names.clone();
+        existing.services = ble_device.services.clone();
+        existing.service_names = ble_device.service_names.clone();
+        existing.service_data = ble_device.service_data.clone();
+        existing.adv_data = ble_device.adv_data.clone(); // 更新广播数据
+        existing.raw_adv_data = ble_device.raw_adv_data.clone(); // 更新原始广播数据
+        existing.adv_fields = ble_device.adv_fields.clone();
+        existing.last_seen = ble_device.last_seen;
+        existing.stale = false;
+        existing.decoded = ble_device.decoded.clone();
+        existing.beacon = beacon::merge(existing.beacon.take(), ble_device.beacon.clone());
+        // 记录最近上报的适配器，并合并所有见过该设备的适配器
+        existing.adapter_id = ble_device.adapter_id.clone();
+        for id in &ble_device.seen_by {
+            if !existing.seen_by.contains(id) {
+                existing.seen_by.push(id.clone());
+            }
+        }
+        // 更新设备名称（如果有新名称）
+        if ble_device.name.is_some() && existing.name.is_none() {
+            existing.name = ble_device.name.clone();
+        }
+    } else {
+        devices.push(ble_device.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::{SimDevice, SimulatedBackend};
+    use crate::backend::BleBackend;
+
+    #[test]
+    fn service_data_is_captured_from_advertisements() {
+        let backend = SimulatedBackend::new();
+        backend.add_adapter("sim0", "00:00:00:00:00:01").add_device(
+            SimDevice::new("Sensor", "AA:BB:CC:DD:EE:09")
+                .paired(true)
+                .advertised_service("0x181A", &[0x34, 0x12])
+                .advertised_service("180F", &[]),
+        );
+        let peripheral = backend.adapters().unwrap()[0].paired_peripherals().unwrap().remove(0);
+        let device = device_from_peripheral(peripheral.as_ref(), "sim0");
+
+        // 键为规范化的 128 位 UUID；没有数据的服务只出现在服务列表中
+        assert_eq!(
+            device.service_data,
+            HashMap::from([("0000181A-0000-1000-8000-00805F9B34FB".to_string(), "3412".to_string())])
+        );
+        assert_eq!(device.services.len(), 2);
+        assert!(device.raw_adv_data.unwrap().contains("05161A183412"));
+    }
+}
@@ -0,0 +1,351 @@
+// GATT 数据库快照：把设备的服务、特征、属性、描述符（以及可选的可读特征值）导出为带版本号的
+// JSON / YAML 文件，并比较两份快照，用于确认固件升级前后 GATT 布局是否变化
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::adv::normalize_uuid;
+use crate::gatt;
+use crate::model::{GattCharacteristic, GattService};
+use crate::state::AppState;
+
+// 快照格式版本，结构不兼容地变化时递增
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GattSnapshot {
+    pub version: u32,
+    pub device_id: String,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub taken_at: u64, // Unix 毫秒
+    pub services: Vec<GattService>,
+    // 可读特征的值：key = 服务UUID/特征UUID，值为十六进制；未读取时为空
+    #[serde(default)]
+    pub values: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    Yaml,
+}
+
+impl SnapshotFormat {
+    // 按扩展名选择格式，.yaml / .yml 为 YAML，其余为 JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("yaml") | Some("yml") => SnapshotFormat::Yaml,
+            _ => SnapshotFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+// 一处差异；path 为 服务UUID[/特征UUID[/描述符UUID]]，field 为发生变化的字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    #[serde(default)]
+    pub field: Option<String>,
+    #[serde(default)]
+    pub old: Option<Value>,
+    #[serde(default)]
+    pub new: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub identical: bool,
+    pub changes: Vec<SnapshotChange>,
+}
+
+fn value_key(service: &str, characteristic: &str) -> String {
+    format!("{}/{}", normalize_uuid(service), normalize_uuid(characteristic))
+}
+
+// 枚举设备 GATT 表生成快照；include_values 时读取所有可读特征，读取失败的特征不记录值
+pub async fn take_snapshot(state: &AppState, device_id: String, include_values: bool) -> Result<GattSnapshot, String> {
+    let services = gatt::get_device_services(state, device_id.clone()).await?;
+    let mut values = BTreeMap::new();
+    if include_values {
+        for s in &services {
+            for c in s.characteristics.iter().filter(|c| c.properties.iter().any(|p| p == "Read")) {
+                match gatt::read_characteristic(state, device_id.clone(), s.uuid.clone(), c.uuid.clone()).await {
+                    Ok(v) => {
+                        values.insert(value_key(&s.uuid, &c.uuid), v.value);
+                    }
+                    Err(e) => eprintln!("快照读取特征值失败: {}/{}: {}", s.uuid, c.uuid, e),
+                }
+            }
+        }
+    }
+    let (address, name) = {
+        let devices = state.devices.lock().unwrap();
+        devices
+            .iter()
+            .find(|d| d.identifier == device_id || d.address.eq_ignore_ascii_case(&device_id))
+            .map(|d| (d.address.clone(), d.name.clone()))
+            .unwrap_or_default()
+    };
+    Ok(GattSnapshot {
+        version: SNAPSHOT_VERSION,
+        device_id,
+        address,
+        name,
+        taken_at: crate::model::now_ms(),
+        services,
+        values,
+    })
+}
+
+pub fn to_string(snapshot: &GattSnapshot, format: SnapshotFormat) -> Result<String, String> {
+    match format {
+        SnapshotFormat::Json => serde_json::to_string_pretty(snapshot).map_err(|e| format!("快照序列化失败: {}", e)),
+        SnapshotFormat::Yaml => serde_yaml::to_string(snapshot).map_err(|e| format!("快照序列化失败: {}", e)),
+    }
+}
+
+// 解析快照文本，JSON 与 YAML 均可（按首个非空字符判断）
+pub fn parse(text: &str) -> Result<GattSnapshot, String> {
+    let snapshot: GattSnapshot = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| format!("快照格式错误: {}", e))?
+    } else {
+        serde_yaml::from_str(text).map_err(|e| format!("快照格式错误: {}", e))?
+    };
+    if snapshot.version > SNAPSHOT_VERSION {
+        return Err(format!("快照版本 {} 高于当前支持的版本 {}", snapshot.version, SNAPSHOT_VERSION));
+    }
+    Ok(snapshot)
+}
+
+pub fn save(snapshot: &GattSnapshot, path: &Path) -> Result<(), String> {
+    let text = to_string(snapshot, SnapshotFormat::from_path(path))?;
+    std::fs::write(path, text).map_err(|e| format!("无法写入快照文件 {}: {}", path.display(), e))
+}
+
+pub fn load(path: &Path) -> Result<GattSnapshot, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("无法读取快照文件 {}: {}", path.display(), e))?;
+    parse(&text)
+}
+
+// 同一父节点下按 UUID 匹配；UUID 重复时按出现顺序编号，如 2A05#2
+fn keyed<T>(items: &[T], uuid: impl Fn(&T) -> &str) -> BTreeMap<String, &T> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    items
+        .iter()
+        .map(|item| {
+            let u = normalize_uuid(uuid(item));
+            let n = seen.entry(u.clone()).or_insert(0);
+            *n += 1;
+            let key = if *n == 1 { u } else { format!("{}#{}", u, n) };
+            (key, item)
+        })
+        .collect()
+}
+
+struct Differ {
+    changes: Vec<SnapshotChange>,
+}
+
+impl Differ {
+    fn push(&mut self, kind: ChangeKind, path: String, field: Option<&str>, old: Option<Value>, new: Option<Value>) {
+        self.changes.push(SnapshotChange { kind, path, field: field.map(str::to_string), old, new });
+    }
+
+    fn field<T: Serialize + PartialEq>(&mut self, path: &str, field: &str, old: &T, new: &T) {
+        if old != new {
+            let to_value = |v: &T| serde_json::to_value(v).ok();
+            self.push(ChangeKind::Changed, path.to_string(), Some(field), to_value(old), to_value(new));
+        }
+    }
+
+    fn characteristic(&mut self, path: &str, old: &GattCharacteristic, new: &GattCharacteristic) {
+        let sorted = |p: &[String]| {
+            let mut p = p.to_vec();
+            p.sort();
+            p
+        };
+        self.field(path, "properties", &sorted(&old.properties), &sorted(&new.properties));
+        self.field(path, "extended_properties", &old.extended_properties, &new.extended_properties);
+        self.field(path, "security", &old.security, &new.security);
+        self.field(path, "user_description", &old.user_description, &new.user_description);
+        self.field(path, "presentation_format", &old.presentation_format, &new.presentation_format);
+
+        let (old_d, new_d) = (keyed(&old.descriptors, |d| &d.uuid), keyed(&new.descriptors, |d| &d.uuid));
+        for (key, d) in &old_d {
+            let p = format!("{}/{}", path, key);
+            match new_d.get(key) {
+                Some(n) => self.field(&p, "value", &d.value, &n.value),
+                None => self.push(ChangeKind::Removed, p, None, None, None),
+            }
+        }
+        for key in new_d.keys().filter(|k| !old_d.contains_key(*k)) {
+            self.push(ChangeKind::Added, format!("{}/{}", path, key), None, None, None);
+        }
+    }
+}
+
+// 比较两份快照的 GATT 布局；特征值只在两份快照都记录了该特征的值时比较
+pub fn diff(old: &GattSnapshot, new: &GattSnapshot) -> SnapshotDiff {
+    let mut differ = Differ { changes: Vec::new() };
+    let (old_s, new_s) = (keyed(&old.services, |s| &s.uuid), keyed(&new.services, |s| &s.uuid));
+    for (skey, s) in &old_s {
+        let Some(n) = new_s.get(skey) else {
+            differ.push(ChangeKind::Removed, skey.clone(), None, None, None);
+            continue;
+        };
+        let (old_c, new_c) = (keyed(&s.characteristics, |c| &c.uuid), keyed(&n.characteristics, |c| &c.uuid));
+        for (ckey, c) in &old_c {
+            let path = format!("{}/{}", skey, ckey);
+            match new_c.get(ckey) {
+                Some(nc) => {
+                    differ.characteristic(&path, c, nc);
+                    let key = value_key(&s.uuid, &c.uuid);
+                    if let (Some(a), Some(b)) = (old.values.get(&key), new.values.get(&key)) {
+                        differ.field(&path, "value", a, b);
+                    }
+                }
+                None => differ.push(ChangeKind::Removed, path, None, None, None),
+            }
+        }
+        for ckey in new_c.keys().filter(|k| !old_c.contains_key(*k)) {
+            differ.push(ChangeKind::Added, format!("{}/{}", skey, ckey), None, None, None);
+        }
+    }
+    for skey in new_s.keys().filter(|k| !old_s.contains_key(*k)) {
+        differ.push(ChangeKind::Added, skey.clone(), None, None, None);
+    }
+    SnapshotDiff { identical: differ.changes.is_empty(), changes: differ.changes }
+}
+
+// 生成快照并写入文件（格式按扩展名）
+pub async fn export_snapshot(
+    state: &AppState,
+    device_id: String,
+    path: String,
+    include_values: bool,
+) -> Result<GattSnapshot, String> {
+    let snapshot = take_snapshot(state, device_id, include_values).await?;
+    save(&snapshot, Path::new(&path))?;
+    Ok(snapshot)
+}
+
+// 比较快照文件与设备当前的 GATT 表；快照带有特征值时同时读取并比较当前值
+pub async fn diff_with_device(state: &AppState, path: String, device_id: String) -> Result<SnapshotDiff, String> {
+    let old = load(Path::new(&path))?;
+    let current = take_snapshot(state, device_id, !old.values.is_empty()).await?;
+    Ok(diff(&old, &current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::GattDescriptor;
+
+    fn characteristic(uuid: &str, properties: &[&str]) -> GattCharacteristic {
+        GattCharacteristic {
+            uuid: uuid.to_string(),
+            name: None,
+            properties: properties.iter().map(|p| p.to_string()).collect(),
+            property_bits: 0,
+            extended_properties: None,
+            security: None,
+            descriptors: Vec::new(),
+            user_description: None,
+            presentation_format: None,
+        }
+    }
+
+    fn snapshot(services: Vec<GattService>, values: &[(&str, &str)]) -> GattSnapshot {
+        GattSnapshot {
+            version: SNAPSHOT_VERSION,
+            device_id: "DEV".into(),
+            address: String::new(),
+            name: None,
+            taken_at: 0,
+            services,
+            values: values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    fn service(uuid: &str, characteristics: Vec<GattCharacteristic>) -> GattService {
+        GattService { uuid: uuid.to_string(), name: None, characteristics }
+    }
+
+    #[test]
+    fn identical_layouts_ignore_order_and_uuid_form() {
+        let a = snapshot(
+            vec![service("180D", vec![characteristic("2A37", &["Notify"]), characteristic("2A38", &["Read"])])],
+            &[],
+        );
+        let b = snapshot(
+            vec![service(
+                "0000180D-0000-1000-8000-00805F9B34FB",
+                vec![characteristic("2A38", &["Read"]), characteristic("2a37", &["Notify"])],
+            )],
+            &[],
+        );
+        assert!(diff(&a, &b).identical);
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_entries() {
+        let mut hrm = characteristic("2A37", &["Notify"]);
+        hrm.descriptors.push(GattDescriptor { uuid: "2902".into(), name: None, value: None });
+        let old = snapshot(
+            vec![service("180D", vec![hrm, characteristic("2A38", &["Read"])]), service("180F", vec![])],
+            &[("0000180D-0000-1000-8000-00805F9B34FB/00002A38-0000-1000-8000-00805F9B34FB", "01")],
+        );
+        let new = snapshot(
+            vec![
+                service("180D", vec![characteristic("2A37", &["Notify", "Read"]), characteristic("2A38", &["Read"])]),
+                service("180A", vec![]),
+            ],
+            &[("0000180D-0000-1000-8000-00805F9B34FB/00002A38-0000-1000-8000-00805F9B34FB", "02")],
+        );
+        let result = diff(&old, &new);
+        let summary: Vec<(ChangeKind, String, Option<String>)> =
+            result.changes.iter().map(|c| (c.kind, c.path.clone(), c.field.clone())).collect();
+        let hrm_path = "0000180D-0000-1000-8000-00805F9B34FB/00002A37-0000-1000-8000-00805F9B34FB";
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Changed, hrm_path.to_string(), Some("properties".into())),
+                (ChangeKind::Removed, format!("{}/00002902-0000-1000-8000-00805F9B34FB", hrm_path), None),
+                (
+                    ChangeKind::Changed,
+                    "0000180D-0000-1000-8000-00805F9B34FB/00002A38-0000-1000-8000-00805F9B34FB".into(),
+                    Some("value".into())
+                ),
+                (ChangeKind::Removed, "0000180F-0000-1000-8000-00805F9B34FB".into(), None),
+                (ChangeKind::Added, "0000180A-0000-1000-8000-00805F9B34FB".into(), None),
+            ]
+        );
+        assert!(!result.identical);
+    }
+
+    #[test]
+    fn round_trips_yaml_and_rejects_newer_versions() {
+        let s = snapshot(vec![service("180F", vec![characteristic("2A19", &["Read"])])], &[("a/b", "57")]);
+        let yaml = to_string(&s, SnapshotFormat::Yaml).unwrap();
+        assert!(diff(&s, &parse(&yaml).unwrap()).identical);
+        let json = to_string(&s, SnapshotFormat::Json).unwrap();
+        assert_eq!(parse(&json).unwrap().values.get("a/b").map(String::as_str), Some("57"));
+        let newer = json.replace(&format!("\"version\": {}", SNAPSHOT_VERSION), "\"version\": 99");
+        assert!(parse(&newer).is_err());
+    }
+}
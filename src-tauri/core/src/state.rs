@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::aging::AgingPolicy;
+use crate::backend::{BleBackend, BleCentral};
+use crate::coalesce::Coalescer;
+use crate::connection::Connections;
+use crate::decoder::DecoderRegistry;
+use crate::filter::CompiledFilter;
+use crate::gatt_value::PresentationFormat;
+use crate::history::HistoryDb;
+use crate::model::{BleAdapter, BleDevice};
+use crate::notifications::NotificationLog;
+use crate::registry::PeripheralRegistry;
+use crate::rssi::RssiTracker;
+use crate::subscriptions::Subscriptions;
+
+// 应用程序状态
+#[derive(Clone)]
+pub struct AppState {
+    pub adapters: Arc<Mutex<Vec<BleAdapter>>>,
+    pub devices: Arc<Mutex<Vec<BleDevice>>>,
+    pub scanning: Arc<Mutex<bool>>,
+    // 蓝牙后端（SimpleBLE 或模拟后端）
+    pub backend: Arc<dyn BleBackend>,
+    // 保持对当前（选中）适配器的引用
+    pub current_adapter: Arc<Mutex<Option<Arc<dyn BleCentral>>>>,
+    // 已枚举的适配器句柄，key = 适配器 identifier
+    pub adapter_handles: Arc<Mutex<HashMap<String, Arc<dyn BleCentral>>>>,
+    // 正在扫描的适配器，停止扫描时逐个调用 scan_stop
+    pub active_scans: Arc<Mutex<HashMap<String, Arc<dyn BleCentral>>>>,
+    // 通知/指示订阅：元数据与转发任务，key = deviceId|serviceUuid|charUuid
+    pub subscriptions: Arc<Mutex<Subscriptions>>,
+    // 外设注册表：所有命令统一从这里取 Peripheral 句柄
+    pub registry: Arc<Mutex<PeripheralRegistry>>,
+    // 各适配器已绑定外设的地址：adapterId -> 地址集合（大写）
+    pub bonds: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+    // 每个设备的连接状态机与重连策略
+    pub connections: Arc<Mutex<Connections>>,
+    // 设备历史数据库，未打开时不做持久化
+    pub history: Arc<Mutex<Option<HistoryDb>>>,
+    // 设备老化策略（stale / lost 超时）
+    pub aging: Arc<Mutex<AgingPolicy>>,
+    // 每个设备的 RSSI 序列与平滑状态
+    pub rssi: Arc<Mutex<RssiTracker>>,
+    // 扫描过滤器，不匹配的广播不更新设备表也不推送事件
+    pub scan_filter: Arc<Mutex<Option<CompiledFilter>>>,
+    // 设备事件合并与计数
+    pub coalescer: Arc<Mutex<Coalescer>>,
+    // 厂商数据解码器（内置 + 布局文件）
+    pub decoders: Arc<Mutex<DecoderRegistry>>,
+    // 特征的呈现格式描述符缓存：key = deviceId|serviceUuid|charUuid（UUID 规范化），None 表示没有该描述符
+    pub presentation_formats: Arc<Mutex<HashMap<String, Option<PresentationFormat>>>>,
+    // 每个订阅的通知环形缓冲区、录制文件与回放任务
+    pub notifications: Arc<Mutex<NotificationLog>>,
+}
+
+impl AppState {
+    pub fn new(backend: Arc<dyn BleBackend>) -> Self {
+        Self {
+            adapters: Arc::new(Mutex::new(Vec::new())),
+            devices: Arc::new(Mutex::new(Vec::new())),
+            scanning: Arc::new(Mutex::new(false)),
+            backend,
+            current_adapter: Arc::new(Mutex::new(None)),
+            adapter_handles: Arc::new(Mutex::new(HashMap::new())),
+            active_scans: Arc::new(Mutex::new(HashMap::new())),
+            subscriptions: Arc::new(Mutex::new(Subscriptions::default())),
+            registry: Arc::new(Mutex::new(PeripheralRegistry::default())),
+            bonds: Arc::new(Mutex::new(HashMap::new())),
+            connections: Arc::new(Mutex::new(Connections::default())),
+            history: Arc::new(Mutex::new(None)),
+            aging: Arc::new(Mutex::new(AgingPolicy::default())),
+            rssi: Arc::new(Mutex::new(RssiTracker::default())),
+            scan_filter: Arc::new(Mutex::new(None)),
+            coalescer: Arc::new(Mutex::new(Coalescer::default())),
+            decoders: Arc::new(Mutex::new(DecoderRegistry::default())),
+            presentation_formats: Arc::new(Mutex::new(HashMap::new())),
+            notifications: Arc::new(Mutex::new(NotificationLog::default())),
+        }
+    }
+}
+
+// 适配器 ID：优先 identifier，取不到时用地址
+pub fn adapter_id(adapter: &dyn BleCentral) -> String {
+    adapter
+        .identifier()
+        .ok()
+        .filter(|id| !id.is_empty())
+        .or_else(|| adapter.address().ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// 重新枚举适配器并刷新句柄缓存（保留已有句柄，避免扫描结果随新句柄丢失）
+pub fn enumerate_adapters(state: &AppState) -> Result<Vec<Arc<dyn BleCentral>>, String> {
+    let adapters = state.backend.adapters().map_err(|e| format!("获取蓝牙适配器失败: {}", e))?;
+    let mut handles = state.adapter_handles.lock().unwrap();
+    Ok(adapters
+        .into_iter()
+        .map(|a| handles.entry(adapter_id(a.as_ref())).or_insert(a).clone())
+        .collect())
+}
+
+// 按 identifier 或地址查找适配器
+pub fn find_adapter(state: &AppState, id: &str) -> Result<Arc<dyn BleCentral>, String> {
+    if let Some(adapter) = state.adapter_handles.lock().unwrap().get(id).cloned() {
+        return Ok(adapter);
+    }
+    enumerate_adapters(state)?
+        .into_iter()
+        .find(|a| adapter_id(a.as_ref()) == id || a.address().map(|addr| addr.eq_ignore_ascii_case(id)).unwrap_or(false))
+        .ok_or_else(|| format!("未找到蓝牙适配器: {}", id))
+}
+
+// 取得选中的适配器；未选择时取后端枚举到的第一个并缓存
+pub fn current_adapter(state: &AppState) -> Result<Arc<dyn BleCentral>, String> {
+    if let Some(adapter) = state.current_adapter.lock().unwrap().clone() {
+        return Ok(adapter);
+    }
+    let adapter = enumerate_adapters(state)?.into_iter().next().ok_or_else(|| "未找到可用的蓝牙适配器".to_string())?;
+    *state.current_adapter.lock().unwrap() = Some(adapter.clone());
+    Ok(adapter)
+}
+
+// 选择默认适配器，后续未显式指定适配器的扫描与连接都使用它
+pub fn select_adapter(state: &AppState, id: &str) -> Result<BleAdapter, String> {
+    let adapter = find_adapter(state, id)?;
+    *state.current_adapter.lock().unwrap() = Some(adapter.clone());
+    let info = BleAdapter {
+        identifier: adapter_id(adapter.as_ref()),
+        address: adapter.address().unwrap_or_default(),
+        powered: state.backend.bluetooth_enabled().unwrap_or(true),
+        selected: true,
+    };
+    let mut adapters = state.adapters.lock().unwrap();
+    for a in adapters.iter_mut() {
+        a.selected = a.identifier == info.identifier;
+    }
+    Ok(info)
+}
+
+// 为设备操作确定适配器：显式指定 > 注册表记录（连接所用或最近上报） > 设备记录 > 选中的适配器
+pub fn resolve_adapter(state: &AppState, device_id: &str, explicit: Option<&str>) -> Result<Arc<dyn BleCentral>, String> {
+    if let Some(id) = explicit.filter(|id| !id.is_empty()) {
+        return find_adapter(state, id);
+    }
+    let known = state.registry.lock().unwrap().get(device_id).map(|e| e.adapter_id.clone()).or_else(|| {
+        let devices = state.devices.lock().unwrap();
+        devices.iter().find(|d| d.identifier == device_id || d.address.eq_ignore_ascii_case(device_id)).map(|d| d.adapter_id.clone()).filter(|id| !id.is_empty())
+    });
+    // 记录的适配器已不可用时退回选中的适配器
+    match known.and_then(|id| find_adapter(state, &id).ok()) {
+        Some(adapter) => Ok(adapter),
+        None => current_adapter(state),
+    }
+}
+
+// 重新读取适配器的绑定列表
+pub fn refresh_bonds(state: &AppState, adapter: &dyn BleCentral) -> Result<(), String> {
+    let bonded: HashSet<String> = adapter
+        .paired_peripherals()
+        .map_err(|e| format!("获取已绑定设备失败: {}", e))?
+        .iter()
+        .filter_map(|p| p.address().ok())
+        .map(|a| a.to_uppercase())
+        .collect();
+    state.bonds.lock().unwrap().insert(adapter_id(adapter), bonded);
+    Ok(())
+}
+
+pub fn is_bonded(state: &AppState, adapter_id: &str, address: &str) -> bool {
+    state
+        .bonds
+        .lock()
+        .unwrap()
+        .get(adapter_id)
+        .map(|set| set.contains(&address.to_uppercase()))
+        .unwrap_or(false)
+}
+
+// 获取可用的蓝牙适配器
+pub async fn get_adapters(state: &AppState) -> Result<Vec<BleAdapter>, String> {
+    // 通过当前后端枚举适配器
+    let adapters = enumerate_adapters(state)?;
+    let selected = current_adapter(state).ok().map(|a| adapter_id(a.as_ref()));
+
+    // 将结果映射到前端期望的结构
+    let mut result: Vec<BleAdapter> = Vec::new();
+    for a in adapters.iter() {
+        let identifier = adapter_id(a.as_ref());
+        let address = a.address().unwrap_or_else(|_| "".into());
+        // powered 状态 SimpleBLE Adapter 暂无直接 API，这里假设蓝牙开启即可认为 true；若需严格可改为 bluetooth_enabled()
+        let powered = state.backend.bluetooth_enabled().unwrap_or(true);
+        let selected = selected.as_deref() == Some(identifier.as_str());
+        result.push(BleAdapter { identifier, address, powered, selected });
+    }
+
+    // 缓存到状态中
+    {
+        let mut adapters_state = state.adapters.lock().unwrap();
+        *adapters_state = result.clone();
+    }
+
+    Ok(result)
+}
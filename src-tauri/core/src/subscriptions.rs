@@ -0,0 +1,640 @@
+// 订阅跟踪：记录每个通知/指示订阅的元数据与转发任务。
+// 重复订阅替换旧任务；设备重新连接后自动恢复该设备的订阅，用户主动断开时清除
+//
+// 吞吐统计：每个订阅统计包数/字节数、滑动窗口内的包速率与字节速率、到达间隔与抖动
+// （RFC 3550 式平滑的相邻间隔差），配置序号字段后统计丢包、重复与乱序；
+// 有订阅时每隔 interval_ms 推送 subscription-stats
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::events::EventSink;
+use crate::gatt;
+use crate::model::now_ms;
+use crate::notifications::NotificationRecord;
+use crate::payload::{Endian, NumberKind, NumberType};
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionInfo {
+    pub device_id: String,
+    pub service_uuid: String,
+    pub characteristic_uuid: String,
+    pub kind: String, // notify / indicate
+    pub created_at: u64,
+    pub messages: u64,
+    pub last_value: Option<String>,
+    pub last_received_at: Option<u64>,
+    // 转发任务仍在运行；链路断开后为 false，等待重连恢复
+    pub active: bool,
+    // 重连后自动恢复的次数
+    pub restored: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsSettings {
+    // subscription-stats 推送间隔（毫秒），0 表示不推送；修改后在下一个周期生效
+    #[serde(alias = "intervalMs")]
+    pub interval_ms: u64,
+    // 计算包速率/字节速率的滑动窗口（毫秒）
+    #[serde(alias = "windowMs")]
+    pub window_ms: u64,
+}
+
+impl Default for StatsSettings {
+    fn default() -> Self {
+        Self { interval_ms: 1000, window_ms: 5000 }
+    }
+}
+
+// 负载中的序号字段：字节偏移与无符号整数类型，文本形式 "<偏移>:<类型>"，如 "0:u8"、"2:u16le"（偏移缺省为 0）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SequenceField {
+    pub offset: usize,
+    pub number: NumberType,
+}
+
+impl FromStr for SequenceField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (offset, name) = match s.trim().split_once(':') {
+            Some((o, n)) => (o.trim().parse::<usize>().map_err(|_| format!("无效的序号偏移: {}", o))?, n),
+            None => (0, s),
+        };
+        let number: NumberType = name.parse()?;
+        if number.kind != NumberKind::Unsigned {
+            return Err(format!("序号字段必须是无符号整数类型: {}", name));
+        }
+        Ok(SequenceField { offset, number })
+    }
+}
+
+impl fmt::Display for SequenceField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.offset, self.number)
+    }
+}
+
+impl TryFrom<String> for SequenceField {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, String> {
+        s.parse()
+    }
+}
+
+impl From<SequenceField> for String {
+    fn from(f: SequenceField) -> String {
+        f.to_string()
+    }
+}
+
+impl SequenceField {
+    // 负载长度不足时返回 None
+    fn extract(&self, data: &[u8]) -> Option<u64> {
+        let bytes = data.get(self.offset..self.offset + self.number.size)?;
+        let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+        Some(match self.number.endian {
+            Endian::Little => bytes.iter().rev().fold(0, fold),
+            Endian::Big => bytes.iter().fold(0, fold),
+        })
+    }
+
+    fn mask(&self) -> u64 {
+        if self.number.size >= 8 {
+            u64::MAX
+        } else {
+            (1u64 << (self.number.size * 8)) - 1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionStats {
+    pub device_id: String,
+    pub service_uuid: String,
+    pub characteristic_uuid: String,
+    pub kind: String,
+    pub active: bool,
+    pub elapsed_ms: u64,
+    pub packets: u64,
+    pub bytes: u64,
+    // 最近 window_ms 内的速率（订阅不足一个窗口时按实际时长计算）
+    pub window_ms: u64,
+    pub packets_per_sec: f64,
+    pub bytes_per_sec: f64,
+    pub mean_interval_ms: Option<f64>,
+    pub min_interval_ms: Option<f64>,
+    pub max_interval_ms: Option<f64>,
+    pub jitter_ms: f64,
+    pub sequence: Option<SequenceField>,
+    pub gaps: u64,         // 序号不连续的次数
+    pub lost_packets: u64, // 按序号推算丢失的包数
+    pub duplicates: u64,
+    pub out_of_order: u64,
+    pub unreadable: u64, // 负载过短、读不到序号的包
+}
+
+// 单个订阅的到达统计，时间取单调时钟
+#[derive(Debug, Clone)]
+struct Throughput {
+    since: Instant,
+    recent: VecDeque<(Instant, usize)>,
+    packets: u64,
+    bytes: u64,
+    last_arrival: Option<Instant>,
+    last_interval_ms: Option<f64>,
+    intervals: u64,
+    interval_sum_ms: f64,
+    min_interval_ms: f64,
+    max_interval_ms: f64,
+    jitter_ms: f64,
+    sequence: Option<SequenceField>,
+    last_seq: Option<u64>,
+    gaps: u64,
+    lost: u64,
+    duplicates: u64,
+    out_of_order: u64,
+    unreadable: u64,
+}
+
+impl Throughput {
+    fn new(now: Instant, sequence: Option<SequenceField>) -> Self {
+        Throughput {
+            since: now,
+            recent: VecDeque::new(),
+            packets: 0,
+            bytes: 0,
+            last_arrival: None,
+            last_interval_ms: None,
+            intervals: 0,
+            interval_sum_ms: 0.0,
+            min_interval_ms: f64::MAX,
+            max_interval_ms: 0.0,
+            jitter_ms: 0.0,
+            sequence,
+            last_seq: None,
+            gaps: 0,
+            lost: 0,
+            duplicates: 0,
+            out_of_order: 0,
+            unreadable: 0,
+        }
+    }
+
+    fn on_packet(&mut self, now: Instant, data: &[u8], window: Duration) {
+        self.packets += 1;
+        self.bytes += data.len() as u64;
+        self.recent.push_back((now, data.len()));
+        while self.recent.front().map(|(t, _)| now.duration_since(*t) > window).unwrap_or(false) {
+            self.recent.pop_front();
+        }
+
+        if let Some(last) = self.last_arrival {
+            let interval = now.duration_since(last).as_secs_f64() * 1000.0;
+            self.intervals += 1;
+            self.interval_sum_ms += interval;
+            self.min_interval_ms = self.min_interval_ms.min(interval);
+            self.max_interval_ms = self.max_interval_ms.max(interval);
+            if let Some(prev) = self.last_interval_ms {
+                self.jitter_ms += ((interval - prev).abs() - self.jitter_ms) / 16.0;
+            }
+            self.last_interval_ms = Some(interval);
+        }
+        self.last_arrival = Some(now);
+
+        let Some(field) = self.sequence else { return };
+        let Some(seq) = field.extract(data) else {
+            self.unreadable += 1;
+            return;
+        };
+        if let Some(last) = self.last_seq {
+            // 按字段宽度回绕；前进超过半个序号空间视为乱序（旧包晚到）
+            let diff = seq.wrapping_sub(last) & field.mask();
+            if diff == 0 {
+                self.duplicates += 1;
+                return;
+            }
+            if diff > field.mask() / 2 {
+                self.out_of_order += 1;
+                return;
+            }
+            if diff > 1 {
+                self.gaps += 1;
+                self.lost += diff - 1;
+            }
+        }
+        self.last_seq = Some(seq);
+    }
+
+    // 链路恢复后重新计间隔与序号，断开期间不算抖动或丢包
+    fn link_restored(&mut self) {
+        self.last_arrival = None;
+        self.last_interval_ms = None;
+        self.last_seq = None;
+    }
+
+    fn set_sequence(&mut self, sequence: Option<SequenceField>) {
+        self.sequence = sequence;
+        self.last_seq = None;
+        self.gaps = 0;
+        self.lost = 0;
+        self.duplicates = 0;
+        self.out_of_order = 0;
+        self.unreadable = 0;
+    }
+
+    fn stats(&self, info: &SubscriptionInfo, now: Instant, window: Duration) -> SubscriptionStats {
+        let elapsed = now.duration_since(self.since);
+        let span = elapsed.min(window).as_secs_f64();
+        let (count, bytes) = self
+            .recent
+            .iter()
+            .filter(|(t, _)| now.duration_since(*t) <= window)
+            .fold((0usize, 0usize), |(c, b), (_, len)| (c + 1, b + len));
+        let rate = |v: usize| if span > 0.0 { v as f64 / span } else { 0.0 };
+        let has_intervals = self.intervals > 0;
+        SubscriptionStats {
+            device_id: info.device_id.clone(),
+            service_uuid: info.service_uuid.clone(),
+            characteristic_uuid: info.characteristic_uuid.clone(),
+            kind: info.kind.clone(),
+            active: info.active,
+            elapsed_ms: elapsed.as_millis() as u64,
+            packets: self.packets,
+            bytes: self.bytes,
+            window_ms: window.as_millis() as u64,
+            packets_per_sec: rate(count),
+            bytes_per_sec: rate(bytes),
+            mean_interval_ms: has_intervals.then(|| self.interval_sum_ms / self.intervals as f64),
+            min_interval_ms: has_intervals.then_some(self.min_interval_ms),
+            max_interval_ms: has_intervals.then_some(self.max_interval_ms),
+            jitter_ms: self.jitter_ms,
+            sequence: self.sequence,
+            gaps: self.gaps,
+            lost_packets: self.lost,
+            duplicates: self.duplicates,
+            out_of_order: self.out_of_order,
+            unreadable: self.unreadable,
+        }
+    }
+}
+
+struct Subscription {
+    info: SubscriptionInfo,
+    generation: u64,
+    task: JoinHandle<()>,
+    throughput: Throughput,
+}
+
+// key = deviceId|服务UUID|特征UUID（UUID 规范化）
+#[derive(Default)]
+pub struct Subscriptions {
+    pub settings: StatsSettings,
+    entries: HashMap<String, Subscription>,
+    next_generation: u64,
+    // 周期推送 subscription-stats 的任务，没有订阅时自行结束
+    ticker: Option<JoinHandle<()>>,
+}
+
+impl Subscriptions {
+    pub fn next_generation(&mut self) -> u64 {
+        self.next_generation += 1;
+        self.next_generation
+    }
+
+    // 登记订阅并替换同一特征的旧任务（保留序号字段配置）；restore 时沿用旧记录的创建时间、计数与吞吐统计
+    pub fn insert(&mut self, key: String, generation: u64, mut info: SubscriptionInfo, task: JoinHandle<()>, restore: bool) {
+        let mut throughput = Throughput::new(Instant::now(), None);
+        if let Some(old) = self.entries.remove(&key) {
+            old.task.abort();
+            if restore {
+                info.created_at = old.info.created_at;
+                info.messages = old.info.messages;
+                info.last_value = old.info.last_value;
+                info.last_received_at = old.info.last_received_at;
+                info.restored = old.info.restored + 1;
+                throughput = old.throughput;
+                throughput.link_restored();
+            } else {
+                throughput.sequence = old.throughput.sequence;
+            }
+        }
+        self.entries.insert(key, Subscription { info, generation, task, throughput });
+    }
+
+    // 转发任务收到数据；generation 不一致说明该任务已被替换
+    pub fn on_message(&mut self, key: &str, generation: u64, record: &NotificationRecord, data: &[u8]) {
+        let window = Duration::from_millis(self.settings.window_ms);
+        if let Some(s) = self.entries.get_mut(key).filter(|s| s.generation == generation) {
+            s.info.messages += 1;
+            s.info.last_value = Some(record.value.clone());
+            s.info.last_received_at = Some(record.timestamp);
+            s.throughput.on_packet(Instant::now(), data, window);
+        }
+    }
+
+    fn stats(&self, device_id: Option<&str>, active_only: bool) -> Vec<SubscriptionStats> {
+        let (now, window) = (Instant::now(), Duration::from_millis(self.settings.window_ms));
+        let mut list: Vec<SubscriptionStats> = self
+            .entries
+            .values()
+            .filter(|s| device_id.map(|id| s.info.device_id == id).unwrap_or(true))
+            .filter(|s| !active_only || s.info.active)
+            .map(|s| s.throughput.stats(&s.info, now, window))
+            .collect();
+        list.sort_by(|a, b| (&a.device_id, &a.characteristic_uuid).cmp(&(&b.device_id, &b.characteristic_uuid)));
+        list
+    }
+
+    // 数据流结束（退订或链路断开）
+    pub fn on_closed(&mut self, key: &str, generation: u64) {
+        if let Some(s) = self.entries.get_mut(key).filter(|s| s.generation == generation) {
+            s.info.active = false;
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        match self.entries.remove(key) {
+            Some(s) => {
+                s.task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn forget_device(&mut self, device_id: &str) {
+        self.entries.retain(|_, s| {
+            let keep = s.info.device_id != device_id;
+            if !keep {
+                s.task.abort();
+            }
+            keep
+        });
+    }
+
+    fn for_device(&self, device_id: &str) -> Vec<SubscriptionInfo> {
+        self.entries.values().filter(|s| s.info.device_id == device_id).map(|s| s.info.clone()).collect()
+    }
+
+    fn set_error(&mut self, key: &str, error: Option<String>) {
+        if let Some(s) = self.entries.get_mut(key) {
+            s.info.last_error = error;
+        }
+    }
+}
+
+pub fn new_info(device_id: &str, service_uuid: &str, characteristic_uuid: &str, kind: &str) -> SubscriptionInfo {
+    SubscriptionInfo {
+        device_id: device_id.to_string(),
+        service_uuid: service_uuid.to_string(),
+        characteristic_uuid: characteristic_uuid.to_string(),
+        kind: kind.to_string(),
+        created_at: now_ms(),
+        messages: 0,
+        last_value: None,
+        last_received_at: None,
+        active: true,
+        restored: 0,
+        last_error: None,
+    }
+}
+
+// 设备重新连接后重新订阅其所有订阅（旧的数据流已随链路断开失效），
+// 每个订阅推送一条 subscription-restored 事件，失败时带 error
+pub(crate) fn restore(state: &AppState, sink: &Arc<dyn EventSink>, device_id: &str) {
+    let previous = state.subscriptions.lock().unwrap().for_device(device_id);
+    for info in previous {
+        let result = gatt::subscribe(
+            state,
+            sink.clone(),
+            info.device_id.clone(),
+            info.service_uuid.clone(),
+            info.characteristic_uuid.clone(),
+            info.kind == "indicate",
+            true,
+        );
+        let error = result.err();
+        match &error {
+            Some(e) => eprintln!("恢复订阅失败: {} {}: {}", device_id, info.characteristic_uuid, e),
+            None => eprintln!("已恢复订阅: {} {}", device_id, info.characteristic_uuid),
+        }
+        let key = gatt::format_key(&info.device_id, &info.service_uuid, &info.characteristic_uuid);
+        state.subscriptions.lock().unwrap().set_error(&key, error.clone());
+        let _ = sink.emit("subscription-restored", serde_json::json!({
+            "deviceId": info.device_id,
+            "serviceUuid": info.service_uuid,
+            "characteristicUuid": info.characteristic_uuid,
+            "kind": info.kind,
+            "error": error
+        }));
+    }
+}
+
+// 启动周期推送 subscription-stats 的任务（已在运行时不重复启动）
+pub(crate) fn ensure_stats_ticker(state: &AppState, sink: &Arc<dyn EventSink>) {
+    let mut subs = state.subscriptions.lock().unwrap();
+    if subs.ticker.is_none() {
+        subs.ticker = Some(tokio::spawn(stats_ticker(state.subscriptions.clone(), sink.clone())));
+    }
+}
+
+async fn stats_ticker(subscriptions: Arc<Mutex<Subscriptions>>, sink: Arc<dyn EventSink>) {
+    loop {
+        let interval = subscriptions.lock().unwrap().settings.interval_ms;
+        // 关闭推送时低频轮询，以便重新开启后恢复
+        sleep(Duration::from_millis(if interval == 0 { 1000 } else { interval })).await;
+        let stats = {
+            let mut subs = subscriptions.lock().unwrap();
+            if subs.entries.is_empty() {
+                subs.ticker = None;
+                return;
+            }
+            if subs.settings.interval_ms == 0 {
+                continue;
+            }
+            subs.stats(None, true)
+        };
+        for s in stats {
+            let _ = sink.emit("subscription-stats", &s);
+        }
+    }
+}
+
+// 列出订阅，device_id 为空时返回全部
+pub async fn list_subscriptions(state: &AppState, device_id: Option<String>) -> Result<Vec<SubscriptionInfo>, String> {
+    let subs = state.subscriptions.lock().unwrap();
+    let mut list: Vec<SubscriptionInfo> = subs
+        .entries
+        .values()
+        .filter(|s| device_id.as_deref().map(|id| s.info.device_id == id).unwrap_or(true))
+        .map(|s| s.info.clone())
+        .collect();
+    list.sort_by(|a, b| (&a.device_id, a.created_at).cmp(&(&b.device_id, b.created_at)));
+    Ok(list)
+}
+
+// 各订阅的吞吐统计，device_id 为空时返回全部
+pub async fn get_subscription_stats(state: &AppState, device_id: Option<String>) -> Result<Vec<SubscriptionStats>, String> {
+    Ok(state.subscriptions.lock().unwrap().stats(device_id.as_deref(), false))
+}
+
+// 设置（或以 None 清除）订阅的序号字段，并重新开始丢包统计
+pub async fn set_subscription_sequence(
+    state: &AppState,
+    device_id: String,
+    service_uuid: String,
+    characteristic_uuid: String,
+    sequence: Option<SequenceField>,
+) -> Result<(), String> {
+    let key = gatt::format_key(&device_id, &service_uuid, &characteristic_uuid);
+    let mut subs = state.subscriptions.lock().unwrap();
+    let entry = subs.entries.get_mut(&key).ok_or_else(|| format!("未订阅该特征: {}", characteristic_uuid))?;
+    entry.throughput.set_sequence(sequence);
+    Ok(())
+}
+
+pub async fn get_subscription_stats_settings(state: &AppState) -> Result<StatsSettings, String> {
+    Ok(state.subscriptions.lock().unwrap().settings.clone())
+}
+
+// 修改推送间隔与速率窗口：窗口立即生效，间隔在下一个周期生效
+pub async fn set_subscription_stats_settings(state: &AppState, settings: StatsSettings) -> Result<(), String> {
+    if settings.window_ms == 0 {
+        return Err("统计窗口必须大于 0".to_string());
+    }
+    state.subscriptions.lock().unwrap().settings = settings;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::sim::{SimCharacteristic, SimDevice, SimService, SimulatedBackend};
+    use crate::connection::{self, ReconnectPolicy};
+    use crate::device;
+    use crate::events::NullSink;
+    use std::time::Duration;
+
+    fn info() -> SubscriptionInfo {
+        new_info("dev", "180D", "2A37", "notify")
+    }
+
+    #[test]
+    fn parses_sequence_fields() {
+        let f: SequenceField = "2:u16le".parse().unwrap();
+        assert_eq!((f.offset, f.number.size), (2, 2));
+        assert_eq!(f.to_string(), "2:u16le");
+        assert_eq!("u8".parse::<SequenceField>().unwrap().offset, 0);
+        assert_eq!(f.extract(&[0xFF, 0xFF, 0x34, 0x12]), Some(0x1234));
+        assert_eq!("1:u16be".parse::<SequenceField>().unwrap().extract(&[0, 0x12, 0x34]), Some(0x1234));
+        assert_eq!(f.extract(&[0, 0, 1]), None);
+        assert!("i16le".parse::<SequenceField>().is_err());
+        assert!("0:u16".parse::<SequenceField>().is_err());
+        assert!("x:u8".parse::<SequenceField>().is_err());
+    }
+
+    #[test]
+    fn rates_intervals_and_jitter() {
+        let t0 = Instant::now();
+        let window = Duration::from_secs(1);
+        let mut t = Throughput::new(t0, None);
+        // 每 10 ms 一包 20 字节，共 2 秒；每隔一个间隔为 20 ms
+        let mut at = Duration::ZERO;
+        for i in 0..100u32 {
+            at += Duration::from_millis(if i % 2 == 0 { 10 } else { 30 });
+            t.on_packet(t0 + at, &[0u8; 20], window);
+        }
+        let s = t.stats(&info(), t0 + at, window);
+        assert_eq!((s.packets, s.bytes), (100, 2000));
+        assert!((s.packets_per_sec - 50.0).abs() <= 1.0, "{}", s.packets_per_sec);
+        assert!((s.bytes_per_sec - 1000.0).abs() <= 20.0, "{}", s.bytes_per_sec);
+        assert!((s.mean_interval_ms.unwrap() - 20.0).abs() < 0.5);
+        assert_eq!(s.min_interval_ms.map(f64::round), Some(10.0));
+        assert_eq!(s.max_interval_ms.map(f64::round), Some(30.0));
+        // 相邻间隔差恒为 20 ms，平滑后趋近 20
+        assert!(s.jitter_ms > 19.0 && s.jitter_ms <= 20.0, "{}", s.jitter_ms);
+
+        // 不足一个窗口时按实际时长计算速率
+        let mut short = Throughput::new(t0, None);
+        short.on_packet(t0 + Duration::from_millis(100), &[0u8; 4], window);
+        let s = short.stats(&info(), t0 + Duration::from_millis(500), window);
+        assert!((s.packets_per_sec - 2.0).abs() < 1e-9);
+        assert_eq!(s.mean_interval_ms, None);
+    }
+
+    #[test]
+    fn sequence_gaps_with_wraparound() {
+        let t0 = Instant::now();
+        let window = Duration::from_secs(1);
+        let mut t = Throughput::new(t0, Some("0:u8".parse().unwrap()));
+        for seq in [250u8, 251, 253, 253, 252, 255, 2, 3] {
+            t.on_packet(t0, &[seq, 0xAA], window);
+        }
+        t.on_packet(t0, &[], window);
+        let s = t.stats(&info(), t0, window);
+        // 251 -> 253 丢 1，253 -> 255 丢 1，255 -> 2 回绕丢 2
+        assert_eq!((s.gaps, s.lost_packets), (3, 4));
+        assert_eq!((s.duplicates, s.out_of_order, s.unreadable), (1, 1, 1));
+
+        // 链路恢复后不把断开期间算作丢包
+        t.link_restored();
+        t.on_packet(t0, &[100], window);
+        assert_eq!(t.stats(&info(), t0, window).lost_packets, 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resubscribe_replaces_and_reconnect_restores() {
+        let backend = SimulatedBackend::new();
+        let adapter = backend.add_adapter("sim0", "00:00:00:00:00:01");
+        adapter.set_advertising_interval(Duration::from_secs(3600));
+        let hrm = adapter.add_device(
+            SimDevice::new("HRM", "AA:BB:CC:DD:EE:01").gatt_service(
+                SimService::new("180D").characteristic(
+                    SimCharacteristic::new("2A37").notify().periodic(Duration::from_millis(10), vec![vec![0x00, 60]]),
+                ),
+            ),
+        );
+        let state = AppState::new(Arc::new(backend));
+        let central = state.backend.adapters().unwrap().remove(0);
+        central.scan_start().unwrap();
+        state.registry.lock().unwrap().observe("sim0", central.scan_results().unwrap().remove(0));
+        let policy = ReconnectPolicy { enabled: true, initial_delay_ms: 10, ..Default::default() };
+        connection::set_reconnect_policy(&state, None, policy).await.unwrap();
+        let sink: Arc<dyn EventSink> = Arc::new(NullSink);
+
+        device::connect_device(&state, sink.clone(), "HRM".to_string(), None).await.unwrap();
+        gatt::notify_characteristic(&state, sink.clone(), "HRM".into(), "180D".into(), "2A37".into()).await.unwrap();
+        gatt::notify_characteristic(&state, sink.clone(), "HRM".into(), "180D".into(), "2A37".into()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let list = list_subscriptions(&state, None).await.unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].active && list[0].messages > 0);
+        assert_eq!(list[0].last_value.as_deref(), Some("003C"));
+
+        hrm.drop_link();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let list = list_subscriptions(&state, Some("HRM".into())).await.unwrap();
+        assert_eq!(list[0].restored, 1);
+        assert!(list[0].active);
+        let before = list[0].messages;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(list_subscriptions(&state, None).await.unwrap()[0].messages > before);
+
+        device::disconnect_device(&state, sink, "HRM".to_string()).await.unwrap();
+        assert!(list_subscriptions(&state, None).await.unwrap().is_empty());
+    }
+}
@@ -10,7 +10,7 @@ import BleService from './services/bleService';
 import ScanControls from './components/ScanControls';
 import AdapterInfo from './components/AdapterInfo';
 import DeviceCard from './components/DeviceCard';
-import ScanFilter, { FilterOptions } from './components/ScanFilter';
+import ScanFilter, { FilterOptions, toScanFilterSpec } from './components/ScanFilter';
 import DeviceDetails from './components/DeviceDetails';
 import WindowService from './services/windowService';
 
@@ -81,12 +81,23 @@ function App() {
     } catch {}
   }, [continuous]);
 
+  // 将可下推的过滤条件同步到后端，扫描中修改立即生效，减少高密度环境下的事件量
+  useEffect(() => {
+    BleService.setScanFilter(toScanFilterSpec(filters)).catch((error) => {
+      console.error('Failed to set scan filter:', error);
+    });
+  }, [filters.name, filters.minRssi, filters.connectable]);
+
   // 设置事件监听器
   useEffect(() => {
     let deviceDiscoveredUnlisten: UnlistenFn;
+    let devicesUpdatedUnlisten: UnlistenFn;
   let scanCompletedUnlisten: UnlistenFn;
     let scanErrorUnlisten: UnlistenFn;
   let connChangedUnlisten: UnlistenFn;
+  let pairingChangedUnlisten: UnlistenFn;
+  let deviceStaleUnlisten: UnlistenFn;
+  let deviceLostUnlisten: UnlistenFn;
 
     const setupEventListeners = async () => {
       // 启动定时刷新，将缓冲的设备更新批量合并到状态
@@ -103,8 +114,11 @@ function App() {
               }
               const existing = map.get(dev.identifier);
               if (existing) {
-                // 仅更新 RSSI，其他字段保持不变，避免卡片重排/抖动
-                existing.rssi = dev.rssi;
+                // 仅更新 RSSI 与出现时间，其他字段保持不变，避免卡片重排/抖动
+                // （差异更新只携带变化的字段，未携带的保持原值）
+                if (dev.rssi !== undefined) existing.rssi = dev.rssi;
+                if (dev.last_seen !== undefined) existing.last_seen = dev.last_seen;
+                existing.stale = false;
                 // 若初次为空而新数据有值，则一次性补齐，避免“很多时候不显示”
                 const existedManuEmpty = !existing.manufacturer_data || Object.keys(existing.manufacturer_data).length === 0;
                 const newManuHas = dev.manufacturer_data && Object.keys(dev.manufacturer_data).length > 0;
@@ -116,7 +130,10 @@ function App() {
                 if (existedSvcEmpty && newSvcHas) {
                   existing.services = dev.services;
                 }
-              } else {
+                if (dev.service_data !== undefined) existing.service_data = dev.service_data;
+                if (dev.decoded !== undefined) existing.decoded = dev.decoded;
+              } else if (dev.address !== undefined) {
+                // 仅有差异而没有完整记录的设备（已被移除）不加入列表
                 map.set(dev.identifier, dev);
               }
             }
@@ -130,6 +147,17 @@ function App() {
         bufferedDevicesRef.current.set(device.identifier, device);
       });
 
+      // 监听批量设备更新：新设备整条缓存，差异合并到缓存中的同一设备
+      devicesUpdatedUnlisten = await BleService.onDevicesUpdated(({ added, updated }) => {
+        const buffered = bufferedDevicesRef.current;
+        for (const device of added) {
+          buffered.set(device.identifier, device);
+        }
+        for (const { identifier, changes } of updated) {
+          buffered.set(identifier, { ...buffered.get(identifier), ...changes, identifier } as BleDevice);
+        }
+      });
+
       // 监听扫描完成事件
       scanCompletedUnlisten = await BleService.onScanCompleted(() => {
         setScanStatus(ScanStatus.Completed);
@@ -147,8 +175,22 @@ function App() {
       });
 
       // 监听跨窗口连接状态变化，立即同步设备状态
-      connChangedUnlisten = await BleService.onDeviceConnectionChanged(({ deviceId, paired }) => {
-        setDevices((prev) => prev.map(d => d.identifier === deviceId ? { ...d, paired } : d));
+      connChangedUnlisten = await BleService.onDeviceConnectionChanged(({ deviceId, connected }) => {
+        setDevices((prev) => prev.map(d => d.identifier === deviceId ? { ...d, connected } : d));
+      });
+
+      // 监听配对/绑定状态变化
+      pairingChangedUnlisten = await BleService.onDevicePairingChanged(({ deviceId, paired, bonded }) => {
+        setDevices((prev) => prev.map(d => d.identifier === deviceId ? { ...d, paired, bonded } : d));
+      });
+
+      // 监听设备老化：超时未出现的设备置灰，移出后从列表删除
+      deviceStaleUnlisten = await BleService.onDeviceStale(({ deviceId }) => {
+        setDevices((prev) => prev.map(d => d.identifier === deviceId ? { ...d, stale: true } : d));
+      });
+      deviceLostUnlisten = await BleService.onDeviceLost(({ deviceId }) => {
+        bufferedDevicesRef.current.delete(deviceId);
+        setDevices((prev) => prev.filter(d => d.identifier !== deviceId));
       });
     };
 
@@ -159,6 +201,9 @@ function App() {
       if (deviceDiscoveredUnlisten) {
         deviceDiscoveredUnlisten();
       }
+      if (devicesUpdatedUnlisten) {
+        devicesUpdatedUnlisten();
+      }
       if (scanCompletedUnlisten) {
         scanCompletedUnlisten();
       }
@@ -168,6 +213,15 @@ function App() {
       if (connChangedUnlisten) {
         connChangedUnlisten();
       }
+      if (pairingChangedUnlisten) {
+        pairingChangedUnlisten();
+      }
+      if (deviceStaleUnlisten) {
+        deviceStaleUnlisten();
+      }
+      if (deviceLostUnlisten) {
+        deviceLostUnlisten();
+      }
       if (flushIntervalRef.current != null) {
         window.clearInterval(flushIntervalRef.current);
         flushIntervalRef.current = null;
@@ -242,7 +296,7 @@ function App() {
         setDevices((prevDevices) =>
           prevDevices.map((device) =>
             device.identifier === deviceId
-              ? { ...device, paired: isConnected }
+              ? { ...device, connected: isConnected }
               : device
           )
         );
@@ -252,7 +306,7 @@ function App() {
           const connectedDevice = devices.find(device => device.identifier === deviceId);
           if (connectedDevice) {
             try {
-              await WindowService.openDeviceWindow({ ...connectedDevice, paired: true });
+              await WindowService.openDeviceWindow({ ...connectedDevice, connected: true });
             } catch (error) {
               console.error('Failed to open device window:', error);
             }
@@ -267,7 +321,7 @@ function App() {
         setDevices((prevDevices) =>
           prevDevices.map((device) =>
             device.identifier === deviceId
-              ? { ...device, paired: true }
+              ? { ...device, connected: true }
               : device
           )
         );
@@ -275,7 +329,7 @@ function App() {
         const connectedDevice = devices.find(device => device.identifier === deviceId);
         if (connectedDevice) {
           try {
-            await WindowService.openDeviceWindow({ ...connectedDevice, paired: true });
+            await WindowService.openDeviceWindow({ ...connectedDevice, connected: true });
           } catch (error) {
             console.error('Failed to open device window:', error);
           }
@@ -319,7 +373,7 @@ function App() {
         setDevices((prevDevices) =>
           prevDevices.map((device) =>
             device.identifier === deviceId
-              ? { ...device, paired: isConnected }
+              ? { ...device, connected: isConnected }
               : device
           )
         );
@@ -335,7 +389,7 @@ function App() {
         setDevices((prevDevices) =>
           prevDevices.map((device) =>
             device.identifier === deviceId
-              ? { ...device, paired: false }
+              ? { ...device, connected: false }
               : device
           )
         );
@@ -1,6 +1,6 @@
 import React, { useState } from 'react';
 import { useTranslation } from 'react-i18next';
-import { BleDevice } from '../types/ble';
+import { Beacon, BleDevice } from '../types/ble';
 import { 
   Smartphone, 
   ChevronDown, 
@@ -18,6 +18,26 @@ import {
 import { formatMacAddress } from '../utils/bleUtils';
 import { formatHexData } from '../utils/advDataParser';
 
+// 信标的简短描述
+const beaconLabel = (beacon: Beacon): string => {
+  switch (beacon.type) {
+    case 'ibeacon':
+      return `iBeacon ${beacon.major}/${beacon.minor}`;
+    case 'altbeacon':
+      return `AltBeacon ${beacon.major}/${beacon.minor}`;
+    case 'eddystone': {
+      const parts = [
+        beacon.url,
+        beacon.uid && `${beacon.uid.namespace}:${beacon.uid.instance}`,
+        beacon.eid && `EID ${beacon.eid}`,
+        beacon.tlm?.battery_mv != null ? `${beacon.tlm.battery_mv} mV` : null,
+        beacon.tlm?.temperature != null ? `${beacon.tlm.temperature.toFixed(1)} °C` : null,
+      ].filter(Boolean);
+      return `Eddystone ${parts.join(' · ')}`;
+    }
+  }
+};
+
 interface DeviceCardProps {
   device: BleDevice;
   onConnect: (deviceId: string) => void;
@@ -166,7 +186,7 @@ const DeviceCard: React.FC<DeviceCardProps> = ({
   );
 
   return (
-    <div className="relative group max-w-lg">
+    <div className={`relative group max-w-lg transition-opacity ${device.stale ? 'opacity-50' : ''}`}>
       {/* 背景光晕效果 */}
       <div className="absolute -inset-1 bg-gradient-to-r from-purple-600 to-pink-600 rounded-2xl blur-lg opacity-25 group-hover:opacity-75 transition duration-1000 group-hover:duration-200 animate-pulse"></div>
       
@@ -247,6 +267,19 @@ const DeviceCard: React.FC<DeviceCardProps> = ({
             </span>
           </div>
 
+          {/* 信标 */}
+          {device.beacon && (
+            <div className="flex items-center justify-between">
+              <div className="flex items-center gap-2">
+                <Radio className="w-4 h-4 text-cyan-400" />
+                <span className="text-gray-600 dark:text-gray-400 text-sm transition-colors">{t('beacon')}</span>
+              </div>
+              <span className="text-gray-900 dark:text-white text-sm font-mono truncate max-w-[60%] transition-colors" title={beaconLabel(device.beacon)}>
+                {beaconLabel(device.beacon)}
+              </span>
+            </div>
+          )}
+
           {/* 连接状态 */}
           <div className="flex items-center justify-between">
             <div className="flex items-center gap-2">
@@ -255,12 +288,12 @@ const DeviceCard: React.FC<DeviceCardProps> = ({
             </div>
             <div className="flex items-center gap-2">
               <div className={`w-2 h-2 rounded-full ${
-                device.paired ? 'bg-green-400' : 'bg-gray-500'
+                device.connected ? 'bg-green-400' : 'bg-gray-500'
               }`} />
               <span className={`text-sm transition-colors ${
-                device.paired ? 'text-green-600 dark:text-green-400' : 'text-gray-600 dark:text-gray-400'
+                device.connected ? 'text-green-600 dark:text-green-400' : 'text-gray-600 dark:text-gray-400'
               }`}>
-                {device.paired ? t('connected') : t('disconnected')}
+                {device.connected ? t('connected') : t('disconnected')}
               </span>
             </div>
           </div>
@@ -314,7 +347,7 @@ const DeviceCard: React.FC<DeviceCardProps> = ({
       {/* 连接按钮 */}
       <button
         onClick={() => {
-          if (device.paired) {
+          if (device.connected) {
             onDisconnect(device.identifier);
           } else {
             onConnect(device.identifier);
@@ -326,7 +359,7 @@ const DeviceCard: React.FC<DeviceCardProps> = ({
             ? 'bg-gradient-to-r from-gray-300/80 to-gray-400/80 text-gray-600 cursor-not-allowed backdrop-blur-sm border border-gray-300/50'
             : !device.connectable
             ? 'bg-gradient-to-r from-gray-200/60 to-gray-300/60 dark:from-gray-700/60 dark:to-gray-600/60 text-gray-500 dark:text-gray-400 cursor-not-allowed border border-gray-300/50 dark:border-gray-600/50 backdrop-blur-sm'
-            : device.paired
+            : device.connected
             ? 'bg-gradient-to-r from-red-500/90 to-rose-600/90 hover:from-red-400 hover:to-rose-500 text-white shadow-2xl shadow-red-500/30 backdrop-blur-sm border border-red-400/50'
             : 'bg-gradient-to-r from-blue-500/90 to-indigo-600/90 hover:from-blue-400 hover:to-indigo-500 text-white shadow-2xl shadow-blue-500/30 backdrop-blur-sm border border-blue-400/50'
         } ${
@@ -349,7 +382,7 @@ const DeviceCard: React.FC<DeviceCardProps> = ({
               <Link2 className="w-5 h-5 opacity-50" />
               {t('actions.notConnectable')}
             </>
-          ) : device.paired ? (
+          ) : device.connected ? (
             <>
               <Link2 className="w-5 h-5" />
               {t('actions.disconnect')}
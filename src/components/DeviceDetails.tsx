@@ -23,12 +23,14 @@ import {
   Trash2,
   Wifi,
   Link2Off,
+  ShieldCheck,
+  ShieldOff,
   Binary,
   Zap,
   Battery
 } from 'lucide-react';
-import { BleDevice } from '../types/ble';
-import { formatUuidForDisplay, getCharacteristicName } from '../utils/bleUtils';
+import { BleDevice, DecodedValue, PayloadEncoding, SecurityRequirements } from '../types/ble';
+import { formatUuidForDisplay, getCharacteristicName, decodedValueEntries } from '../utils/bleUtils';
 import { getManufacturerName, formatManufacturerData } from '../utils/manufacturerUtils';
 import { parseAdvData, formatHexData } from '../utils/advDataParser';
 import BleService from '../services/bleService';
@@ -46,11 +48,22 @@ interface CopyState {
   [key: string]: boolean;
 }
 
+interface Descriptor {
+  uuid: string;
+  name: string;
+  value?: string | null;
+  isReading?: boolean;
+}
+
 interface Characteristic {
   uuid: string;
   name: string;
+  userDescription?: string | null;
   properties: string[];
+  security?: SecurityRequirements | null;
+  descriptors: Descriptor[];
   value?: string;
+  decoded?: DecodedValue | null; // 标准特征的解码结果
   isReading?: boolean;
   isWriting?: boolean;
   isNotifying?: boolean;
@@ -118,16 +131,24 @@ const generateDeviceName = (device: BleDevice): string => {
   return 'BLE设备';
 };
 
+// 写入框可选的编码，完整列表与 struct 模板见后端 payload 模块
+const WRITE_ENCODINGS: PayloadEncoding[] = ['hex', 'utf8', 'base64', 'u8', 'i8', 'u16le', 'u16be', 'i16le', 'u32le', 'i32le', 'f32le', 'f64le'];
+
 const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClose, onDisconnect, isStandaloneWindow = false }) => {
   const { t } = useTranslation('device');
   const [services, setServices] = useState<Service[]>([]);
   const [writeValue, setWriteValue] = useState<string>('');
+  // 写入编码，auto 表示由后端按 HEX/文本猜测
+  const [writeEncoding, setWriteEncoding] = useState<PayloadEncoding>('auto');
   const [selectedCharacteristic, setSelectedCharacteristic] = useState<string>('');
   // 每个特征值单独的 HEX/TEXT 视图控制
   const [hexViewByChar, setHexViewByChar] = useState<Record<string, boolean>>({});
   // 复制状态管理
   const [copyStates, setCopyStates] = useState<CopyState>({});
   const [disconnecting, setDisconnecting] = useState<boolean>(false);
+  // 配对/绑定状态（独立窗口中 device 不会随事件更新，这里单独跟踪）
+  const [pairing, setPairing] = useState<{ paired: boolean; bonded: boolean }>({ paired: !!device.paired, bonded: !!device.bonded });
+  const [pairBusy, setPairBusy] = useState<boolean>(false);
   // 广告数据展开状态
   const [advDataExpanded, setAdvDataExpanded] = useState<boolean>(false);
   const [manufacturerExpanded, setManufacturerExpanded] = useState<boolean>(false);
@@ -149,6 +170,30 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
     } catch {}
   };
 
+  useEffect(() => {
+    setPairing({ paired: !!device.paired, bonded: !!device.bonded });
+    const unlistenPromise = BleService.onDevicePairingChanged((p) => {
+      if (p.deviceId === device.identifier) setPairing({ paired: p.paired, bonded: p.bonded });
+    });
+    return () => { unlistenPromise.then(unlisten => unlisten()); };
+  }, [device.identifier, device.paired, device.bonded]);
+
+  const togglePairing = async () => {
+    if (pairBusy) return;
+    setPairBusy(true);
+    try {
+      if (pairing.bonded || pairing.paired) {
+        await BleService.unpairDevice(device.identifier);
+      } else {
+        await BleService.pairDevice(device.identifier);
+      }
+    } catch (e) {
+      alert(`${t('details.pairFailed')}: ${e}`);
+    } finally {
+      setPairBusy(false);
+    }
+  };
+
   // 当从可见变为不可见时，主动退订所有订阅，避免资源泄漏
   useEffect(() => {
     if (!isVisible) {
@@ -173,6 +218,7 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
           characteristics: s.characteristics.map(c => c.uuid === p.characteristicUuid ? {
             ...c,
             notifications: [p.value, ...(c.notifications || [])].slice(0, 100),
+            decoded: p.decoded ?? c.decoded,
             isNotifying: p.kind === 'notify' ? true : c.isNotifying,
             isIndicating: p.kind === 'indicate' ? true : c.isIndicating,
           } : c)
@@ -186,13 +232,20 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
           // 组装服务与特征，并优先将 Generic Access (0x1800) 置顶
           const mapped: Service[] = raw.map(s => ({
             uuid: s.uuid,
-            // 使用可读的服务名称
-            name: formatUuidForDisplay(s.uuid).name,
+            // 使用可读的服务名称（优先后端解析的 SIG 名称）
+            name: s.name || formatUuidForDisplay(s.uuid).name,
             isExpanded: true,
             characteristics: s.characteristics.map(c => ({
               uuid: c.uuid,
-              // 使用可读的特征名称
-              name: getCharacteristicName(c.uuid),
+              // 使用可读的特征名称（优先后端解析的 SIG 名称，其次设备提供的用户描述）
+              name: c.name || c.user_description || getCharacteristicName(c.uuid),
+              userDescription: c.user_description,
+              security: c.security,
+              descriptors: (c.descriptors || []).map(d => ({
+                uuid: d.uuid,
+                name: d.name || formatUuidForDisplay(d.uuid).display,
+                value: d.value,
+              })),
               properties: c.properties.map(p => {
                 // 映射属性文案
                 switch (p) {
@@ -201,6 +254,10 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                   case 'WriteCommand': return 'Write (Cmd)';
                   case 'Notify': return 'Notify';
                   case 'Indicate': return 'Indicate';
+                  case 'AuthenticatedSignedWrites': return 'Signed Write';
+                  case 'ExtendedProperties': return 'Ext Props';
+                  case 'ReliableWrite': return 'Reliable Write';
+                  case 'WritableAuxiliaries': return 'Writable Aux';
                   default: return p;
                 }
               }),
@@ -237,10 +294,10 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
       characteristics: s.characteristics.map(c => c.uuid === charUuid ? { ...c, isReading: true } : c)
     } : s));
     try {
-      const hex = await BleService.readCharacteristic(device.identifier, serviceUuid, charUuid);
+      const { value, decoded } = await BleService.readCharacteristic(device.identifier, serviceUuid, charUuid);
       setServices(prev => prev.map(s => s.uuid === serviceUuid ? {
         ...s,
-        characteristics: s.characteristics.map(c => c.uuid === charUuid ? { ...c, value: hex } : c)
+        characteristics: s.characteristics.map(c => c.uuid === charUuid ? { ...c, value, decoded } : c)
       } : s));
     } catch (e) {
       console.error('读取特征值失败:', e);
@@ -252,6 +309,28 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
     }
   };
 
+  const updateDescriptor = (serviceUuid: string, charUuid: string, descUuid: string, patch: Partial<Descriptor>) => {
+    setServices(prev => prev.map(s => s.uuid === serviceUuid ? {
+      ...s,
+      characteristics: s.characteristics.map(c => c.uuid === charUuid ? {
+        ...c,
+        descriptors: c.descriptors.map(d => d.uuid === descUuid ? { ...d, ...patch } : d)
+      } : c)
+    } : s));
+  };
+
+  const handleReadDescriptor = async (serviceUuid: string, charUuid: string, descUuid: string) => {
+    updateDescriptor(serviceUuid, charUuid, descUuid, { isReading: true });
+    try {
+      const value = await BleService.readDescriptor(device.identifier, serviceUuid, charUuid, descUuid);
+      updateDescriptor(serviceUuid, charUuid, descUuid, { value });
+    } catch (e) {
+      console.error('读取描述符失败:', e);
+    } finally {
+      updateDescriptor(serviceUuid, charUuid, descUuid, { isReading: false });
+    }
+  };
+
   const handleWrite = async (serviceUuid: string, charUuid: string, value: string) => {
     if (!value.trim()) return;
     setServices(prev => prev.map(s => s.uuid === serviceUuid ? {
@@ -259,8 +338,7 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
       characteristics: s.characteristics.map(c => c.uuid === charUuid ? { ...c, isWriting: true } : c)
     } : s));
     try {
-      // 依据输入是否以 0x 开头选择 hex，否则视为文本，后端已兼容
-      await BleService.writeCharacteristic(device.identifier, serviceUuid, charUuid, value, 'request');
+      await BleService.writeCharacteristic(device.identifier, serviceUuid, charUuid, value, 'request', writeEncoding);
     } catch (e) {
       console.error('写入失败:', e);
     } finally {
@@ -297,11 +375,12 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
 
     return {
       companyId: pillId,
-      companyName: formatted.companyName || getManufacturerName(companyIdRaw),
+      companyName: device.manufacturer_names?.[companyIdRaw] || formatted.companyName || getManufacturerName(companyIdRaw),
       data: formatted.dataHex,
       dataType: formatted.interpretation || 'Manufacturer Data',
       totalEntries: entries.length,
       dataLength: formatted.dataLength,
+      decoded: device.decoded?.find(d => d.company_id === parseInt(companyIdRaw, 16)),
     };
   };
 
@@ -330,8 +409,10 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
 
       return {
         uuid,
-        name: getServiceName(uuid),
-        shortId: uuid.substring(0, 4)
+        name: device.service_names?.[service] || getServiceName(uuid),
+        standard: uuid.endsWith('-0000-1000-8000-00805F9B34FB') || uuid.length <= 8,
+        shortId: uuid.substring(0, 4),
+        data: device.service_data?.[uuid]
       };
     });
   };
@@ -539,18 +620,33 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                 </div>
                 <div className="flex items-center gap-1">
                   <Activity className={`w-4 h-4 ${
-                    device.paired ? 'text-green-400' : 'text-gray-400'
+                    device.connected ? 'text-green-400' : 'text-gray-400'
                   }`} />
                   <span className={`text-sm transition-colors ${
-                    device.paired ? 'text-green-600 dark:text-green-400' : 'text-gray-600 dark:text-gray-400'
+                    device.connected ? 'text-green-600 dark:text-green-400' : 'text-gray-600 dark:text-gray-400'
                   }`}>
-                    {device.paired ? t('connected') : t('disconnected')}
+                    {device.connected ? t('connected') : t('disconnected')}
                   </span>
                 </div>
+                {(pairing.paired || pairing.bonded) && (
+                  <div className="flex items-center gap-1 text-blue-600 dark:text-blue-400">
+                    <ShieldCheck className="w-4 h-4" />
+                    <span className="text-sm">{pairing.bonded ? t('bonded') : t('paired')}</span>
+                  </div>
+                )}
               </div>
             </div>
           </div>
           <div className="flex items-center gap-2">
+            {/* 配对 / 解除配对 */}
+            <button
+              onClick={togglePairing}
+              className={`p-3 rounded-xl border transition-all duration-200 text-blue-600 dark:text-blue-300 bg-blue-100 dark:bg-blue-600/20 hover:bg-blue-200 dark:hover:bg-blue-600/30 border-blue-300 dark:border-blue-500/30 ${pairBusy ? 'opacity-60 cursor-not-allowed' : ''}`}
+              title={pairing.paired || pairing.bonded ? t('details.unpair') : t('details.pair')}
+            >
+              {pairing.paired || pairing.bonded ? <ShieldOff className="w-5 h-5" /> : <ShieldCheck className="w-5 h-5" />}
+            </button>
+
             {/* 顶部小型断开按钮：总是显示（若提供 onDisconnect） */}
             {onDisconnect && (
               <button
@@ -567,7 +663,7 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                   }
                 }}
                 className={`p-3 rounded-xl border transition-all duration-200 ${
-                  device.paired
+                  device.connected
                     ? 'text-red-600 dark:text-red-300 hover:text-white bg-red-100 dark:bg-red-600/20 hover:bg-red-200 dark:hover:bg-red-600/30 border-red-300 dark:border-red-500/30'
                     : 'text-gray-600 dark:text-slate-300 hover:text-white bg-gray-100 dark:bg-slate-600/30 hover:bg-gray-200 dark:hover:bg-slate-600/40 border-gray-300 dark:border-slate-500/30'
                 } ${disconnecting ? 'opacity-60 cursor-not-allowed' : ''}`}
@@ -654,6 +750,23 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                               {manufacturerInfo.data}
                             </div>
                           </div>
+                          {manufacturerInfo.decoded && (
+                            <div className="bg-slate-800/60 rounded-lg p-3 border border-slate-600/20">
+                              <div className="text-slate-300 text-sm mb-2">
+                                {t('manufacturer.decoded', { decoder: manufacturerInfo.decoded.decoder })}
+                              </div>
+                              <div className="space-y-1">
+                                {manufacturerInfo.decoded.fields.map((field) => (
+                                  <div key={field.name} className="flex items-center justify-between text-sm">
+                                    <span className="text-slate-400">{field.name}</span>
+                                    <span className="text-slate-100 font-mono">
+                                      {String(field.value)}{field.unit ? ` ${field.unit}` : ''}
+                                    </span>
+                                  </div>
+                                ))}
+                              </div>
+                            </div>
+                          )}
                         </div>
                       )}
                     </div>
@@ -695,7 +808,7 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                           <div key={index} className="bg-gradient-to-r from-slate-800/60 to-slate-700/60 rounded-xl p-4 border border-slate-600/20">
                             <div className="flex items-center justify-between mb-3">
                               <div className="bg-gradient-to-r from-blue-500 to-blue-600 text-white text-xs px-3 py-1 rounded-lg font-semibold shadow">
-                                {service.name.includes('Standard') || service.name.includes('Generic') ? t('services.std') : t('services.custom')}
+                                {service.standard ? t('services.std') : t('services.custom')}
                               </div>
                               <CopyButton
                                 value={service.uuid}
@@ -718,6 +831,14 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                                 {service.uuid}
                               </div>
                             </div>
+                            {service.data && (
+                              <div className="bg-slate-800/60 rounded-lg p-2 border border-slate-600/20 mt-2">
+                                <div className="text-slate-300 text-xs mb-1">{t('services.data')}:</div>
+                                <div className="text-slate-100 font-mono text-sm break-all">
+                                  {formatHexData(service.data)}
+                                </div>
+                              </div>
+                            )}
                           </div>
                         ))}
                       </div>
@@ -1060,6 +1181,8 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                                         case 'write (cmd)': return 'bg-blue-500/20 text-blue-300 border-blue-500/30';
                                         case 'notify': return 'bg-yellow-500/20 text-yellow-300 border-yellow-500/30';
                                         case 'indicate': return 'bg-orange-500/20 text-orange-300 border-orange-500/30';
+                                        case 'signed write':
+                                        case 'reliable write': return 'bg-cyan-500/20 text-cyan-300 border-cyan-500/30';
                                         default: return 'bg-slate-500/20 text-slate-300 border-slate-500/30';
                                       }
                                     };
@@ -1072,10 +1195,48 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                                       </span>
                                     );
                                   })}
+                                  {char.security && (char.security.read !== 'none' || char.security.write !== 'none') && (
+                                    <span
+                                      className="flex items-center gap-1 text-sm px-3 py-1.5 rounded-lg border font-semibold whitespace-nowrap bg-red-500/20 text-red-300 border-red-500/30"
+                                    >
+                                      <Shield className="w-3 h-3" />
+                                      {t('details.security', { read: char.security.read, write: char.security.write })}
+                                    </span>
+                                  )}
                                 </div>
                               </div>
                             </div>
 
+                            {/* Descriptors */}
+                            {char.descriptors.length > 0 && (
+                              <div className="space-y-2">
+                                <span className="text-slate-300 text-sm font-medium">{t('details.descriptors')}</span>
+                                <div className="bg-slate-800/60 rounded-lg border border-slate-600/20">
+                                  {char.descriptors.map((desc) => (
+                                    <div
+                                      key={desc.uuid}
+                                      className="flex items-center justify-between gap-3 p-3 text-sm border-b border-slate-600/20 last:border-b-0"
+                                    >
+                                      <div className="min-w-0">
+                                        <div className="text-slate-200">{desc.name}</div>
+                                        <div className="text-green-400 font-mono break-all">
+                                          {desc.value ? `0x${desc.value}` : t('details.noData')}
+                                        </div>
+                                      </div>
+                                      <button
+                                        onClick={() => handleReadDescriptor(service.uuid, char.uuid, desc.uuid)}
+                                        disabled={desc.isReading}
+                                        className="flex items-center gap-1 text-xs px-3 py-1.5 bg-green-500/20 hover:bg-green-500/30 text-green-300 rounded-lg border border-green-500/30 transition-all duration-200 flex-shrink-0"
+                                      >
+                                        {desc.isReading ? <RefreshCw className="w-3 h-3 animate-spin" /> : <Download className="w-3 h-3" />}
+                                        {t('details.read')}
+                                      </button>
+                                    </div>
+                                  ))}
+                                </div>
+                              </div>
+                            )}
+
                             {/* Characteristic Value */}
                             <div className="space-y-3">
                               <div className="flex items-center justify-between">
@@ -1121,6 +1282,19 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                                     );
                                   })()}
                                 </div>
+                                {char.decoded && (
+                                  <div className="mt-3 pt-3 border-t border-slate-600/20 space-y-1">
+                                    <div className="text-slate-300 text-xs mb-1">
+                                      {t('details.decodedValue', { type: char.decoded.type })}
+                                    </div>
+                                    {decodedValueEntries(char.decoded).map(([key, v]) => (
+                                      <div key={key} className="flex items-center justify-between text-sm">
+                                        <span className="text-slate-400">{key}</span>
+                                        <span className="text-slate-100 font-mono">{v}</span>
+                                      </div>
+                                    ))}
+                                  </div>
+                                )}
                               </div>
                             </div>
 
@@ -1204,6 +1378,17 @@ const DeviceDetails: React.FC<DeviceDetailsProps> = ({ device, isVisible, onClos
                                     placeholder={t('details.writePlaceholder')}
                                     className="flex-1 px-4 py-2 text-sm bg-slate-800/60 border border-slate-600/50 rounded-xl text-white placeholder-slate-400 focus:outline-none focus:ring-2 focus:ring-blue-500/50 focus:border-blue-500/50 transition-all duration-200"
                                   />
+                                  <select
+                                    value={writeEncoding}
+                                    onChange={(e) => setWriteEncoding(e.target.value)}
+                                    title={t('details.encoding')}
+                                    className="px-2 py-2 text-sm bg-slate-800/60 border border-slate-600/50 rounded-xl text-white focus:outline-none focus:ring-2 focus:ring-blue-500/50"
+                                  >
+                                    <option value="auto">{t('details.encodingAuto')}</option>
+                                    {WRITE_ENCODINGS.map(e => (
+                                      <option key={e} value={e}>{e}</option>
+                                    ))}
+                                  </select>
                                   <button
                                     onClick={() => handleWrite(service.uuid, char.uuid, writeValue)}
                                     disabled={char.isWriting || !writeValue.trim()}
@@ -1,6 +1,7 @@
 import React, { useState } from 'react';
 import { useTranslation } from 'react-i18next';
 import { Filter, X, ChevronDown, ChevronUp } from 'lucide-react';
+import { ScanFilterSpec } from '../types/ble';
 
 export interface FilterOptions {
   name: string;
@@ -10,6 +11,21 @@ export interface FilterOptions {
   connectable: 'all' | 'connectable' | 'non-connectable';
 }
 
+// 可以下推到后端的条件（名称、最低 RSSI、仅可连接），其余条件仍在前端过滤
+export const toScanFilterSpec = (filters: FilterOptions): ScanFilterSpec | null => {
+  const spec: ScanFilterSpec = {};
+  if (filters.name.trim()) {
+    spec.name = `(?i)${filters.name.trim().replace(/[.*+?^${}()|[\]\\]/g, '\\$&')}`;
+  }
+  if (filters.minRssi > -100) {
+    spec.min_rssi = filters.minRssi;
+  }
+  if (filters.connectable === 'connectable') {
+    spec.connectable_only = true;
+  }
+  return Object.keys(spec).length > 0 ? spec : null;
+};
+
 interface ScanFilterProps {
   filters: FilterOptions;
   onFiltersChange: (filters: FilterOptions) => void;
@@ -1,6 +1,46 @@
 import { invoke } from '@tauri-apps/api/tauri';
 import { listen } from '@tauri-apps/api/event';
-import { BleAdapter, BleDevice } from '../types/ble';
+import {
+  AgingPolicy,
+  AssignedNumbers,
+  BleAdapter,
+  BleDevice,
+  CharacteristicValue,
+  CharacteristicValueEvent,
+  CoalesceSettings,
+  ConnectionChangedEvent,
+  ConnectionInfo,
+  DecoderInfo,
+  DeviceAgingEvent,
+  DeviceHistory,
+  DevicesUpdatedEvent,
+  EventStats,
+  GattService,
+  GattSnapshot,
+  HistoryDevice,
+  HistoryQuery,
+  LongWriteOptions,
+  LongWriteResult,
+  NotificationRecord,
+  PairingChangedEvent,
+  PayloadEncoding,
+  PruneSummary,
+  RecordingFormat,
+  RecordingStatus,
+  ReplayCompletedEvent,
+  ReconnectPolicy,
+  RssiHistory,
+  RssiSettings,
+  RssiSummary,
+  ScanFilterSpec,
+  SequenceField,
+  SnapshotDiff,
+  StatsSettings,
+  SubscriptionInfo,
+  SubscriptionRestoredEvent,
+  SubscriptionStats,
+  WriteProgressEvent,
+} from '../types/ble';
 
 export class BleService {
   // 获取蓝牙适配器
@@ -8,12 +48,17 @@ export class BleService {
     return await invoke('get_adapters');
   }
 
+  // 选择默认适配器
+  static async selectAdapter(adapterId: string): Promise<BleAdapter> {
+    return await invoke('select_adapter', { adapterId });
+  }
+
   // 获取设备的 GATT 服务与特征
-  static async getDeviceServices(deviceId: string): Promise<Array<{ uuid: string; characteristics: Array<{ uuid: string; properties: string[] }> }>> {
+  static async getDeviceServices(deviceId: string): Promise<GattService[]> {
     return await invoke('get_device_services', { deviceId });
   }
 
-  static async readCharacteristic(deviceId: string, serviceUuid: string, characteristicUuid: string): Promise<string> {
+  static async readCharacteristic(deviceId: string, serviceUuid: string, characteristicUuid: string): Promise<CharacteristicValue> {
     return await invoke('read_characteristic', { deviceId, serviceUuid, characteristicUuid });
   }
 
@@ -22,9 +67,21 @@ export class BleService {
     serviceUuid: string,
     characteristicUuid: string,
     data: string,
-    writeType: 'request' | 'command' = 'request'
+    writeType: 'request' | 'command' = 'request',
+    encoding?: PayloadEncoding
   ): Promise<void> {
-    return await invoke('write_characteristic', { deviceId, serviceUuid, characteristicUuid, data, writeType });
+    return await invoke('write_characteristic', { deviceId, serviceUuid, characteristicUuid, data, writeType, encoding });
+  }
+
+  // 按 MTU 分块写入长值，进度见 onWriteProgress
+  static async writeCharacteristicLong(
+    deviceId: string,
+    serviceUuid: string,
+    characteristicUuid: string,
+    data: string,
+    options?: LongWriteOptions
+  ): Promise<LongWriteResult> {
+    return await invoke('write_characteristic_long', { deviceId, serviceUuid, characteristicUuid, data, options });
   }
 
   static async notifyCharacteristic(deviceId: string, serviceUuid: string, characteristicUuid: string): Promise<void> {
@@ -39,29 +96,163 @@ export class BleService {
     return await invoke('unsubscribe_characteristic', { deviceId, serviceUuid, characteristicUuid });
   }
 
+  // 列出订阅（消息数、最后一个值等），重复订阅同一特征会替换旧订阅
+  static async listSubscriptions(deviceId?: string): Promise<SubscriptionInfo[]> {
+    return await invoke('list_subscriptions', { deviceId });
+  }
+
+  static async getSubscriptionStats(deviceId?: string): Promise<SubscriptionStats[]> {
+    return await invoke('get_subscription_stats', { deviceId });
+  }
+
+  // 设置序号字段后统计丢包、重复与乱序；传 null 关闭
+  static async setSubscriptionSequence(
+    deviceId: string,
+    serviceUuid: string,
+    characteristicUuid: string,
+    sequence: SequenceField | null
+  ): Promise<void> {
+    return await invoke('set_subscription_sequence', { deviceId, serviceUuid, characteristicUuid, sequence });
+  }
+
+  static async getSubscriptionStatsSettings(): Promise<StatsSettings> {
+    return await invoke('get_subscription_stats_settings');
+  }
+
+  static async setSubscriptionStatsSettings(settings: StatsSettings): Promise<void> {
+    return await invoke('set_subscription_stats_settings', { settings });
+  }
+
+  // 订阅的最近通知（环形缓冲区）
+  static async getNotifications(deviceId: string, serviceUuid: string, characteristicUuid: string, limit?: number): Promise<NotificationRecord[]> {
+    return await invoke('get_notifications', { deviceId, serviceUuid, characteristicUuid, limit });
+  }
+
+  static async clearNotifications(deviceId: string, serviceUuid: string, characteristicUuid: string): Promise<void> {
+    return await invoke('clear_notifications', { deviceId, serviceUuid, characteristicUuid });
+  }
+
+  static async setNotificationBufferSize(capacity: number): Promise<void> {
+    return await invoke('set_notification_buffer_size', { capacity });
+  }
+
+  // 录制通知到文件，format 缺省时按扩展名（.csv / .jsonl / .bin）选择
+  static async startRecording(path: string, format?: RecordingFormat, deviceId?: string): Promise<RecordingStatus> {
+    return await invoke('start_recording', { path, format, deviceId });
+  }
+
+  static async stopRecording(): Promise<RecordingStatus | null> {
+    return await invoke('stop_recording');
+  }
+
+  static async getRecordingStatus(): Promise<RecordingStatus | null> {
+    return await invoke('get_recording_status');
+  }
+
+  // 回放录制文件，事件通过 onCharacteristicValue 推送（replay = true）
+  static async replayRecording(path: string, speed = 1): Promise<number> {
+    return await invoke('replay_recording', { path, speed });
+  }
+
+  static async stopReplay(): Promise<boolean> {
+    return await invoke('stop_replay');
+  }
+
+  static async readDescriptor(deviceId: string, serviceUuid: string, characteristicUuid: string, descriptorUuid: string): Promise<string> {
+    return await invoke('read_descriptor', { deviceId, serviceUuid, characteristicUuid, descriptorUuid });
+  }
+
+  static async writeDescriptor(
+    deviceId: string,
+    serviceUuid: string,
+    characteristicUuid: string,
+    descriptorUuid: string,
+    data: string,
+    encoding?: PayloadEncoding
+  ): Promise<void> {
+    return await invoke('write_descriptor', { deviceId, serviceUuid, characteristicUuid, descriptorUuid, data, encoding });
+  }
+
+  static async takeGattSnapshot(deviceId: string, includeValues = false): Promise<GattSnapshot> {
+    return await invoke('take_gatt_snapshot', { deviceId, includeValues });
+  }
+
+  // 导出快照到文件，扩展名 .yaml/.yml 为 YAML，其余为 JSON
+  static async exportGattSnapshot(deviceId: string, path: string, includeValues = false): Promise<GattSnapshot> {
+    return await invoke('export_gatt_snapshot', { deviceId, path, includeValues });
+  }
+
+  static async loadGattSnapshot(path: string): Promise<GattSnapshot> {
+    return await invoke('load_gatt_snapshot', { path });
+  }
+
+  static async diffGattSnapshots(oldSnapshot: GattSnapshot, newSnapshot: GattSnapshot): Promise<SnapshotDiff> {
+    return await invoke('diff_gatt_snapshots', { old: oldSnapshot, new: newSnapshot });
+  }
+
+  static async diffGattSnapshotWithDevice(path: string, deviceId: string): Promise<SnapshotDiff> {
+    return await invoke('diff_gatt_snapshot_with_device', { path, deviceId });
+  }
+
   static async getMtu(deviceId: string): Promise<number> {
     return await invoke('get_mtu', { deviceId });
   }
 
-  static onCharacteristicValue(callback: (payload: { deviceId: string; serviceUuid: string; characteristicUuid: string; value: string; kind: 'notify' | 'indicate' }) => void) {
+  static onCharacteristicValue(callback: (payload: CharacteristicValueEvent) => void) {
     return listen('characteristic-value', (event: any) => {
       callback(event.payload);
     });
   }
+
+  static onReplayCompleted(callback: (payload: ReplayCompletedEvent) => void) {
+    return listen('replay-completed', (event: any) => {
+      callback(event.payload);
+    });
+  }
+
+  // 设备重连后自动恢复订阅时触发，失败时 error 非空
+  static onSubscriptionRestored(callback: (payload: SubscriptionRestoredEvent) => void) {
+    return listen('subscription-restored', (event: any) => {
+      callback(event.payload);
+    });
+  }
+
+  static onSubscriptionStats(callback: (payload: SubscriptionStats) => void) {
+    return listen('subscription-stats', (event: any) => {
+      callback(event.payload);
+    });
+  }
+
+  static onWriteProgress(callback: (payload: WriteProgressEvent) => void) {
+    return listen('write-progress', (event: any) => {
+      callback(event.payload);
+    });
+  }
   // 开始扫描（可传入扫描时长，单位：秒）
-  static async startScan(durationSecs?: number): Promise<void> {
+  // adapterIds 为空时使用默认适配器，['all'] 表示所有适配器同时扫描
+  static async startScan(durationSecs?: number, adapterIds?: string[]): Promise<void> {
     const hasValue = typeof durationSecs === 'number' && !Number.isNaN(durationSecs as number);
+    const adapters = adapterIds && adapterIds.length > 0 ? { adapter_ids: adapterIds } : {};
     if (!hasValue) {
-      return await invoke('start_scan', {} as any);
+      return await invoke('start_scan', { args: adapters } as any);
     }
     const v = Math.max(0, Math.floor(durationSecs as number));
     // 同时传顶层和封装参数，确保后端任一路径都能拿到值
     return await invoke('start_scan', {
       duration_secs: v,
-      args: { duration_secs: v },
+      args: { duration_secs: v, ...adapters },
     } as any);
   }
 
+  // 设置后端扫描过滤器（传 null 清除），扫描中修改立即生效
+  static async setScanFilter(filter: ScanFilterSpec | null): Promise<void> {
+    return await invoke('set_scan_filter', { filter });
+  }
+
+  static async getScanFilter(): Promise<ScanFilterSpec | null> {
+    return await invoke('get_scan_filter');
+  }
+
   // 停止扫描
   static async stopScan(): Promise<void> {
     return await invoke('stop_scan');
@@ -78,12 +269,12 @@ export class BleService {
   }
 
   // 连接设备
-  static async connectDevice(deviceId: string): Promise<void> {
+  static async connectDevice(deviceId: string, adapterId?: string): Promise<void> {
     console.log('BleService.connectDevice 被调用，设备ID:', deviceId);
     
     try {
       // 尝试使用驼峰命名的参数名
-  await invoke('connect_device', { deviceId: deviceId });
+  await invoke('connect_device', { deviceId: deviceId, adapterId });
   console.log('connect_device 调用成功');
   return;
     } catch (error) {
@@ -107,6 +298,15 @@ export class BleService {
     }
   }
 
+  // 配对 / 解除配对
+  static async pairDevice(deviceId: string): Promise<void> {
+    return await invoke('pair_device', { deviceId });
+  }
+
+  static async unpairDevice(deviceId: string): Promise<void> {
+    return await invoke('unpair_device', { deviceId });
+  }
+
   // 检查设备连接状态
   static async checkDeviceConnection(deviceId: string): Promise<boolean> {
     return await invoke('check_device_connection', { deviceId: deviceId });
@@ -119,9 +319,137 @@ export class BleService {
     });
   }
 
+  // 获取设备连接状态机的当前状态
+  static async getConnectionState(deviceId: string): Promise<ConnectionInfo> {
+    return await invoke('get_connection_state', { deviceId });
+  }
+
+  static async listConnections(): Promise<ConnectionInfo[]> {
+    return await invoke('list_connections');
+  }
+
+  // 自动重连策略；不传 deviceId 时为全局默认
+  static async getReconnectPolicy(deviceId?: string): Promise<ReconnectPolicy> {
+    return await invoke('get_reconnect_policy', { deviceId });
+  }
+
+  static async setReconnectPolicy(policy: ReconnectPolicy, deviceId?: string): Promise<void> {
+    return await invoke('set_reconnect_policy', { deviceId, policy });
+  }
+
+  // 设备事件合并窗口与计数
+  static async getCoalesceSettings(): Promise<CoalesceSettings> {
+    return await invoke('get_coalesce_settings');
+  }
+
+  static async setCoalesceSettings(settings: CoalesceSettings): Promise<void> {
+    return await invoke('set_coalesce_settings', { settings });
+  }
+
+  static async getEventStats(): Promise<EventStats> {
+    return await invoke('get_event_stats');
+  }
+
+  // 公司 ID 与特征名称表（后端内置），整个会话只请求一次
+  private static assignedNumbers?: Promise<AssignedNumbers>;
+
+  static getAssignedNumbers(): Promise<AssignedNumbers> {
+    if (!BleService.assignedNumbers) {
+      BleService.assignedNumbers = invoke<AssignedNumbers>('get_assigned_numbers').catch((e) => {
+        BleService.assignedNumbers = undefined;
+        throw e;
+      });
+    }
+    return BleService.assignedNumbers;
+  }
+
+  // 厂商数据解码器
+  static async listDecoders(): Promise<DecoderInfo[]> {
+    return await invoke('list_decoders');
+  }
+
+  // 重新加载解码器布局文件
+  static async reloadDecoders(): Promise<DecoderInfo[]> {
+    return await invoke('reload_decoders');
+  }
+
+  // 设备老化策略
+  static async getAgingPolicy(): Promise<AgingPolicy> {
+    return await invoke('get_aging_policy');
+  }
+
+  static async setAgingPolicy(policy: AgingPolicy): Promise<void> {
+    return await invoke('set_aging_policy', { policy });
+  }
+
+  // RSSI 序列、平滑值与距离估计
+  static async getRssiHistory(deviceId: string): Promise<RssiHistory> {
+    return await invoke('get_rssi_history', { deviceId });
+  }
+
+  static async getRssiSettings(): Promise<RssiSettings> {
+    return await invoke('get_rssi_settings');
+  }
+
+  static async setRssiSettings(settings: RssiSettings): Promise<void> {
+    return await invoke('set_rssi_settings', { settings });
+  }
+
+  // 设备位于已知距离（米）时调用，校准参考功率或环境因子
+  static async calibrateDistance(deviceId: string, meters: number): Promise<RssiSettings> {
+    return await invoke('calibrate_distance', { deviceId, meters });
+  }
+
+  // 设备历史
+  static async queryHistory(query: HistoryQuery = {}): Promise<HistoryDevice[]> {
+    return await invoke('query_history', { query });
+  }
+
+  static async getDeviceHistory(address: string, since?: number, limit?: number): Promise<DeviceHistory> {
+    return await invoke('get_device_history', { address, since, limit });
+  }
+
+  static async pruneHistory(maxAgeDays: number): Promise<PruneSummary> {
+    return await invoke('prune_history', { maxAgeDays });
+  }
+
   // 监听设备连接状态变化（跨窗口同步连接/断开）
-  static onDeviceConnectionChanged(callback: (payload: { deviceId: string; paired: boolean }) => void) {
-    return listen<{ deviceId: string; paired: boolean }>('device-connection-changed', (event) => {
+  static onDeviceConnectionChanged(callback: (payload: ConnectionChangedEvent) => void) {
+    return listen<ConnectionChangedEvent>('device-connection-changed', (event) => {
+      callback(event.payload);
+    });
+  }
+
+  // 监听配对/绑定状态变化
+  static onDevicePairingChanged(callback: (payload: PairingChangedEvent) => void) {
+    return listen<PairingChangedEvent>('device-pairing-changed', (event) => {
+      callback(event.payload);
+    });
+  }
+
+  // 监听设备超时未出现（stale）与移出列表（lost）
+  static onDeviceStale(callback: (payload: DeviceAgingEvent) => void) {
+    return listen<DeviceAgingEvent>('device-stale', (event) => {
+      callback(event.payload);
+    });
+  }
+
+  static onDeviceLost(callback: (payload: DeviceAgingEvent) => void) {
+    return listen<DeviceAgingEvent>('device-lost', (event) => {
+      callback(event.payload);
+    });
+  }
+
+  // 监听批量设备更新（开启合并时替代 device-discovered）
+  static onDevicesUpdated(callback: (batch: DevicesUpdatedEvent) => void) {
+    return listen<DevicesUpdatedEvent>('devices-updated', (event) => {
+      callback(event.payload);
+    });
+  }
+
+  // 监听周期性 RSSI 聚合（仅包含上次推送后有新采样的设备）
+  static onRssiSummary(callback: (summaries: RssiSummary[]) => void) {
+    return listen<RssiSummary[]>('rssi-summary', (event) => {
       callback(event.payload);
     });
   }
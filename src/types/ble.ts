@@ -2,20 +2,497 @@ export interface BleDevice {
   identifier: string;
   name?: string;
   address: string;
-  rssi?: number;
+  rssi?: number; // 主适配器上报的值
+  rssi_by_adapter?: Record<string, number>; // 各适配器最近一次上报的 RSSI
   tx_power?: number;
   connectable: boolean;
-  paired: boolean;
+  connected: boolean; // 当前是否已连接
+  paired: boolean; // 协议栈报告的配对状态
+  bonded: boolean; // 系统中是否保存了绑定信息
   manufacturer_data: Record<string, string>;
+  manufacturer_names?: Record<string, string>; // 公司 ID -> SIG 公司名称
   services: string[];
+  service_names?: Record<string, string>; // 服务 UUID -> SIG 服务名称
+  service_data?: Record<string, string>; // 服务 UUID（128 位大写）-> 十六进制服务数据
   adv_data?: Record<string, string>; // 广播数据，包含完整的 Advertisement Data
-  raw_adv_data?: string; // 原始广播数据的十六进制字符串
+  raw_adv_data?: string; // 由观测字段重建的广播数据十六进制字符串
+  adv_fields?: AdvField[]; // 各广播字段的来源
+  adapter_id?: string; // 最近上报该设备的适配器
+  seen_by?: string[]; // 扫描到该设备的所有适配器
+  last_seen?: number; // 最近一次收到广播的时间（Unix 毫秒）
+  stale?: boolean; // 超过 stale 超时未再出现
+  beacon?: Beacon | null; // 识别出的信标
+  decoded?: DecodedData[]; // 厂商数据解码结果
+}
+
+// 厂商数据解码器输出的字段
+export interface DecodedField {
+  name: string;
+  value: number | string | null;
+  unit: string | null;
+}
+
+export interface DecodedData {
+  company_id: number;
+  decoder: string;
+  fields: DecodedField[];
+}
+
+export interface DecoderInfo {
+  name: string;
+  companyId: number;
+  prefix: string;
+  source: 'builtin' | 'layout';
+}
+
+// Eddystone-TLM 遥测帧
+export interface EddystoneTlm {
+  version: number;
+  battery_mv: number | null;
+  temperature: number | null; // 摄氏度
+  adv_count: number;
+  uptime_secs: number;
+}
+
+// 后端解码的信标，按 type 区分
+export type Beacon =
+  | { type: 'ibeacon'; uuid: string; major: number; minor: number; measured_power: number }
+  | { type: 'altbeacon'; manufacturer_id: number; uuid: string; major: number; minor: number; measured_power: number; reserved: number }
+  | {
+      type: 'eddystone';
+      tx_power: number | null;
+      uid: { namespace: string; instance: string } | null;
+      url: string | null;
+      tlm: EddystoneTlm | null;
+      eid: string | null;
+    };
+
+export interface AdvField {
+  field: string;
+  source: 'observed' | 'unavailable';
+  value?: string;
 }
 
 export interface BleAdapter {
   identifier: string;
   address: string;
   powered: boolean;
+  selected?: boolean; // 是否为默认适配器
+}
+
+export type ConnectionState = 'disconnected' | 'connecting' | 'connected' | 'disconnecting' | 'failed';
+
+// device-connection-changed 事件负载
+export interface ConnectionChangedEvent {
+  deviceId: string;
+  state: ConnectionState;
+  previous: ConnectionState;
+  reason: string; // user | callback | link-lost | reconnect | reconnect-failed | max-attempts | connect-failed | disconnect-failed
+  adapterId: string;
+  attempt: number;
+  error?: string | null;
+  connected: boolean;
+}
+
+// device-pairing-changed 事件负载
+export interface PairingChangedEvent {
+  deviceId: string;
+  paired: boolean;
+  bonded: boolean;
+}
+
+// devices-updated：合并窗口内的新设备（完整记录）与已有设备的变化字段
+export interface DevicesUpdatedEvent {
+  added: BleDevice[];
+  updated: { identifier: string; changes: Partial<BleDevice> }[];
+}
+
+export interface CoalesceSettings {
+  window_ms: number; // 0 表示关闭合并，每次广播推送 device-discovered
+}
+
+// SIG 分配编号名称表，key 为 4 位大写十六进制
+export interface AssignedNumbers {
+  companies: Record<string, string>;
+  characteristics: Record<string, string>;
+}
+
+export interface EventStats {
+  received: number;
+  filtered: number;
+  coalesced: number;
+  dropped: number;
+  batches: number;
+  added: number;
+  updated: number;
+}
+
+// 后端扫描过滤器，在 Rust 扫描循环中求值
+export interface ManufacturerFilter {
+  company_id: number;
+  data?: string; // 十六进制前缀
+  mask?: string; // 十六进制，长度与 data 相同
+}
+
+export interface ScanFilterSpec {
+  name?: string; // 正则，匹配名称或 identifier
+  address_prefix?: string;
+  service_uuids?: string[];
+  manufacturer?: ManufacturerFilter;
+  min_rssi?: number;
+  connectable_only?: boolean;
+}
+
+// device-stale / device-lost 事件
+export interface DeviceAgingEvent {
+  deviceId: string;
+  address: string;
+  lastSeen: number;
+}
+
+// 设备老化策略，超时为 0 表示关闭该阶段
+export interface AgingPolicy {
+  stale_after_secs: number;
+  lost_after_secs: number;
+}
+
+// RSSI 时间序列（时间戳为 Unix 毫秒）
+export interface RssiSettings {
+  capacity: number;
+  smoothing: 'kalman' | 'ema';
+  ema_alpha: number;
+  kalman_q: number;
+  kalman_r: number;
+  path_loss_exponent: number; // 环境因子
+  measured_power: number | null; // 1 米处参考 RSSI，为空时由 tx_power 推算
+  emit_interval_ms: number;
+}
+
+export interface RssiStats {
+  count: number;
+  min: number;
+  max: number;
+  mean: number;
+  variance: number;
+}
+
+// rssi-summary 事件的元素
+export interface RssiSummary {
+  deviceId: string;
+  adapterId: string; // 序列所用的适配器
+  rssi: number;
+  smoothed: number;
+  stats: RssiStats;
+  distance: number | null; // 米
+}
+
+export interface RssiHistory extends RssiSummary {
+  samples: { timestamp: number; rssi: number }[];
+}
+
+export interface ConnectionInfo {
+  deviceId: string;
+  state: ConnectionState;
+  adapterId: string;
+  autoReconnect: boolean;
+  attempt: number;
+  lastError?: string | null;
+}
+
+// 自动重连策略
+export interface ReconnectPolicy {
+  enabled: boolean;
+  initial_delay_ms: number;
+  max_delay_ms: number;
+  multiplier: number;
+  max_attempts: number; // 0 表示不限次数
+  reconnect_on_advertisement: boolean;
+}
+
+// 特征呈现格式描述符 (0x2904)
+export interface PresentationFormat {
+  format: number;
+  formatName: string | null;
+  exponent: number;
+  unit: number;
+  unitSymbol: string | null;
+  namespace: number;
+  description: number;
+}
+
+export type SecurityLevel = 'none' | 'encryption' | 'authentication' | 'authorization';
+
+export interface SecurityRequirements {
+  read: SecurityLevel;
+  write: SecurityLevel;
+}
+
+export interface GattDescriptor {
+  uuid: string;
+  name?: string | null; // SIG 描述符名称
+  value?: string | null; // 枚举时预读的元数据描述符值（十六进制）
+}
+
+export interface GattCharacteristic {
+  uuid: string;
+  name?: string | null; // SIG 特征名称（后端解析）
+  properties: string[];
+  property_bits?: number; // 特征声明属性位
+  extended_properties?: number | null; // 0x2900 描述符值
+  security?: SecurityRequirements | null; // 后端提供时的读写安全要求
+  descriptors?: GattDescriptor[];
+  user_description?: string | null; // 0x2901
+  presentation_format?: PresentationFormat | null; // 0x2904
+}
+
+// 标准特征值解码结果（后端按特征 UUID 解析），按 type 区分
+export type DecodedValue =
+  | { type: 'text'; value: string }
+  | { type: 'scalar'; value: number; unit: string }
+  | { type: 'enumerated'; value: number; name: string | null }
+  | { type: 'heartRate'; bpm: number; sensorContact: boolean | null; energyExpended: number | null; rrIntervals: number[] }
+  | { type: 'temperature'; value: number | null; unit: string; timestamp: string | null; temperatureType: { value: number; name: string | null } | null }
+  | {
+      type: 'bloodPressure';
+      systolic: number | null;
+      diastolic: number | null;
+      meanArterialPressure: number | null;
+      unit: string;
+      timestamp: string | null;
+      pulseRate: number | null;
+      userId: number | null;
+      measurementStatus: number | null;
+    }
+  | { type: 'csc'; wheelRevolutions: number | null; lastWheelEventTime: number | null; crankRevolutions: number | null; lastCrankEventTime: number | null }
+  | { type: 'rsc'; speed: number; cadence: number; strideLength: number | null; totalDistance: number | null; running: boolean }
+  | { type: 'appearance'; value: number; name: string | null }
+  | { type: 'pnpId'; vendorIdSource: number; vendorId: number; vendorName: string | null; productId: number; productVersion: number };
+
+// 读取结果：大写十六进制 + 可选的解码结果
+export interface CharacteristicValue {
+  value: string;
+  decoded: DecodedValue | null;
+}
+
+export interface CharacteristicValueEvent extends CharacteristicValue {
+  deviceId: string;
+  serviceUuid: string;
+  characteristicUuid: string;
+  kind: 'notify' | 'indicate';
+  timestamp?: number; // Unix 毫秒
+  replay?: boolean; // 来自录制文件回放
+}
+
+// 通知缓冲区 / 录制文件中的一条记录
+export interface NotificationRecord {
+  timestamp: number; // Unix 毫秒
+  deviceId: string;
+  serviceUuid: string;
+  characteristicUuid: string;
+  kind: 'notify' | 'indicate';
+  value: string; // 十六进制
+}
+
+export type RecordingFormat = 'csv' | 'jsonl' | 'binary';
+
+export interface RecordingStatus {
+  path: string;
+  format: RecordingFormat;
+  deviceId: string | null;
+  startedAt: number;
+  records: number;
+}
+
+export interface ReplayCompletedEvent {
+  path: string;
+  records: number;
+}
+
+export interface SubscriptionInfo {
+  deviceId: string;
+  serviceUuid: string;
+  characteristicUuid: string;
+  kind: 'notify' | 'indicate';
+  createdAt: number; // Unix 毫秒
+  messages: number;
+  lastValue: string | null; // 十六进制
+  lastReceivedAt: number | null;
+  active: boolean; // 链路断开后为 false，重连时自动恢复
+  restored: number;
+  lastError: string | null;
+}
+
+export interface SubscriptionRestoredEvent {
+  deviceId: string;
+  serviceUuid: string;
+  characteristicUuid: string;
+  kind: 'notify' | 'indicate';
+  error: string | null;
+}
+
+// 负载中的序号字段："<偏移>:<无符号类型>"，如 "0:u8"、"2:u16le"
+export type SequenceField = string;
+
+// 订阅吞吐统计，也是 subscription-stats 事件的负载
+export interface SubscriptionStats {
+  deviceId: string;
+  serviceUuid: string;
+  characteristicUuid: string;
+  kind: 'notify' | 'indicate';
+  active: boolean;
+  elapsedMs: number;
+  packets: number;
+  bytes: number;
+  windowMs: number; // 速率按最近 windowMs 计算
+  packetsPerSec: number;
+  bytesPerSec: number;
+  meanIntervalMs: number | null;
+  minIntervalMs: number | null;
+  maxIntervalMs: number | null;
+  jitterMs: number;
+  sequence: SequenceField | null;
+  gaps: number;
+  lostPackets: number;
+  duplicates: number;
+  outOfOrder: number;
+  unreadable: number; // 负载过短、读不到序号的包
+}
+
+export interface StatsSettings {
+  interval_ms: number; // subscription-stats 推送间隔，0 表示不推送
+  window_ms: number;
+}
+
+// 写入数据编码：hex / base64 / utf8 / auto（按 HEX 或文本猜测），数值类型 u8 i8 u16le u16be ... i64be f32le f32be f64le f64be
+// （多个值以空格或逗号分隔），或 struct:u8,u16le,hex 字段模板；缺省时按 UTF-8
+export type PayloadEncoding = string;
+
+// 长值写入：chunked 按 MTU - 3 分块；prepared / reliable 使用 Prepare/Execute Write（总长不超过 512 字节）
+export type LongWriteMode = 'chunked' | 'prepared' | 'reliable';
+
+export interface LongWriteOptions {
+  mode?: LongWriteMode;
+  without_response?: boolean; // 仅 chunked
+  chunk_interval_ms?: number; // 写命令之间的间隔
+  verify?: boolean; // 写后回读比对
+  encoding?: PayloadEncoding;
+}
+
+export interface LongWriteResult {
+  mode: LongWriteMode;
+  totalBytes: number;
+  chunkSize: number;
+  chunks: number;
+  elapsedMs: number;
+  verified: boolean | null;
+}
+
+export interface WriteProgressEvent {
+  deviceId: string;
+  serviceUuid: string;
+  characteristicUuid: string;
+  chunk: number;
+  chunks: number;
+  bytesWritten: number;
+  totalBytes: number;
+}
+
+export interface GattService {
+  uuid: string;
+  name?: string | null; // SIG 服务名称（后端解析）
+  characteristics: GattCharacteristic[];
+}
+
+// GATT 快照（可导出为 JSON / YAML），values 的 key 为 服务UUID/特征UUID
+export interface GattSnapshot {
+  version: number;
+  deviceId: string;
+  address: string;
+  name?: string | null;
+  takenAt: number; // Unix 毫秒
+  services: GattService[];
+  values: Record<string, string>;
+}
+
+export interface SnapshotChange {
+  kind: 'added' | 'removed' | 'changed';
+  path: string; // 服务UUID[/特征UUID[/描述符UUID]]
+  field?: string | null;
+  old?: unknown;
+  new?: unknown;
+}
+
+export interface SnapshotDiff {
+  identical: boolean;
+  changes: SnapshotChange[];
+}
+
+// 设备历史（持久化在 SQLite 中，时间戳为 Unix 毫秒）
+export interface HistoryQuery {
+  address?: string; // 地址前缀
+  company_id?: number;
+  name?: string;
+  since?: number;
+  limit?: number;
+}
+
+export interface NameRecord {
+  name: string;
+  firstSeen: number;
+  lastSeen: number;
+}
+
+export interface ManufacturerRevision {
+  companyId: number;
+  data: string;
+  firstSeen: number;
+  lastSeen: number;
+}
+
+export interface ServiceDataRevision {
+  uuid: string;
+  data: string;
+  firstSeen: number;
+  lastSeen: number;
+}
+
+export interface HistoryDevice {
+  address: string;
+  identifier: string;
+  firstSeen: number;
+  lastSeen: number;
+  sightings: number;
+  lastRssi: number | null;
+  connectable: boolean;
+  names: NameRecord[];
+  manufacturerData: ManufacturerRevision[];
+  serviceData: ServiceDataRevision[];
+}
+
+export interface RssiSample {
+  timestamp: number;
+  rssi: number;
+  adapterId: string;
+}
+
+export interface GattRevision {
+  timestamp: number;
+  services: GattService[];
+}
+
+export interface DeviceHistory {
+  device: HistoryDevice;
+  rssi: RssiSample[];
+  gatt: GattRevision[];
+}
+
+export interface PruneSummary {
+  devices: number;
+  rssiSamples: number;
+  names: number;
+  manufacturerData: number;
+  serviceData: number;
+  gattTables: number;
 }
 
 export enum ScanStatus {
@@ -1,5 +1,6 @@
 // BLE 工具函数
 import { getCharNameFromCache } from './characteristicNamesLoader';
+import { DecodedValue } from '../types/ble';
 
 // 常见的 BLE 服务 UUID 映射
 const WELL_KNOWN_SERVICES: Record<string, string> = {
@@ -215,4 +216,21 @@ export function getCharacteristicName(uuid: string): string {
     return `Characteristic ${upperUuid.substring(0, 8)}...`;
   }
   return `Characteristic ${uuid}`;
-}
\ No newline at end of file
+}
+
+/**
+ * 将后端解码的特征值展开为 [字段, 显示值] 列表，跳过空字段
+ */
+export function decodedValueEntries(decoded: DecodedValue): [string, string][] {
+  const unit = 'unit' in decoded ? decoded.unit : '';
+  return Object.entries(decoded)
+    .filter(([key, v]) => key !== 'type' && key !== 'unit' && v !== null && !(Array.isArray(v) && v.length === 0))
+    .map(([key, v]) => {
+      if (Array.isArray(v)) return [key, v.join(', ')];
+      if (typeof v === 'object') return [key, v.name ?? String(v.value)];
+      // 单位只附加在主数值上
+      if (key === 'value' && typeof v === 'number' && unit) return [key, `${v} ${unit}`];
+      if (unit && ['systolic', 'diastolic', 'meanArterialPressure'].includes(key)) return [key, `${v} ${unit}`];
+      return [key, String(v)];
+    });
+}
@@ -1,4 +1,6 @@
-// 动态加载 GATT Characteristic 名称映射，便于维护与替换数据文件
+// 加载 GATT Characteristic 名称映射；清单由后端内置（src-tauri/core/data）
+
+import BleService from '../services/bleService';
 
 export type CharMap = Record<string, string>;
 
@@ -17,24 +19,6 @@ export function normalizeCharUuid(id: string): { short?: string; full?: string }
   return {};
 }
 
-function parseTxt(text: string): CharMap {
-  const map: CharMap = {};
-  const lines = text.split(/\r?\n/);
-  for (const raw of lines) {
-    const line = raw.trim();
-    if (!line || line.startsWith('#') || line.startsWith('UUID') || line.startsWith('//')) continue;
-    // 形如：0x2A00<TAB>Device Name 或 2A00<TAB>Device Name
-    let m = line.match(/^0x([0-9A-Fa-f]{4})\s+(.+?)\s*$/);
-    if (!m) m = line.match(/^([0-9A-Fa-f]{4})\s+(.+?)\s*$/);
-    if (m) {
-      const code = m[1].toUpperCase();
-      const name = m[2].trim();
-      map[code] = name;
-    }
-  }
-  return map;
-}
-
 let CHAR_MAP: CharMap = {
   // 内置常用种子
   '2A00': 'Device Name',
@@ -56,26 +40,8 @@ let loadStarted = false;
 
 export async function loadCharacteristicNames(): Promise<CharMap> {
   try {
-    const res = await fetch('characteristic_names.json');
-    if (res.ok) {
-      const data = await res.json();
-      const m: CharMap = {};
-      if (Array.isArray(data)) {
-        for (const [id, name] of data) m[String(id).toUpperCase()] = String(name);
-      } else if (data && typeof data === 'object') {
-        for (const [id, name] of Object.entries(data)) m[id.toUpperCase()] = String(name);
-      }
-      CHAR_MAP = { ...CHAR_MAP, ...m };
-      return CHAR_MAP;
-    }
-  } catch {}
-
-  try {
-    const resTxt = await fetch('characteristic_names.txt');
-    if (resTxt.ok) {
-      const txt = await resTxt.text();
-      CHAR_MAP = { ...CHAR_MAP, ...parseTxt(txt) };
-    }
+    const { characteristics } = await BleService.getAssignedNumbers();
+    CHAR_MAP = { ...CHAR_MAP, ...characteristics };
   } catch {}
   return CHAR_MAP;
 }
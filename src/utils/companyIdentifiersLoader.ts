@@ -1,4 +1,6 @@
-// 动态加载 Bluetooth SIG 厂商编号映射，便于后期维护与替换数据文件。
+// 加载 Bluetooth SIG 厂商编号映射；清单由后端内置（src-tauri/core/data），与设备记录中的 manufacturer_names 同源。
+
+import BleService from '../services/bleService';
 
 export type CompanyMap = Record<string, string>;
 
@@ -16,26 +18,6 @@ function addToMap(map: CompanyMap, id: string, name: string) {
   map[short] = name;
 }
 
-function parseTxt(text: string): CompanyMap {
-  const map: CompanyMap = {};
-  const lines = text.split(/\r?\n/);
-  for (const raw of lines) {
-    const line = raw.trim();
-    if (!line || line.startsWith('#') || line.startsWith('//')) continue;
-    // 支持两种格式：
-    // 1) 0x004C<TAB>Name
-    // 2) 004C<TAB>Name
-    let m = line.match(/^0x([0-9A-Fa-f]{4})\s+(.+?)\s*$/);
-    if (!m) m = line.match(/^([0-9A-Fa-f]{4})\s+(.+?)\s*$/);
-    if (m) {
-      const code = m[1];
-      const name = m[2].replace(/\s+$/, '');
-      addToMap(map, code, name);
-    }
-  }
-  return map;
-}
-
 // 全局缓存，模块加载后自动尝试预加载（不阻塞）
 function buildSeed(): CompanyMap {
   const seedList: Array<[string, string]> = [
@@ -57,39 +39,14 @@ function buildSeed(): CompanyMap {
 let COMPANY_MAP: CompanyMap = buildSeed();
 let loadOnceStarted = false;
 
-async function tryFetch(url: string): Promise<string | null> {
-  try {
-    const res = await fetch(url);
-    if (!res.ok) return null;
-    return await res.text();
-  } catch {
-    return null;
-  }
-}
-
 export async function loadCompanyIdentifiers(): Promise<CompanyMap> {
-  // 优先 JSON（可直接是 {"004C":"Apple, Inc."} 映射，或数组 [["004C","Apple, Inc."]]）
   try {
-    const res = await fetch('company_identifiers.json');
-    if (res.ok) {
-      const data = await res.json();
-      const map: CompanyMap = {};
-      if (Array.isArray(data)) {
-        for (const [id, name] of data) addToMap(map, String(id), String(name));
-      } else if (data && typeof data === 'object') {
-        for (const [id, name] of Object.entries(data)) addToMap(map, id, String(name));
-      }
-      COMPANY_MAP = map;
-      return COMPANY_MAP;
-    }
+    const { companies } = await BleService.getAssignedNumbers();
+    const map: CompanyMap = {};
+    for (const [id, name] of Object.entries(companies)) addToMap(map, id, name);
+    COMPANY_MAP = map;
   } catch {
-    // 忽略，降级到 txt
-  }
-
-  // 退回 TXT（你可直接把 SIG 提供的清单放在 public/company_identifiers.txt）
-  const txt = await tryFetch('company_identifiers.txt');
-  if (txt) {
-    COMPANY_MAP = parseTxt(txt);
+    // 获取失败时保留内置种子
   }
   return COMPANY_MAP;
 }